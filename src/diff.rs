@@ -0,0 +1,1003 @@
+use crate::encoding::encode_html_entities;
+use std::collections::HashMap;
+
+/// The unit a text is split into before diffing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    /// Lines, each including its trailing newline.
+    Line,
+    /// Runs of word characters, runs of whitespace and single punctuation characters.
+    Word,
+    /// Unicode scalar values.
+    Char,
+    /// Approximate extended grapheme clusters (base character plus combining marks, joiners and modifiers).
+    Grapheme,
+}
+
+/// The algorithm used to compute the edit script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Myers' O(ND) shortest edit script.
+    Myers,
+    /// Patience diff: anchors on tokens unique to both sides, Myers in between.
+    Patience,
+    /// Histogram diff: anchors on the least frequent common tokens, Myers as fallback.
+    Histogram,
+}
+
+/// One segment of a diff between two texts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    /// Text present in both inputs.
+    Equal(String),
+    /// Text only present in the old input.
+    Delete(String),
+    /// Text only present in the new input.
+    Insert(String),
+}
+
+/// A single token-level edit, indexing into the old and new token lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+fn is_extending(c: char) -> bool {
+    matches!(c as u32,
+        0x0300..=0x036F
+        | 0x0483..=0x0489
+        | 0x0591..=0x05BD
+        | 0x0610..=0x061A
+        | 0x064B..=0x065F
+        | 0x0900..=0x0903
+        | 0x093A..=0x094F
+        | 0x1AB0..=0x1AFF
+        | 0x1DC0..=0x1DFF
+        | 0x200C..=0x200D
+        | 0x20D0..=0x20FF
+        | 0xFE00..=0xFE0F
+        | 0xFE20..=0xFE2F
+        | 0x1F3FB..=0x1F3FF
+        | 0xE0020..=0xE007F
+        | 0xE0100..=0xE01EF)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+fn graphemes(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut chars = text.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let mut end = start + c.len_utf8();
+        let mut prev = c;
+        let mut regional_pair = is_regional_indicator(c);
+        while let Some(&(i, next)) = chars.peek() {
+            let joins = is_extending(next)
+                || prev == '\u{200D}'
+                || (prev == '\r' && next == '\n')
+                || (regional_pair && is_regional_indicator(next));
+            if !joins {
+                break;
+            }
+            regional_pair = false;
+            prev = next;
+            end = i + next.len_utf8();
+            chars.next();
+        }
+        tokens.push(&text[start..end]);
+    }
+    tokens
+}
+
+fn words(text: &str) -> Vec<&str> {
+    #[derive(PartialEq)]
+    enum Class {
+        Word,
+        Space,
+        Other,
+    }
+    let class = |c: char| {
+        if c.is_alphanumeric() || c == '_' {
+            Class::Word
+        } else if c.is_whitespace() {
+            Class::Space
+        } else {
+            Class::Other
+        }
+    };
+
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut current: Option<Class> = None;
+    for (i, c) in text.char_indices() {
+        let cls = class(c);
+        let split = match &current {
+            Some(prev) => *prev != cls || cls == Class::Other,
+            None => false,
+        };
+        if split {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        current = Some(cls);
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// Splits a text into diff tokens at the given granularity.
+///
+/// Concatenating the tokens always reproduces the input exactly.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to split.
+/// * `granularity` - The unit to split the text into.
+///
+/// # Returns
+///
+/// * A `Vec<&str>` containing the tokens in order.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::diff::{tokenize, Granularity};
+///
+/// let result = tokenize("hello, world", Granularity::Word);
+/// assert_eq!(result, vec!["hello", ",", " ", "world"]);
+/// ```
+pub fn tokenize(text: &str, granularity: Granularity) -> Vec<&str> {
+    match granularity {
+        Granularity::Line => text.split_inclusive('\n').collect(),
+        Granularity::Word => words(text),
+        Granularity::Char => text.char_indices().map(|(i, c)| &text[i..i + c.len_utf8()]).collect(),
+        Granularity::Grapheme => graphemes(text),
+    }
+}
+
+/// Maps tokens of both sides to shared integer ids so the algorithms compare integers.
+fn intern(a: &[&str], b: &[&str]) -> (Vec<u32>, Vec<u32>) {
+    let mut ids: HashMap<&str, u32> = HashMap::new();
+    let mut id_of = |t| {
+        let next = ids.len() as u32;
+        *ids.entry(t).or_insert(next)
+    };
+    let a = a.iter().map(|&t| id_of(t)).collect();
+    let b = b.iter().map(|&t| id_of(t)).collect();
+    (a, b)
+}
+
+fn shift(edits: Vec<Edit>, da: usize, db: usize) -> impl Iterator<Item = Edit> {
+    edits.into_iter().map(move |e| match e {
+        Edit::Equal(i, j) => Edit::Equal(i + da, j + db),
+        Edit::Delete(i) => Edit::Delete(i + da),
+        Edit::Insert(j) => Edit::Insert(j + db),
+    })
+}
+
+/// A Myers frontier indexed by diagonal `k`, which may be negative.
+struct Frontier {
+    offset: isize,
+    v: Vec<usize>,
+}
+
+impl Frontier {
+    fn new(max_d: usize) -> Self {
+        Frontier {
+            offset: max_d as isize + 1,
+            v: vec![0; 2 * max_d + 3],
+        }
+    }
+}
+
+impl std::ops::Index<isize> for Frontier {
+    type Output = usize;
+
+    fn index(&self, k: isize) -> &usize {
+        &self.v[(k + self.offset) as usize]
+    }
+}
+
+impl std::ops::IndexMut<isize> for Frontier {
+    fn index_mut(&mut self, k: isize) -> &mut usize {
+        &mut self.v[(k + self.offset) as usize]
+    }
+}
+
+fn max_d(n: usize, m: usize) -> usize {
+    (n + m).div_ceil(2) + 1
+}
+
+/// Finds the start of the middle snake of an optimal path through `a × b`,
+/// searching forwards and backwards at once in O(N + M) space.
+fn middle_snake(a: &[u32], b: &[u32], vf: &mut Frontier, vb: &mut Frontier) -> Option<(usize, usize)> {
+    let (n, m) = (a.len(), b.len());
+    let delta = n as isize - m as isize;
+    let odd = delta & 1 == 1;
+    vf[1] = 0;
+    vb[1] = 0;
+    for d in 0..max_d(n, m) as isize {
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && vf[k - 1] < vf[k + 1]) {
+                vf[k + 1]
+            } else {
+                vf[k - 1] + 1
+            };
+            let y = (x as isize - k) as usize;
+            let (x0, y0) = (x, y);
+            if x < n && y < m {
+                x += a[x..].iter().zip(&b[y..]).take_while(|(p, q)| p == q).count();
+            }
+            vf[k] = x;
+            if odd && (k - delta).abs() < d && vf[k] + vb[-(k - delta)] >= n {
+                return Some((x0, y0));
+            }
+        }
+        for k in (-d..=d).rev().step_by(2) {
+            let mut x = if k == -d || (k != d && vb[k - 1] < vb[k + 1]) {
+                vb[k + 1]
+            } else {
+                vb[k - 1] + 1
+            };
+            let mut y = (x as isize - k) as usize;
+            if x < n && y < m {
+                let advance = a[..n - x].iter().rev().zip(b[..m - y].iter().rev()).take_while(|(p, q)| p == q).count();
+                x += advance;
+                y += advance;
+            }
+            vb[k] = x;
+            if !odd && (k - delta).abs() <= d && vb[k] + vf[-(k - delta)] >= n {
+                return Some((n - x, m - y));
+            }
+        }
+    }
+    None
+}
+
+/// Divide and conquer around the middle snake, appending edits for `a[a_range] × b[b_range]`.
+fn myers_between(
+    a: &[u32],
+    b: &[u32],
+    a_range: std::ops::Range<usize>,
+    b_range: std::ops::Range<usize>,
+    vf: &mut Frontier,
+    vb: &mut Frontier,
+    edits: &mut Vec<Edit>,
+) {
+    let (a_start, b_start) = (a_range.start, b_range.start);
+    let (old, new) = (&a[a_range.clone()], &b[b_range.clone()]);
+    let prefix = old.iter().zip(new).take_while(|(x, y)| x == y).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    edits.extend((0..prefix).map(|i| Edit::Equal(a_start + i, b_start + i)));
+
+    let (a_mid, b_mid) = (a_start + prefix..a_range.end - suffix, b_start + prefix..b_range.end - suffix);
+    if a_mid.is_empty() {
+        edits.extend(b_mid.map(Edit::Insert));
+    } else if b_mid.is_empty() {
+        edits.extend(a_mid.map(Edit::Delete));
+    } else {
+        match middle_snake(&a[a_mid.clone()], &b[b_mid.clone()], vf, vb) {
+            Some((x, y)) => {
+                let (x, y) = (a_mid.start + x, b_mid.start + y);
+                myers_between(a, b, a_mid.start..x, b_mid.start..y, vf, vb, edits);
+                myers_between(a, b, x..a_mid.end, y..b_mid.end, vf, vb, edits);
+            }
+            None => {
+                edits.extend(a_mid.map(Edit::Delete));
+                edits.extend(b_mid.map(Edit::Insert));
+            }
+        }
+    }
+
+    edits.extend((0..suffix).map(|i| Edit::Equal(a_range.end - suffix + i, b_range.end - suffix + i)));
+}
+
+/// Myers' shortest edit script, using the linear-space divide-and-conquer variant.
+fn myers(a: &[u32], b: &[u32]) -> Vec<Edit> {
+    let max_d = max_d(a.len(), b.len());
+    let mut vf = Frontier::new(max_d);
+    let mut vb = Frontier::new(max_d);
+    let mut edits = Vec::with_capacity(a.len() + b.len());
+    myers_between(a, b, 0..a.len(), 0..b.len(), &mut vf, &mut vb, &mut edits);
+    edits
+}
+
+/// Splits off the common prefix and suffix, diffs the middle with `inner`, and stitches the result.
+fn with_trimmed(a: &[u32], b: &[u32], inner: impl Fn(&[u32], &[u32]) -> Vec<Edit>) -> Vec<Edit> {
+    let prefix = a.iter().zip(b).take_while(|(x, y)| x == y).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(x, y)| x == y)
+        .count();
+    let (a_mid, b_mid) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    let mut edits: Vec<Edit> = (0..prefix).map(|i| Edit::Equal(i, i)).collect();
+    if a_mid.is_empty() {
+        edits.extend((0..b_mid.len()).map(|j| Edit::Insert(prefix + j)));
+    } else if b_mid.is_empty() {
+        edits.extend((0..a_mid.len()).map(|i| Edit::Delete(prefix + i)));
+    } else {
+        edits.extend(shift(inner(a_mid, b_mid), prefix, prefix));
+    }
+    edits.extend((0..suffix).map(|i| Edit::Equal(a.len() - suffix + i, b.len() - suffix + i)));
+    edits
+}
+
+/// Diffs the gaps between matched anchor pairs recursively with `inner`.
+fn between_anchors(
+    a: &[u32],
+    b: &[u32],
+    anchors: &[(usize, usize)],
+    inner: fn(&[u32], &[u32]) -> Vec<Edit>,
+) -> Vec<Edit> {
+    let mut edits = Vec::new();
+    let (mut ai, mut bi) = (0, 0);
+    for &(ax, bx) in anchors {
+        edits.extend(shift(with_trimmed(&a[ai..ax], &b[bi..bx], inner), ai, bi));
+        edits.push(Edit::Equal(ax, bx));
+        ai = ax + 1;
+        bi = bx + 1;
+    }
+    edits.extend(shift(with_trimmed(&a[ai..], &b[bi..], inner), ai, bi));
+    edits
+}
+
+fn patience(a: &[u32], b: &[u32]) -> Vec<Edit> {
+    let mut counts: HashMap<u32, (usize, usize, usize, usize)> = HashMap::new();
+    for (i, &t) in a.iter().enumerate() {
+        let entry = counts.entry(t).or_insert((0, 0, 0, 0));
+        entry.0 += 1;
+        entry.2 = i;
+    }
+    for (j, &t) in b.iter().enumerate() {
+        let entry = counts.entry(t).or_insert((0, 0, 0, 0));
+        entry.1 += 1;
+        entry.3 = j;
+    }
+    let mut unique: Vec<(usize, usize)> = counts
+        .values()
+        .filter(|c| c.0 == 1 && c.1 == 1)
+        .map(|c| (c.2, c.3))
+        .collect();
+    if unique.is_empty() {
+        return myers(a, b);
+    }
+    unique.sort_unstable();
+
+    // Longest increasing subsequence on the new-side positions (patience sorting).
+    let mut piles: Vec<usize> = Vec::new();
+    let mut back: Vec<Option<usize>> = vec![None; unique.len()];
+    for (idx, &(_, bj)) in unique.iter().enumerate() {
+        let pos = piles.partition_point(|&p| unique[p].1 < bj);
+        if pos > 0 {
+            back[idx] = Some(piles[pos - 1]);
+        }
+        if pos == piles.len() {
+            piles.push(idx);
+        } else {
+            piles[pos] = idx;
+        }
+    }
+    let mut anchors = Vec::new();
+    let mut cur = piles.last().copied();
+    while let Some(idx) = cur {
+        anchors.push(unique[idx]);
+        cur = back[idx];
+    }
+    anchors.reverse();
+
+    between_anchors(a, b, &anchors, patience)
+}
+
+fn histogram(a: &[u32], b: &[u32]) -> Vec<Edit> {
+    histogram_at(a, b, 0)
+}
+
+/// Histogram diff at recursion `depth`, falling back to Myers once the recursion gets too deep.
+fn histogram_at(a: &[u32], b: &[u32], depth: usize) -> Vec<Edit> {
+    const MAX_DEPTH: usize = 64;
+
+    let pivot = if depth < MAX_DEPTH { histogram_pivot(a, b) } else { None };
+    let Some((i, j, len)) = pivot else {
+        return myers(a, b);
+    };
+
+    let recurse = move |a: &[u32], b: &[u32]| histogram_at(a, b, depth + 1);
+    let mut edits = with_trimmed(&a[..i], &b[..j], recurse);
+    edits.extend((0..len).map(|k| Edit::Equal(i + k, j + k)));
+    edits.extend(shift(with_trimmed(&a[i + len..], &b[j + len..], recurse), i + len, j + len));
+    edits
+}
+
+/// Picks the common token with the fewest occurrences in the old side, preferring the one
+/// nearest the middle of the new side so the gaps stay balanced, and extends it into the
+/// longest matching region around it. Returns the region as `(old start, new start, length)`.
+fn histogram_pivot(a: &[u32], b: &[u32]) -> Option<(usize, usize, usize)> {
+    const MAX_CHAIN: usize = 64;
+
+    // Occurrence count and first position of each token in the old side.
+    let mut counts: HashMap<u32, (usize, usize)> = HashMap::new();
+    for (i, &t) in a.iter().enumerate() {
+        counts.entry(t).or_insert((0, i)).0 += 1;
+    }
+
+    let mut best: Option<(usize, usize, usize, usize)> = None;
+    for (j, t) in b.iter().enumerate() {
+        if let Some(&(count, i)) = counts.get(t) {
+            let distance = j.abs_diff(b.len() / 2);
+            if count <= MAX_CHAIN && best.is_none_or(|(c, d, _, _)| (count, distance) < (c, d)) {
+                best = Some((count, distance, i, j));
+            }
+        }
+    }
+    let (_, _, mut i, mut j) = best?;
+
+    while i > 0 && j > 0 && a[i - 1] == b[j - 1] {
+        i -= 1;
+        j -= 1;
+    }
+    let mut len = 0;
+    while i + len < a.len() && j + len < b.len() && a[i + len] == b[j + len] {
+        len += 1;
+    }
+    Some((i, j, len))
+}
+
+/// Reorders each run of changes between two equal tokens so deletions come before insertions.
+fn normalize(edits: Vec<Edit>) -> Vec<Edit> {
+    let mut result = Vec::with_capacity(edits.len());
+    let mut inserts = Vec::new();
+    for edit in edits {
+        match edit {
+            Edit::Insert(_) => inserts.push(edit),
+            Edit::Delete(_) => result.push(edit),
+            Edit::Equal(..) => {
+                result.append(&mut inserts);
+                result.push(edit);
+            }
+        }
+    }
+    result.append(&mut inserts);
+    result
+}
+
+pub(crate) fn diff_tokens(a: &[&str], b: &[&str], algorithm: Algorithm) -> Vec<Edit> {
+    let (a, b) = intern(a, b);
+    let inner = match algorithm {
+        Algorithm::Myers => myers,
+        Algorithm::Patience => patience,
+        Algorithm::Histogram => histogram,
+    };
+    normalize(with_trimmed(&a, &b, inner))
+}
+
+fn to_changes(a: &[&str], b: &[&str], edits: &[Edit]) -> Vec<Change> {
+    let mut changes: Vec<Change> = Vec::new();
+    for edit in edits {
+        match (*edit, changes.last_mut()) {
+            (Edit::Equal(i, _), Some(Change::Equal(s))) => s.push_str(a[i]),
+            (Edit::Delete(i), Some(Change::Delete(s))) => s.push_str(a[i]),
+            (Edit::Insert(j), Some(Change::Insert(s))) => s.push_str(b[j]),
+            (Edit::Equal(i, _), _) => changes.push(Change::Equal(a[i].to_string())),
+            (Edit::Delete(i), _) => changes.push(Change::Delete(a[i].to_string())),
+            (Edit::Insert(j), _) => changes.push(Change::Insert(b[j].to_string())),
+        }
+    }
+    changes
+}
+
+/// Computes the differences between two texts using the Myers algorithm.
+///
+/// Adjacent tokens of the same kind are merged into a single [`Change`].
+///
+/// # Arguments
+///
+/// * `old` - A string slice that holds the original text.
+/// * `new` - A string slice that holds the modified text.
+/// * `granularity` - The unit to compare the texts by.
+///
+/// # Returns
+///
+/// * A `Vec<Change>` that transforms `old` into `new`.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::diff::{diff, Change, Granularity};
+///
+/// let result = diff("hello world", "hello there", Granularity::Word);
+/// assert_eq!(result, vec![
+///     Change::Equal("hello ".to_string()),
+///     Change::Delete("world".to_string()),
+///     Change::Insert("there".to_string()),
+/// ]);
+/// ```
+pub fn diff(old: &str, new: &str, granularity: Granularity) -> Vec<Change> {
+    diff_with_algorithm(old, new, granularity, Algorithm::Myers)
+}
+
+/// Computes the differences between two texts using the chosen algorithm.
+///
+/// # Arguments
+///
+/// * `old` - A string slice that holds the original text.
+/// * `new` - A string slice that holds the modified text.
+/// * `granularity` - The unit to compare the texts by.
+/// * `algorithm` - The diff algorithm to use.
+///
+/// # Returns
+///
+/// * A `Vec<Change>` that transforms `old` into `new`.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::diff::{diff_with_algorithm, Algorithm, Change, Granularity};
+///
+/// let result = diff_with_algorithm("a\nb\n", "a\nc\n", Granularity::Line, Algorithm::Patience);
+/// assert_eq!(result, vec![
+///     Change::Equal("a\n".to_string()),
+///     Change::Delete("b\n".to_string()),
+///     Change::Insert("c\n".to_string()),
+/// ]);
+/// ```
+pub fn diff_with_algorithm(old: &str, new: &str, granularity: Granularity, algorithm: Algorithm) -> Vec<Change> {
+    let a = tokenize(old, granularity);
+    let b = tokenize(new, granularity);
+    let edits = diff_tokens(&a, &b, algorithm);
+    to_changes(&a, &b, &edits)
+}
+
+/// Computes how similar two texts are from their diff.
+///
+/// The ratio is `2 * M / T`, where `M` is the number of matching tokens and
+/// `T` the total number of tokens in both texts.
+///
+/// # Arguments
+///
+/// * `old` - A string slice that holds the first text.
+/// * `new` - A string slice that holds the second text.
+/// * `granularity` - The unit to compare the texts by.
+///
+/// # Returns
+///
+/// * A `f64` between 0.0 and 1.0. Two empty texts have a ratio of 1.0.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::diff::{similarity_ratio, Granularity};
+///
+/// let result = similarity_ratio("abcd", "abce", Granularity::Char);
+/// assert_eq!(result, 0.75);
+/// ```
+pub fn similarity_ratio(old: &str, new: &str, granularity: Granularity) -> f64 {
+    let a = tokenize(old, granularity);
+    let b = tokenize(new, granularity);
+    let total = a.len() + b.len();
+    if total == 0 {
+        return 1.0;
+    }
+    let matches = diff_tokens(&a, &b, Algorithm::Myers)
+        .iter()
+        .filter(|e| matches!(e, Edit::Equal(..)))
+        .count();
+    2.0 * matches as f64 / total as f64
+}
+
+fn push_line(out: &mut String, prefix: char, line: &str) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+fn hunk_range(start: usize, count: usize) -> String {
+    // An empty range refers to the line before it, as in GNU diff.
+    let start = if count == 0 { start } else { start + 1 };
+    if count == 1 {
+        start.to_string()
+    } else {
+        format!("{},{}", start, count)
+    }
+}
+
+/// Renders the line differences between two texts as a unified diff.
+///
+/// # Arguments
+///
+/// * `old` - A string slice that holds the original text.
+/// * `new` - A string slice that holds the modified text.
+/// * `old_label` - The file name shown on the `---` header line.
+/// * `new_label` - The file name shown on the `+++` header line.
+/// * `context` - The number of unchanged lines shown around each change.
+///
+/// # Returns
+///
+/// * A `String` containing the unified diff, or an empty string if the texts are identical.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::diff::unified_diff("a\nb\nc\n", "a\nx\nc\n", "old.txt", "new.txt", 1);
+/// assert_eq!(result, "--- old.txt\n+++ new.txt\n@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n");
+/// ```
+pub fn unified_diff(old: &str, new: &str, old_label: &str, new_label: &str, context: usize) -> String {
+    let a = tokenize(old, Granularity::Line);
+    let b = tokenize(new, Granularity::Line);
+    let edits = diff_tokens(&a, &b, Algorithm::Myers);
+
+    let changed: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    if changed.is_empty() {
+        return String::new();
+    }
+
+    // Group changed edits whose surrounding context would overlap.
+    let mut groups: Vec<(usize, usize)> = Vec::new();
+    for &i in &changed {
+        match groups.last_mut() {
+            Some((_, end)) if i <= *end + 2 * context + 1 => *end = i,
+            _ => groups.push((i, i)),
+        }
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+    for (first, last) in groups {
+        let start = first.saturating_sub(context);
+        let end = (last + context + 1).min(edits.len());
+        let hunk = &edits[start..end];
+
+        // Line positions before the hunk on each side.
+        let (mut old_start, mut new_start) = (0, 0);
+        for edit in &edits[..start] {
+            match edit {
+                Edit::Equal(..) => {
+                    old_start += 1;
+                    new_start += 1;
+                }
+                Edit::Delete(_) => old_start += 1,
+                Edit::Insert(_) => new_start += 1,
+            }
+        }
+        let old_count = hunk.iter().filter(|e| !matches!(e, Edit::Insert(_))).count();
+        let new_count = hunk.iter().filter(|e| !matches!(e, Edit::Delete(_))).count();
+
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_count),
+            hunk_range(new_start, new_count)
+        ));
+        for edit in hunk {
+            match *edit {
+                Edit::Equal(i, _) => push_line(&mut out, ' ', a[i]),
+                Edit::Delete(i) => push_line(&mut out, '-', a[i]),
+                Edit::Insert(j) => push_line(&mut out, '+', b[j]),
+            }
+        }
+    }
+    out
+}
+
+fn fit_column(line: &str, width: usize) -> String {
+    let line = line.trim_end_matches(['\n', '\r']);
+    let mut cell: String = line.chars().take(width).collect();
+    let len = cell.chars().count();
+    cell.extend(std::iter::repeat_n(' ', width - len));
+    cell
+}
+
+type Row<'a> = (Option<&'a str>, char, Option<&'a str>);
+
+/// Pairs up a run of deleted and inserted lines into side-by-side rows.
+fn pair_rows<'a>(rows: &mut Vec<Row<'a>>, deletes: &mut Vec<&'a str>, inserts: &mut Vec<&'a str>) {
+    let n = deletes.len().max(inserts.len());
+    for k in 0..n {
+        let row = match (deletes.get(k), inserts.get(k)) {
+            (Some(&d), Some(&i)) => (Some(d), '|', Some(i)),
+            (Some(&d), None) => (Some(d), '<', None),
+            (None, Some(&i)) => (None, '>', Some(i)),
+            (None, None) => unreachable!(),
+        };
+        rows.push(row);
+    }
+    deletes.clear();
+    inserts.clear();
+}
+
+/// Renders the line differences between two texts side by side.
+///
+/// Each row shows the old line, a marker and the new line. The marker is a
+/// space for unchanged lines, `|` for changed lines, `<` for deleted lines and
+/// `>` for inserted lines.
+///
+/// # Arguments
+///
+/// * `old` - A string slice that holds the original text.
+/// * `new` - A string slice that holds the modified text.
+/// * `width` - The width of each column in characters; longer lines are truncated.
+///
+/// # Returns
+///
+/// * A `String` containing one row per line.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::diff::side_by_side("a\nb\n", "a\nc\n", 3);
+/// assert_eq!(result, "a     a\nb   | c\n");
+/// ```
+pub fn side_by_side(old: &str, new: &str, width: usize) -> String {
+    let a = tokenize(old, Granularity::Line);
+    let b = tokenize(new, Granularity::Line);
+    let edits = diff_tokens(&a, &b, Algorithm::Myers);
+
+    let mut rows: Vec<Row> = Vec::new();
+    let mut deletes: Vec<&str> = Vec::new();
+    let mut inserts: Vec<&str> = Vec::new();
+    for edit in &edits {
+        match *edit {
+            Edit::Equal(i, j) => {
+                pair_rows(&mut rows, &mut deletes, &mut inserts);
+                rows.push((Some(a[i]), ' ', Some(b[j])));
+            }
+            Edit::Delete(i) => deletes.push(a[i]),
+            Edit::Insert(j) => inserts.push(b[j]),
+        }
+    }
+    pair_rows(&mut rows, &mut deletes, &mut inserts);
+
+    let mut out = String::new();
+    for (left, mark, right) in rows {
+        let row = format!(
+            "{} {} {}",
+            fit_column(left.unwrap_or(""), width),
+            mark,
+            fit_column(right.unwrap_or(""), width)
+        );
+        out.push_str(row.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+/// Renders changes inline with ANSI colors: deletions in red, insertions in green.
+///
+/// # Arguments
+///
+/// * `changes` - The changes to render, for example from [`diff`].
+///
+/// # Returns
+///
+/// * A `String` containing the colored text.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::diff::{diff, render_inline_ansi, Granularity};
+///
+/// let changes = diff("cat", "cut", Granularity::Char);
+/// let result = render_inline_ansi(&changes);
+/// assert_eq!(result, "c\x1b[31ma\x1b[0m\x1b[32mu\x1b[0mt");
+/// ```
+pub fn render_inline_ansi(changes: &[Change]) -> String {
+    changes
+        .iter()
+        .map(|change| match change {
+            Change::Equal(s) => s.clone(),
+            Change::Delete(s) => format!("\x1b[31m{}\x1b[0m", s),
+            Change::Insert(s) => format!("\x1b[32m{}\x1b[0m", s),
+        })
+        .collect()
+}
+
+/// Renders changes inline as HTML using `<del>` and `<ins>` elements.
+///
+/// All text is escaped with [`encode_html_entities`].
+///
+/// # Arguments
+///
+/// * `changes` - The changes to render, for example from [`diff`].
+///
+/// # Returns
+///
+/// * A `String` containing the HTML markup.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::diff::{diff, render_inline_html, Granularity};
+///
+/// let changes = diff("a < b", "a > b", Granularity::Word);
+/// let result = render_inline_html(&changes);
+/// assert_eq!(result, "a <del>&lt;</del><ins>&gt;</ins> b");
+/// ```
+pub fn render_inline_html(changes: &[Change]) -> String {
+    changes
+        .iter()
+        .map(|change| match change {
+            Change::Equal(s) => encode_html_entities(s),
+            Change::Delete(s) => format!("<del>{}</del>", encode_html_entities(s)),
+            Change::Insert(s) => format!("<ins>{}</ins>", encode_html_entities(s)),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(changes: &[Change]) -> (String, String) {
+        let mut old = String::new();
+        let mut new = String::new();
+        for change in changes {
+            match change {
+                Change::Equal(s) => {
+                    old.push_str(s);
+                    new.push_str(s);
+                }
+                Change::Delete(s) => old.push_str(s),
+                Change::Insert(s) => new.push_str(s),
+            }
+        }
+        (old, new)
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(tokenize("a\nb", Granularity::Line), vec!["a\n", "b"]);
+        assert_eq!(tokenize("hi  there!", Granularity::Word), vec!["hi", "  ", "there", "!"]);
+        assert_eq!(tokenize("héllo", Granularity::Char).len(), 5);
+        assert_eq!(tokenize("e\u{301}x", Granularity::Grapheme), vec!["e\u{301}", "x"]);
+        assert_eq!(tokenize("🇫🇷🇩🇪", Granularity::Grapheme), vec!["🇫🇷", "🇩🇪"]);
+    }
+
+    #[test]
+    fn test_diff() {
+        let result = diff("hello world", "hello there", Granularity::Word);
+        assert_eq!(
+            result,
+            vec![
+                Change::Equal("hello ".to_string()),
+                Change::Delete("world".to_string()),
+                Change::Insert("there".to_string()),
+            ]
+        );
+        assert!(diff("", "", Granularity::Char).is_empty());
+    }
+
+    #[test]
+    fn test_diff_with_algorithm() {
+        let old = "fn a() {\n  one\n}\n\nfn b() {\n  two\n}\n";
+        let new = "fn b() {\n  two\n}\n\nfn a() {\n  one\n}\n";
+        for algorithm in [Algorithm::Myers, Algorithm::Patience, Algorithm::Histogram] {
+            for granularity in [Granularity::Line, Granularity::Word, Granularity::Char] {
+                let changes = diff_with_algorithm(old, new, granularity, algorithm);
+                assert_eq!(apply(&changes), (old.to_string(), new.to_string()));
+            }
+        }
+    }
+
+    #[test]
+    fn test_myers_is_minimal() {
+        let a = tokenize("ABCABBA", Granularity::Char);
+        let b = tokenize("CBABAC", Granularity::Char);
+        let edits = diff_tokens(&a, &b, Algorithm::Myers);
+        let changes = edits.iter().filter(|e| !matches!(e, Edit::Equal(..))).count();
+        assert_eq!(changes, 5);
+
+        // Compare against the edit distance from an LCS table on pseudo-random inputs.
+        let mut seed = 7u32;
+        let mut next = || {
+            seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            ((seed >> 16) % 3) as u8 + b'a'
+        };
+        for _ in 0..50 {
+            let old: String = (0..12).map(|_| next() as char).collect();
+            let new: String = (0..9).map(|_| next() as char).collect();
+            let (a, b) = (tokenize(&old, Granularity::Char), tokenize(&new, Granularity::Char));
+            let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+            for i in (0..a.len()).rev() {
+                for j in (0..b.len()).rev() {
+                    lcs[i][j] = if a[i] == b[j] { lcs[i + 1][j + 1] + 1 } else { lcs[i + 1][j].max(lcs[i][j + 1]) };
+                }
+            }
+            let edits = diff_tokens(&a, &b, Algorithm::Myers);
+            let changes = edits.iter().filter(|e| !matches!(e, Edit::Equal(..))).count();
+            assert_eq!(changes, a.len() + b.len() - 2 * lcs[0][0], "{} -> {}", old, new);
+        }
+    }
+
+    #[test]
+    fn test_myers_large_input() {
+        // Fully different inputs are the worst case for the edit distance.
+        let old: String = (0..3_000).map(|i| format!("old {}\n", i)).collect();
+        let new: String = (0..3_000).map(|i| format!("new {}\n", i)).collect();
+        let changes = diff(&old, &new, Granularity::Line);
+        assert_eq!(changes.len(), 2);
+        assert_eq!(changes[0], Change::Delete(old.clone()));
+
+        let new: String = (0..20_000)
+            .map(|i| if i % 100 == 0 { format!("changed {}\n", i) } else { format!("line {}\n", i) })
+            .collect();
+        let old: String = (0..20_000).map(|i| format!("line {}\n", i)).collect();
+        let (a, b) = (tokenize(&old, Granularity::Line), tokenize(&new, Granularity::Line));
+        let edits = diff_tokens(&a, &b, Algorithm::Myers);
+        assert_eq!(edits.iter().filter(|e| !matches!(e, Edit::Equal(..))).count(), 400);
+    }
+
+    #[test]
+    fn test_histogram_large_input() {
+        // Swapping every adjacent pair leaves only unique, single-token matches.
+        let old: String = (0..20_000).map(|i| format!("line {}\n", i)).collect();
+        let new: String = (0..20_000).map(|i| format!("line {}\n", i ^ 1)).collect();
+        let (a, b) = (tokenize(&old, Granularity::Line), tokenize(&new, Granularity::Line));
+        let edits = diff_tokens(&a, &b, Algorithm::Histogram);
+        assert_eq!(edits.iter().filter(|e| matches!(e, Edit::Equal(..))).count(), 10_000);
+        let changes = diff_with_algorithm(&old, &new, Granularity::Line, Algorithm::Histogram);
+        assert_eq!(apply(&changes), (old, new));
+    }
+
+    #[test]
+    fn test_similarity_ratio() {
+        assert_eq!(similarity_ratio("abcd", "abce", Granularity::Char), 0.75);
+        assert_eq!(similarity_ratio("", "", Granularity::Char), 1.0);
+        assert_eq!(similarity_ratio("abc", "xyz", Granularity::Char), 0.0);
+    }
+
+    #[test]
+    fn test_unified_diff() {
+        let result = unified_diff("a\nb\nc\n", "a\nx\nc\n", "old.txt", "new.txt", 1);
+        assert_eq!(result, "--- old.txt\n+++ new.txt\n@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n");
+
+        let result = unified_diff("a\n", "a\nb", "a", "b", 3);
+        assert_eq!(result, "--- a\n+++ b\n@@ -1 +1,2 @@\n a\n+b\n\\ No newline at end of file\n");
+
+        let old = "1\n2\n3\n4\n5\n6\n7\n8\n9\n";
+        let new = "1\nX\n3\n4\n5\n6\n7\nY\n9\n";
+        let result = unified_diff(old, new, "a", "b", 1);
+        assert_eq!(result.matches("@@ -").count(), 2);
+        assert!(result.contains("@@ -1,3 +1,3 @@"));
+        assert!(result.contains("@@ -7,3 +7,3 @@"));
+
+        assert_eq!(unified_diff("same\n", "same\n", "a", "b", 3), "");
+        assert_eq!(unified_diff("", "new\n", "a", "b", 3), "--- a\n+++ b\n@@ -0,0 +1 @@\n+new\n");
+    }
+
+    #[test]
+    fn test_side_by_side() {
+        assert_eq!(side_by_side("a\nb\n", "a\nc\n", 3), "a     a\nb   | c\n");
+        assert_eq!(side_by_side("a\nb\n", "a\n", 3), "a     a\nb   <\n");
+        assert_eq!(side_by_side("a\n", "a\nb\n", 3), "a     a\n    > b\n");
+    }
+
+    #[test]
+    fn test_render_inline_ansi() {
+        let changes = diff("cat", "cut", Granularity::Char);
+        assert_eq!(render_inline_ansi(&changes), "c\x1b[31ma\x1b[0m\x1b[32mu\x1b[0mt");
+    }
+
+    #[test]
+    fn test_render_inline_html() {
+        let changes = diff("a < b", "a > b", Granularity::Word);
+        assert_eq!(render_inline_html(&changes), "a <del>&lt;</del><ins>&gt;</ins> b");
+    }
+}
@@ -1,3 +1,13 @@
+use regex::Regex;
+use std::borrow::Cow;
+use std::fmt;
+use std::sync::OnceLock;
+
+pub mod charset;
+mod html_entities;
+pub mod literal;
+pub mod recipe;
+
 /// Encodes a string into Base64.
 ///
 /// # Arguments
@@ -16,12 +26,247 @@
 /// assert_eq!(result, "aGVsbG8gd29ybGQ=");
 /// ```
 pub fn encode_base64(text: &str) -> String {
-    const BASE64_ALPHABET: &[u8; 64] =
-        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    encode_base64_with(text.as_bytes(), &Base64Config::STANDARD)
+}
 
-    let bytes = text.as_bytes();
-    let mut encoded = String::new();
-    let mut padding = 0;
+/// Decodes a Base64 string into a regular string.
+///
+/// Decoding uses [`Base64Config::STANDARD`]: padding may be omitted, but
+/// anything else that is malformed is rejected.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the Base64 encoded text to decode.
+///
+/// # Returns
+///
+/// * A `Result<String, String>` containing the decoded text or an error.
+///
+/// # Examples
+///
+/// ```
+/// let encoded = "aGVsbG8gd29ybGQ=";
+/// let result = loki_text::encoding::decode_base64(encoded);
+/// assert_eq!(result, Ok("hello world".to_string()));
+/// ```
+pub fn decode_base64(encoded: &str) -> Result<String, String> {
+    decode_base64_bytes(encoded).map_err(|e| e.to_string()).and_then(utf8_string)
+}
+
+/// Encodes bytes into standard Base64 with padding.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the Base64 encoded bytes.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::encode_base64_bytes(&[0x00, 0xff, 0x10]);
+/// assert_eq!(result, "AP8Q");
+/// ```
+pub fn encode_base64_bytes(bytes: &[u8]) -> String {
+    encode_base64_with(bytes, &Base64Config::STANDARD)
+}
+
+/// Decodes standard Base64 into bytes.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the Base64 encoded data.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes or an error.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::decode_base64_bytes("AP8Q");
+/// assert_eq!(result, Ok(vec![0x00, 0xff, 0x10]));
+/// ```
+pub fn decode_base64_bytes(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_base64_with(encoded, &Base64Config::STANDARD)
+}
+
+// Converts decoded bytes into a `String`, as the text-level decoders do.
+fn utf8_string(bytes: Vec<u8>) -> Result<String, String> {
+    String::from_utf8(bytes).map_err(|_| "Decoded bytes are not valid UTF-8".to_string())
+}
+
+/// An error produced while decoding (or, for [`encode_z85`], [`encode_punycode`] and [`charset::encode`], encoding), with the byte offset in the input where it was detected.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DecodeError {
+    /// The byte offset in the encoded input.
+    pub position: usize,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl DecodeError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        DecodeError {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+// Returns the character starting at byte `position` for error messages.
+fn char_at(text: &str, position: usize) -> char {
+    text.get(position..)
+        .and_then(|rest| rest.chars().next())
+        .unwrap_or('\u{FFFD}')
+}
+
+/// The 64-character alphabet used by a Base64 engine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base64Alphabet {
+    /// The RFC 4648 standard alphabet, ending in `+` and `/`.
+    Standard,
+    /// The RFC 4648 URL and filename safe alphabet, ending in `-` and `_`.
+    UrlSafe,
+}
+
+impl Base64Alphabet {
+    fn symbols(self) -> &'static [u8; 64] {
+        match self {
+            Base64Alphabet::Standard => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/",
+            Base64Alphabet::UrlSafe => b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_",
+        }
+    }
+}
+
+/// How `=` padding is written and checked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Padding {
+    /// Padding is written, and decoding requires it.
+    Required,
+    /// Padding is written; decoding accepts input with or without it, but checks it when present.
+    Optional,
+    /// Padding is never written, and decoding rejects it.
+    Forbidden,
+}
+
+/// How strictly a decoder treats malformed input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DecodeMode {
+    /// Reject invalid characters, misplaced or wrong padding and non-canonical trailing bits.
+    Strict,
+    /// Skip characters outside the alphabet, ignore padding and discard leftover bits.
+    Lenient,
+}
+
+/// Configuration of the Base64 engine used by [`encode_base64_with`] and [`decode_base64_with`].
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{Base64Config, Padding};
+///
+/// let config = Base64Config { padding: Padding::Forbidden, ..Base64Config::URL_SAFE };
+/// assert_eq!(loki_text::encoding::encode_base64_with(&[0xfb, 0xff], &config), "-_8");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Base64Config {
+    /// The alphabet to encode with and to accept when decoding.
+    pub alphabet: Base64Alphabet,
+    /// The padding policy.
+    pub padding: Padding,
+    /// Wrap encoded output into lines of at most this many characters, separated by CRLF.
+    pub line_width: Option<usize>,
+    /// Skip ASCII whitespace, including line breaks, when decoding.
+    pub skip_whitespace: bool,
+    /// How strictly to decode.
+    pub mode: DecodeMode,
+}
+
+impl Base64Config {
+    /// RFC 4648 standard alphabet with padding; decoding accepts missing padding.
+    pub const STANDARD: Base64Config = Base64Config {
+        alphabet: Base64Alphabet::Standard,
+        padding: Padding::Optional,
+        line_width: None,
+        skip_whitespace: false,
+        mode: DecodeMode::Strict,
+    };
+
+    /// RFC 4648 URL-safe alphabet with padding; decoding accepts missing padding.
+    pub const URL_SAFE: Base64Config = Base64Config {
+        alphabet: Base64Alphabet::UrlSafe,
+        ..Base64Config::STANDARD
+    };
+
+    /// RFC 4648 URL-safe alphabet without padding, as used by JWTs.
+    pub const URL_SAFE_NO_PAD: Base64Config = Base64Config {
+        alphabet: Base64Alphabet::UrlSafe,
+        padding: Padding::Forbidden,
+        ..Base64Config::STANDARD
+    };
+
+    /// RFC 2045 MIME: standard alphabet, required padding, 76-column lines, whitespace skipped.
+    pub const MIME: Base64Config = Base64Config {
+        alphabet: Base64Alphabet::Standard,
+        padding: Padding::Required,
+        line_width: Some(76),
+        skip_whitespace: true,
+        mode: DecodeMode::Strict,
+    };
+}
+
+impl Default for Base64Config {
+    fn default() -> Self {
+        Base64Config::STANDARD
+    }
+}
+
+// Inserts CRLF line breaks every `width` characters of an ASCII string.
+fn wrap_lines(encoded: String, width: Option<usize>) -> String {
+    match width {
+        Some(width) if width > 0 && encoded.len() > width => encoded
+            .as_bytes()
+            .chunks(width)
+            .map(|line| std::str::from_utf8(line).unwrap_or_default())
+            .collect::<Vec<&str>>()
+            .join("\r\n"),
+        _ => encoded,
+    }
+}
+
+/// Encodes bytes into Base64 using the given configuration.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+/// * `config` - The alphabet, padding and line wrapping to use.
+///
+/// # Returns
+///
+/// * A `String` containing the Base64 encoded bytes.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{encode_base64_with, Base64Config};
+///
+/// let result = encode_base64_with(b"hello world", &Base64Config::URL_SAFE_NO_PAD);
+/// assert_eq!(result, "aGVsbG8gd29ybGQ");
+/// ```
+pub fn encode_base64_with(bytes: &[u8], config: &Base64Config) -> String {
+    let alphabet = config.alphabet.symbols();
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
 
     // Iterate over input bytes in chunks of 3 (24 bits).
     for chunk in bytes.chunks(3) {
@@ -30,258 +275,2539 @@ pub fn encode_base64(text: &str) -> String {
             buffer |= (byte as u32) << (16 - i * 8);
         }
 
-        // Calculate padding based on chunk length.
-        padding = 3 - chunk.len();
-
-        // Encode into 4 Base64 characters.
+        // Encode into up to 4 Base64 characters, padding short chunks.
+        let padding = 3 - chunk.len();
         for i in 0..(4 - padding) {
             let index = ((buffer >> (18 - i * 6)) & 0x3F) as usize;
-            encoded.push(BASE64_ALPHABET[index] as char);
+            encoded.push(alphabet[index] as char);
+        }
+        if config.padding != Padding::Forbidden {
+            encoded.extend(std::iter::repeat_n('=', padding));
         }
     }
 
-    // Add padding characters '='.
-    for _ in 0..padding {
-        encoded.push('=');
-    }
-
-    encoded
+    wrap_lines(encoded, config.line_width)
 }
 
-/// Decodes a Base64 string into a regular string.
+/// Decodes Base64 into bytes using the given configuration.
+///
+/// In [`DecodeMode::Strict`] every problem is reported with the byte offset where
+/// it was found: characters outside the alphabet, padding that is forbidden,
+/// missing, of the wrong length or followed by data, a dangling final
+/// character, and non-zero bits left over in the final character.
 ///
 /// # Arguments
 ///
 /// * `encoded` - A string slice that holds the Base64 encoded text to decode.
+/// * `config` - The alphabet, padding policy, whitespace handling and mode to use.
 ///
 /// # Returns
 ///
-/// * A `Result<String, base64::DecodeError>` containing the decoded text or an error.
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes or the first error found.
 ///
 /// # Examples
 ///
 /// ```
-/// let encoded = "aGVsbG8gd29ybGQ=";
-/// let result = loki_text::encoding::decode_base64(encoded);
-/// assert_eq!(result, Ok("hello world".to_string()));
+/// use loki_text::encoding::{decode_base64_with, Base64Config};
+///
+/// let result = decode_base64_with("aGVs\r\nbG8=", &Base64Config::MIME);
+/// assert_eq!(result, Ok(b"hello".to_vec()));
+///
+/// let error = decode_base64_with("aGVs*G8=", &Base64Config::STANDARD).unwrap_err();
+/// assert_eq!(error.position, 4);
 /// ```
-pub fn decode_base64(encoded: &str) -> Result<String, String> {
-    const BASE64_ALPHABET: &[u8; 64] =
-        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
-    let mut decoded = Vec::new();
+pub fn decode_base64_with(encoded: &str, config: &Base64Config) -> Result<Vec<u8>, DecodeError> {
+    let strict = config.mode == DecodeMode::Strict;
+    let mut table = [0xFFu8; 256];
+    for (value, &symbol) in config.alphabet.symbols().iter().enumerate() {
+        table[symbol as usize] = value as u8;
+    }
+    if !strict {
+        // Lenient decoding accepts either alphabet.
+        for alphabet in [Base64Alphabet::Standard, Base64Alphabet::UrlSafe] {
+            for &symbol in &alphabet.symbols()[62..] {
+                table[symbol as usize] = if symbol == b'+' || symbol == b'-' { 62 } else { 63 };
+            }
+        }
+    }
+
+    let mut decoded = Vec::with_capacity(encoded.len() / 4 * 3);
     let mut buffer = 0u32;
     let mut bits_collected = 0;
+    let mut symbols = 0usize;
+    let mut last_symbol = (0usize, 0u8);
+    let mut padding_start = None;
+    let mut padding_count = 0;
 
-    for &byte in encoded.as_bytes() {
+    for (i, &byte) in encoded.as_bytes().iter().enumerate() {
+        if (config.skip_whitespace || !strict) && byte.is_ascii_whitespace() {
+            continue;
+        }
         if byte == b'=' {
-            break; // Stop processing on padding characters.
+            if strict && config.padding == Padding::Forbidden {
+                return Err(DecodeError::new(i, "Padding character '=' is not allowed"));
+            }
+            padding_start.get_or_insert(i);
+            padding_count += 1;
+            continue;
+        }
+        let value = table[byte as usize];
+        if value == 0xFF {
+            if strict {
+                return Err(DecodeError::new(
+                    i,
+                    format!("Invalid Base64 character '{}'", char_at(encoded, i)),
+                ));
+            }
+            continue;
         }
+        if strict && padding_count > 0 {
+            return Err(DecodeError::new(i, "Unexpected data after padding"));
+        }
+
+        buffer = (buffer << 6) | value as u32;
+        bits_collected += 6;
+        if bits_collected >= 8 {
+            bits_collected -= 8;
+            decoded.push((buffer >> bits_collected) as u8);
+        }
+        symbols += 1;
+        last_symbol = (i, value);
+    }
+
+    if !strict {
+        return Ok(decoded);
+    }
+
+    let remainder = symbols % 4;
+    if remainder == 1 {
+        return Err(DecodeError::new(
+            last_symbol.0,
+            "Trailing Base64 character does not form a complete byte",
+        ));
+    }
+    let expected_padding = (4 - remainder) % 4;
+    if let Some(start) = padding_start {
+        if padding_count != expected_padding {
+            return Err(DecodeError::new(
+                start,
+                format!("Expected {} padding characters, found {}", expected_padding, padding_count),
+            ));
+        }
+    } else if config.padding == Padding::Required && expected_padding > 0 {
+        return Err(DecodeError::new(encoded.len(), "Missing padding"));
+    }
+    let unused_bits = match remainder {
+        2 => 0x0F,
+        3 => 0x03,
+        _ => 0,
+    };
+    if last_symbol.1 & unused_bits != 0 {
+        return Err(DecodeError::new(last_symbol.0, "Non-zero trailing bits in final Base64 character"));
+    }
+
+    Ok(decoded)
+}
+
+/// The alphabet used by [`encode_base32`] and [`decode_base32`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base32Alphabet {
+    /// The RFC 4648 standard alphabet, `A`-`Z` then `2`-`7`.
+    Rfc4648,
+    /// The RFC 4648 extended hex alphabet, `0`-`9` then `A`-`V`, which preserves sort order.
+    Hex,
+    /// Douglas Crockford's alphabet, which omits `I`, `L`, `O` and `U` and decodes hyphens, `I`/`L` and `O` leniently.
+    Crockford,
+    /// The human-oriented z-base-32 alphabet.
+    ZBase32,
+}
+
+impl Base32Alphabet {
+    fn symbols(self) -> &'static [u8; 32] {
+        match self {
+            Base32Alphabet::Rfc4648 => b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567",
+            Base32Alphabet::Hex => b"0123456789ABCDEFGHIJKLMNOPQRSTUV",
+            Base32Alphabet::Crockford => b"0123456789ABCDEFGHJKMNPQRSTVWXYZ",
+            Base32Alphabet::ZBase32 => b"ybndrfg8ejkmcpqxot1uwisza345h769",
+        }
+    }
+
+    // Decodes one symbol, case-insensitively.
+    fn value(self, byte: u8) -> Option<u8> {
+        let byte = match self {
+            Base32Alphabet::ZBase32 => byte.to_ascii_lowercase(),
+            Base32Alphabet::Crockford => match byte.to_ascii_uppercase() {
+                b'I' | b'L' => b'1',
+                b'O' => b'0',
+                upper => upper,
+            },
+            _ => byte.to_ascii_uppercase(),
+        };
+        self.symbols().iter().position(|&s| s == byte).map(|v| v as u8)
+    }
+}
+
+/// Encodes bytes into Base32.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+/// * `alphabet` - The Base32 alphabet to use.
+/// * `padding` - Whether to pad the output with `=` to a multiple of 8 characters.
+///
+/// # Returns
+///
+/// * A `String` containing the Base32 encoded bytes.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{encode_base32, Base32Alphabet, Padding};
+///
+/// assert_eq!(encode_base32(b"foobar", Base32Alphabet::Rfc4648, Padding::Required), "MZXW6YTBOI======");
+/// assert_eq!(encode_base32(b"foobar", Base32Alphabet::Hex, Padding::Forbidden), "CPNMUOJ1E8");
+/// ```
+pub fn encode_base32(bytes: &[u8], alphabet: Base32Alphabet, padding: Padding) -> String {
+    let symbols = alphabet.symbols();
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u16;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            encoded.push(symbols[((buffer >> bits) & 0x1F) as usize] as char);
+        }
+        buffer &= (1 << bits) - 1;
+    }
+    if bits > 0 {
+        encoded.push(symbols[((buffer << (5 - bits)) & 0x1F) as usize] as char);
+    }
+    if padding != Padding::Forbidden {
+        while !encoded.len().is_multiple_of(8) {
+            encoded.push('=');
+        }
+    }
 
-        let index = BASE64_ALPHABET.iter().position(|&b| b == byte);
-        if let Some(index) = index {
-            buffer = (buffer << 6) | (index as u32);
-            bits_collected += 6;
+    encoded
+}
+
+/// Decodes Base32 into bytes.
+///
+/// Symbols are matched case-insensitively. With the Crockford alphabet, hyphens
+/// are ignored and `I`/`L` and `O` are read as `1` and `0`. Invalid characters,
+/// padding that breaks the `padding` policy, a dangling final group and
+/// non-zero trailing bits are reported with their byte offset.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the Base32 encoded data.
+/// * `alphabet` - The Base32 alphabet to decode.
+/// * `padding` - Whether `=` padding is required, optional or forbidden.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes or the first error found.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{decode_base32, Base32Alphabet, Padding};
+///
+/// let result = decode_base32("mzxw6ytboi", Base32Alphabet::Rfc4648, Padding::Optional);
+/// assert_eq!(result, Ok(b"foobar".to_vec()));
+///
+/// let result = decode_base32("CSQ-PY", Base32Alphabet::Crockford, Padding::Forbidden);
+/// assert_eq!(result, Ok(b"foo".to_vec()));
+/// ```
+pub fn decode_base32(encoded: &str, alphabet: Base32Alphabet, padding: Padding) -> Result<Vec<u8>, DecodeError> {
+    let mut decoded = Vec::with_capacity(encoded.len() * 5 / 8);
+    let mut buffer = 0u16;
+    let mut bits = 0;
+    let mut symbols = 0usize;
+    let mut last_symbol = (0usize, 0u8);
+    let mut padding_start = None;
+    let mut padding_count = 0;
 
-            if bits_collected >= 8 {
-                bits_collected -= 8;
-                decoded.push((buffer >> bits_collected) as u8 & 0xFF);
+    for (i, &byte) in encoded.as_bytes().iter().enumerate() {
+        if byte == b'-' && alphabet == Base32Alphabet::Crockford {
+            continue;
+        }
+        if byte == b'=' {
+            if padding == Padding::Forbidden {
+                return Err(DecodeError::new(i, "Padding character '=' is not allowed"));
             }
-        } else {
-            return Err(format!("Invalid Base64 character: {}", byte as char));
+            padding_start.get_or_insert(i);
+            padding_count += 1;
+            continue;
         }
+        let value = alphabet.value(byte).ok_or_else(|| {
+            DecodeError::new(i, format!("Invalid Base32 character '{}'", char_at(encoded, i)))
+        })?;
+        if padding_count > 0 {
+            return Err(DecodeError::new(i, "Unexpected data after padding"));
+        }
+
+        buffer = (buffer << 5) | value as u16;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((buffer >> bits) as u8);
+            buffer &= (1 << bits) - 1;
+        }
+        symbols += 1;
+        last_symbol = (i, value);
     }
 
-    // Convert decoded bytes to a UTF-8 string.
-    match String::from_utf8(decoded) {
-        Ok(s) => Ok(s),
-        Err(_) => Err("Decoded bytes are not valid UTF-8".to_string()),
+    let expected_padding = match symbols % 8 {
+        0 => 0,
+        2 => 6,
+        4 => 4,
+        5 => 3,
+        7 => 1,
+        _ => {
+            return Err(DecodeError::new(
+                last_symbol.0,
+                "Trailing Base32 character does not form a complete byte",
+            ))
+        }
+    };
+    if let Some(start) = padding_start {
+        if padding_count != expected_padding {
+            return Err(DecodeError::new(
+                start,
+                format!("Expected {} padding characters, found {}", expected_padding, padding_count),
+            ));
+        }
+    } else if padding == Padding::Required && expected_padding > 0 {
+        return Err(DecodeError::new(encoded.len(), "Missing padding"));
+    }
+    if buffer != 0 {
+        return Err(DecodeError::new(last_symbol.0, "Non-zero trailing bits in final Base32 character"));
     }
+
+    Ok(decoded)
 }
 
-/// Encodes a string into Hex.
+// Crockford check symbols: the 32 data symbols followed by five extra ones.
+const CROCKFORD_CHECK_SYMBOLS: &[u8; 37] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ*~$=U";
+
+// Computes the Crockford check value: the number written by the canonical data symbols, modulo 37.
+fn crockford_check_value(symbols: &str) -> usize {
+    symbols.bytes().fold(0, |acc, s| {
+        let digit = CROCKFORD_CHECK_SYMBOLS[..32].iter().position(|&c| c == s).unwrap_or(0);
+        (acc * 32 + digit) % 37
+    })
+}
+
+/// Encodes bytes with Crockford's Base32 alphabet followed by a check symbol.
+///
+/// The check symbol is the number written by the data symbols, including the
+/// pad bits of the last symbol, modulo 37.
 ///
 /// # Arguments
 ///
-/// * `text` - A string slice that holds the text to encode.
+/// * `bytes` - The bytes to encode.
 ///
 /// # Returns
 ///
-/// * A `String` containing the Hex encoded text.
+/// * A `String` containing the unpadded Crockford Base32 encoding and its check symbol.
 ///
 /// # Examples
 ///
 /// ```
-/// let text = "hello world";
-/// let result = loki_text::encoding::encode_hex(text);
-/// assert_eq!(result, "68656c6c6f20776f726c64");
+/// let result = loki_text::encoding::encode_crockford_check(&[0x04, 0xd2]);
+/// assert_eq!(result, "0K90Q");
 /// ```
-pub fn encode_hex(text: &str) -> String {
-    text.bytes()
-        .map(|b| format!("{:02x}", b))
-        .collect()
+pub fn encode_crockford_check(bytes: &[u8]) -> String {
+    let mut encoded = encode_base32(bytes, Base32Alphabet::Crockford, Padding::Forbidden);
+    encoded.push(CROCKFORD_CHECK_SYMBOLS[crockford_check_value(&encoded)] as char);
+    encoded
 }
 
-/// Decodes a Hex string into a regular string.
+/// Decodes Crockford Base32 ending in a check symbol, verifying the check.
 ///
 /// # Arguments
 ///
-/// * `encoded` - A string slice that holds the Hex encoded text to decode.
+/// * `encoded` - A string slice that holds the encoded data followed by its check symbol.
 ///
 /// # Returns
 ///
-/// * A `Result<String, hex::FromHexError>` containing the decoded text or an error.
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes, or an error if the data is malformed or the check symbol does not match.
 ///
 /// # Examples
 ///
 /// ```
-/// let encoded = "68656c6c6f20776f726c64";
-/// let result = loki_text::encoding::decode_hex(encoded);
-/// assert_eq!(result, Ok("hello world".to_string()));
+/// let result = loki_text::encoding::decode_crockford_check("0k9-0q");
+/// assert_eq!(result, Ok(vec![0x04, 0xd2]));
+/// assert!(loki_text::encoding::decode_crockford_check("0K90D").is_err());
 /// ```
-pub fn decode_hex(encoded: &str) -> Result<String, String> {
-    if encoded.len() % 2 != 0 {
-        return Err("Invalid hex string length".to_string());
+pub fn decode_crockford_check(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    let (check_position, check_char) = encoded
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| c != '-')
+        .ok_or_else(|| DecodeError::new(0, "Missing check symbol"))?;
+    let check_value = match check_char.to_ascii_uppercase() {
+        'I' | 'L' => Some(1),
+        'O' => Some(0),
+        c => CROCKFORD_CHECK_SYMBOLS.iter().position(|&s| s as char == c),
     }
+    .ok_or_else(|| DecodeError::new(check_position, format!("Invalid check symbol '{}'", check_char)))?;
 
-    let bytes: Result<Vec<u8>, String> = (0..encoded.len())
-        .step_by(2)
-        .map(|i| {
-            u8::from_str_radix(&encoded[i..i + 2], 16)
-                .map_err(|_| format!("Invalid hex character at position {}", i))
-        })
-        .collect();
+    let decoded = decode_base32(&encoded[..check_position], Base32Alphabet::Crockford, Padding::Forbidden)?;
+    // Re-encoding gives the canonical symbols, without hyphens or aliases.
+    let expected = crockford_check_value(&encode_base32(&decoded, Base32Alphabet::Crockford, Padding::Forbidden));
+    if check_value != expected {
+        return Err(DecodeError::new(
+            check_position,
+            format!(
+                "Check symbol mismatch: expected '{}', found '{}'",
+                CROCKFORD_CHECK_SYMBOLS[expected] as char, check_char
+            ),
+        ));
+    }
+    Ok(decoded)
+}
+
+/// The alphabet used by [`encode_base58`] and [`decode_base58`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Base58Alphabet {
+    /// The Bitcoin alphabet, also used by IPFS and most Base58Check systems.
+    Bitcoin,
+    /// The Ripple (XRP Ledger) alphabet.
+    Ripple,
+    /// The Flickr short-URL alphabet.
+    Flickr,
+}
 
-    match bytes {
-        Ok(bytes) => match String::from_utf8(bytes) {
-            Ok(s) => Ok(s),
-            Err(_) => Err("Decoded bytes are not valid UTF-8".to_string()),
-        },
-        Err(e) => Err(e),
+impl Base58Alphabet {
+    fn symbols(self) -> &'static [u8; 58] {
+        match self {
+            Base58Alphabet::Bitcoin => b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz",
+            Base58Alphabet::Ripple => b"rpshnaf39wBUDNEGHJKLM4PQRST7VWXYZ2bcdeCg65jkm8oFqi1tuvAxyz",
+            Base58Alphabet::Flickr => b"123456789abcdefghijkmnopqrstuvwxyzABCDEFGHJKLMNPQRSTUVWXYZ",
+        }
     }
 }
 
-/// Encodes a string using URL encoding (percent encoding).
+/// Encodes bytes into Base58.
+///
+/// Each leading zero byte is encoded as one leading copy of the alphabet's
+/// first symbol, so leading zeros survive a round trip.
 ///
 /// # Arguments
 ///
-/// * `text` - A string slice that holds the text to encode.
+/// * `bytes` - The bytes to encode.
+/// * `alphabet` - The Base58 alphabet to use.
 ///
 /// # Returns
 ///
-/// * A `String` containing the URL encoded text.
+/// * A `String` containing the Base58 encoded bytes.
 ///
 /// # Examples
 ///
 /// ```
-/// let text = "hello world!";
-/// let result = loki_text::encoding::encode_url(text);
-/// assert_eq!(result, "hello%20world%21");
+/// use loki_text::encoding::{encode_base58, Base58Alphabet};
+///
+/// assert_eq!(encode_base58(b"Hello World!", Base58Alphabet::Bitcoin), "2NEpo7TZRRrLZSi2U");
+/// assert_eq!(encode_base58(&[0, 0, 1], Base58Alphabet::Bitcoin), "112");
 /// ```
-pub fn encode_url(text: &str) -> String {
-    text.bytes()
-        .map(|b| match b {
-            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
-                (b as char).to_string()
-            }
-            _ => format!("%{:02X}", b),
-        })
+pub fn encode_base58(bytes: &[u8], alphabet: Base58Alphabet) -> String {
+    let symbols = alphabet.symbols();
+    let zeros = bytes.iter().take_while(|&&b| b == 0).count();
+
+    // Base-58 digits of the number, least significant first.
+    let mut digits: Vec<u8> = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for &byte in &bytes[zeros..] {
+        let mut carry = byte as u32;
+        for digit in digits.iter_mut() {
+            carry += (*digit as u32) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    std::iter::repeat_n(symbols[0] as char, zeros)
+        .chain(digits.iter().rev().map(|&d| symbols[d as usize] as char))
         .collect()
 }
 
-/// Decodes a URL encoded string.
+/// Decodes Base58 into bytes.
 ///
 /// # Arguments
 ///
-/// * `encoded` - A string slice that holds the URL encoded text to decode.
+/// * `encoded` - A string slice that holds the Base58 encoded data.
+/// * `alphabet` - The Base58 alphabet to decode.
 ///
 /// # Returns
 ///
-/// * A `Result<String, String>` containing the decoded text or an error.
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes, or an error locating the first invalid character.
 ///
 /// # Examples
 ///
 /// ```
-/// let encoded = "hello%20world%21";
-/// let result = loki_text::encoding::decode_url(encoded);
-/// assert_eq!(result, Ok("hello world!".to_string()));
+/// use loki_text::encoding::{decode_base58, Base58Alphabet};
+///
+/// assert_eq!(decode_base58("112", Base58Alphabet::Bitcoin), Ok(vec![0, 0, 1]));
+/// assert_eq!(decode_base58("1O2", Base58Alphabet::Bitcoin).unwrap_err().position, 1);
 /// ```
-pub fn decode_url(encoded: &str) -> Result<String, String> {
-    let mut decoded = Vec::new();
-    let mut chars = encoded.chars().peekable();
-
-    while let Some(ch) = chars.next() {
-        match ch {
-            '%' => {
-                let hex1 = chars.next().ok_or("Invalid URL encoding: incomplete percent sequence")?;
-                let hex2 = chars.next().ok_or("Invalid URL encoding: incomplete percent sequence")?;
-                let hex_str = format!("{}{}", hex1, hex2);
-                let byte = u8::from_str_radix(&hex_str, 16)
-                    .map_err(|_| format!("Invalid hex sequence: {}", hex_str))?;
-                decoded.push(byte);
-            }
-            '+' => decoded.push(b' '),
-            _ => decoded.push(ch as u8),
+pub fn decode_base58(encoded: &str, alphabet: Base58Alphabet) -> Result<Vec<u8>, DecodeError> {
+    let symbols = alphabet.symbols();
+    let mut table = [0xFFu8; 256];
+    for (value, &symbol) in symbols.iter().enumerate() {
+        table[symbol as usize] = value as u8;
+    }
+
+    let input = encoded.as_bytes();
+    let zeros = input.iter().take_while(|&&b| b == symbols[0]).count();
+
+    // Bytes of the number, least significant first.
+    let mut bytes: Vec<u8> = Vec::with_capacity(input.len() * 733 / 1000 + 1);
+    for (i, &symbol) in input.iter().enumerate().skip(zeros) {
+        let value = table[symbol as usize];
+        if value == 0xFF {
+            return Err(DecodeError::new(
+                i,
+                format!("Invalid Base58 character '{}'", char_at(encoded, i)),
+            ));
+        }
+        let mut carry = value as u32;
+        for byte in bytes.iter_mut() {
+            carry += *byte as u32 * 58;
+            *byte = carry as u8;
+            carry >>= 8;
+        }
+        while carry > 0 {
+            bytes.push(carry as u8);
+            carry >>= 8;
         }
     }
 
-    String::from_utf8(decoded).map_err(|_| "Decoded bytes are not valid UTF-8".to_string())
+    Ok(std::iter::repeat_n(0, zeros).chain(bytes.into_iter().rev()).collect())
 }
 
-/// Encodes a string using HTML entity encoding.
+// Computes the SHA-256 digest of `data` (FIPS 180-4).
+fn sha256(data: &[u8]) -> [u8; 32] {
+    const K: [u32; 64] = [
+        0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+        0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+        0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+        0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+        0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+        0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+        0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+        0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+    ];
+    let mut state: [u32; 8] = [
+        0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+    ];
+
+    // Pad with a 1 bit, zeros, and the 64-bit message length in bits.
+    let mut message = data.to_vec();
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&((data.len() as u64).wrapping_mul(8)).to_be_bytes());
+
+    for block in message.chunks(64) {
+        let mut w = [0u32; 64];
+        for (i, word) in block.chunks(4).enumerate() {
+            w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+        }
+        for i in 16..64 {
+            let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+            let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+            w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+        }
+
+        let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+        for i in 0..64 {
+            let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+            let ch = (e & f) ^ (!e & g);
+            let t1 = h.wrapping_add(s1).wrapping_add(ch).wrapping_add(K[i]).wrapping_add(w[i]);
+            let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+            let maj = (a & b) ^ (a & c) ^ (b & c);
+            let t2 = s0.wrapping_add(maj);
+            h = g;
+            g = f;
+            f = e;
+            e = d.wrapping_add(t1);
+            d = c;
+            c = b;
+            b = a;
+            a = t1.wrapping_add(t2);
+        }
+        for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+            *s = s.wrapping_add(v);
+        }
+    }
+
+    let mut digest = [0u8; 32];
+    for (chunk, word) in digest.chunks_mut(4).zip(state) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    digest
+}
+
+// The Base58Check checksum: the first four bytes of a double SHA-256.
+fn base58check_checksum(data: &[u8]) -> [u8; 4] {
+    let digest = sha256(&sha256(data));
+    [digest[0], digest[1], digest[2], digest[3]]
+}
+
+/// Encodes a version byte and payload as Base58Check.
+///
+/// The version byte and payload are followed by a 4-byte checksum, the start of
+/// the double SHA-256 of both, before Base58 encoding.
+///
+/// # Arguments
+///
+/// * `version` - The version byte, such as `0x00` for a Bitcoin P2PKH address.
+/// * `payload` - The payload bytes.
+/// * `alphabet` - The Base58 alphabet to use.
+///
+/// # Returns
+///
+/// * A `String` containing the Base58Check encoding.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{encode_base58check, Base58Alphabet};
+///
+/// let result = encode_base58check(0x00, &[0u8; 20], Base58Alphabet::Bitcoin);
+/// assert_eq!(result, "1111111111111111111114oLvT2");
+/// ```
+pub fn encode_base58check(version: u8, payload: &[u8], alphabet: Base58Alphabet) -> String {
+    let mut data = Vec::with_capacity(payload.len() + 5);
+    data.push(version);
+    data.extend_from_slice(payload);
+    let checksum = base58check_checksum(&data);
+    data.extend_from_slice(&checksum);
+    encode_base58(&data, alphabet)
+}
+
+/// Decodes Base58Check into its version byte and payload, verifying the checksum.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the Base58Check encoded data.
+/// * `alphabet` - The Base58 alphabet to decode.
+///
+/// # Returns
+///
+/// * A `Result<(u8, Vec<u8>), DecodeError>` containing the version byte and payload, or an error for invalid characters, data too short to hold a version and checksum, or a checksum mismatch.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{decode_base58check, Base58Alphabet};
+///
+/// let result = decode_base58check("1111111111111111111114oLvT2", Base58Alphabet::Bitcoin);
+/// assert_eq!(result, Ok((0x00, vec![0u8; 20])));
+/// assert!(decode_base58check("1111111111111111111114oLvT3", Base58Alphabet::Bitcoin).is_err());
+/// ```
+pub fn decode_base58check(encoded: &str, alphabet: Base58Alphabet) -> Result<(u8, Vec<u8>), DecodeError> {
+    let data = decode_base58(encoded, alphabet)?;
+    if data.len() < 5 {
+        return Err(DecodeError::new(0, "Base58Check data is too short"));
+    }
+    let (body, checksum) = data.split_at(data.len() - 4);
+    if base58check_checksum(body) != checksum {
+        return Err(DecodeError::new(0, "Base58Check checksum mismatch"));
+    }
+    Ok((body[0], body[1..].to_vec()))
+}
+
+/// Options for [`encode_ascii85`] and [`decode_ascii85`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Ascii85Config {
+    /// Wrap the encoding in Adobe's `<~` and `~>` delimiters, and require them when decoding.
+    pub adobe_framing: bool,
+    /// Encode a group of four spaces as `y`, and accept `y` when decoding (a btoa extension).
+    pub compress_spaces: bool,
+}
+
+impl Ascii85Config {
+    /// The Adobe variant used in PostScript and PDF: `<~ ~>` framing and no `y` compression.
+    pub const ADOBE: Ascii85Config = Ascii85Config {
+        adobe_framing: true,
+        compress_spaces: false,
+    };
+}
+
+// Base85 alphabets indexed by digit value.
+const Z85_ALPHABET: &[u8; 85] =
+    b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ.-:+=^!/*?&<>()[]{}@%$#";
+const BASE85_ALPHABET: &[u8; 85] =
+    b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz!#$%&()*+-;<=>?@^_`{|}~";
+
+// Encodes a (possibly partial) group of up to four bytes as `len + 1` base-85 digits.
+fn base85_digits(group: &[u8]) -> ([u8; 5], usize) {
+    let mut padded = [0u8; 4];
+    padded[..group.len()].copy_from_slice(group);
+    let mut value = u32::from_be_bytes(padded);
+    let mut digits = [0u8; 5];
+    for digit in digits.iter_mut().rev() {
+        *digit = (value % 85) as u8;
+        value /= 85;
+    }
+    (digits, group.len() + 1)
+}
+
+// Decodes up to five base-85 digits, padding short groups with the highest digit.
+// Returns `None` when the group overflows 32 bits.
+fn base85_group(digits: &[u8]) -> Option<Vec<u8>> {
+    let value = (0..5).try_fold(0u32, |acc, i| {
+        acc.checked_mul(85)?.checked_add(*digits.get(i).unwrap_or(&84) as u32)
+    })?;
+    Some(value.to_be_bytes()[..digits.len() - 1].to_vec())
+}
+
+// Encodes bytes with a plain 85-symbol alphabet, truncating the final partial group.
+fn encode_base85_with(bytes: &[u8], alphabet: &[u8; 85]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(4) * 5);
+    for group in bytes.chunks(4) {
+        let (digits, len) = base85_digits(group);
+        encoded.extend(digits[..len].iter().map(|&d| alphabet[d as usize] as char));
+    }
+    encoded
+}
+
+// Decodes a plain 85-symbol alphabet, accepting a truncated final group.
+fn decode_base85_with(encoded: &str, alphabet: &[u8; 85], name: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut table = [0xFFu8; 256];
+    for (value, &symbol) in alphabet.iter().enumerate() {
+        table[symbol as usize] = value as u8;
+    }
+    let input = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(input.len() / 5 * 4);
+    for (index, group) in input.chunks(5).enumerate() {
+        let start = index * 5;
+        let mut digits = Vec::with_capacity(5);
+        for (offset, &symbol) in group.iter().enumerate() {
+            let value = table[symbol as usize];
+            if value == 0xFF {
+                let position = start + offset;
+                return Err(DecodeError::new(
+                    position,
+                    format!("Invalid {} character '{}'", name, char_at(encoded, position)),
+                ));
+            }
+            digits.push(value);
+        }
+        if digits.len() == 1 {
+            return Err(DecodeError::new(start, format!("Trailing {} character does not form a complete byte", name)));
+        }
+        let bytes = base85_group(&digits)
+            .ok_or_else(|| DecodeError::new(start, format!("{} group overflows 32 bits", name)))?;
+        decoded.extend(bytes);
+    }
+    Ok(decoded)
+}
+
+/// Encodes bytes into Ascii85.
+///
+/// A group of four zero bytes is written as `z`, and, when
+/// [`Ascii85Config::compress_spaces`] is set, four spaces as `y`.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+/// * `config` - The framing and compression options.
+///
+/// # Returns
+///
+/// * A `String` containing the Ascii85 encoded bytes.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{encode_ascii85, Ascii85Config};
+///
+/// let result = encode_ascii85(b"Man \0\0\0\0", &Ascii85Config::ADOBE);
+/// assert_eq!(result, "<~9jqo^z~>");
+/// ```
+pub fn encode_ascii85(bytes: &[u8], config: &Ascii85Config) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(4) * 5 + 4);
+    if config.adobe_framing {
+        encoded.push_str("<~");
+    }
+    for group in bytes.chunks(4) {
+        if group == [0; 4] {
+            encoded.push('z');
+        } else if config.compress_spaces && group == [b' '; 4] {
+            encoded.push('y');
+        } else {
+            let (digits, len) = base85_digits(group);
+            encoded.extend(digits[..len].iter().map(|&d| (d + b'!') as char));
+        }
+    }
+    if config.adobe_framing {
+        encoded.push_str("~>");
+    }
+    encoded
+}
+
+/// Decodes Ascii85 into bytes.
+///
+/// Whitespace is ignored. `z` (and `y` when enabled) is only accepted between
+/// groups, and with Adobe framing the input must be wrapped in `<~` and `~>`.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the Ascii85 encoded data.
+/// * `config` - The framing and compression options.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes or the first error found.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{decode_ascii85, Ascii85Config};
+///
+/// let result = decode_ascii85("<~9jqo^\nz~>", &Ascii85Config::ADOBE);
+/// assert_eq!(result, Ok(b"Man \0\0\0\0".to_vec()));
+/// ```
+pub fn decode_ascii85(encoded: &str, config: &Ascii85Config) -> Result<Vec<u8>, DecodeError> {
+    let (start, end) = if config.adobe_framing {
+        let start = encoded.len() - encoded.trim_start().len();
+        let end = encoded.trim_end().len();
+        if !encoded[start..].starts_with("<~") {
+            return Err(DecodeError::new(start, "Missing '<~' delimiter"));
+        }
+        if end < start + 4 || !encoded[..end].ends_with("~>") {
+            return Err(DecodeError::new(end, "Missing '~>' delimiter"));
+        }
+        (start + 2, end - 2)
+    } else {
+        (0, encoded.len())
+    };
+
+    let input = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity((end - start) / 5 * 4);
+    let mut digits = Vec::with_capacity(5);
+    let mut group_start = start;
+    for (i, &byte) in input.iter().enumerate().take(end).skip(start) {
+        match byte {
+            b if b.is_ascii_whitespace() => continue,
+            b'z' | b'y' if digits.is_empty() && (byte == b'z' || config.compress_spaces) => {
+                decoded.extend_from_slice(if byte == b'z' { &[0; 4] } else { &[b' '; 4] });
+            }
+            b'!'..=b'u' => {
+                if digits.is_empty() {
+                    group_start = i;
+                }
+                digits.push(byte - b'!');
+                if digits.len() == 5 {
+                    let bytes = base85_group(&digits)
+                        .ok_or_else(|| DecodeError::new(group_start, "Ascii85 group overflows 32 bits"))?;
+                    decoded.extend(bytes);
+                    digits.clear();
+                }
+            }
+            b'z' | b'y' if !digits.is_empty() => {
+                return Err(DecodeError::new(i, format!("'{}' inside an Ascii85 group", byte as char)));
+            }
+            _ => {
+                return Err(DecodeError::new(
+                    i,
+                    format!("Invalid Ascii85 character '{}'", char_at(encoded, i)),
+                ))
+            }
+        }
+    }
+    match digits.len() {
+        0 => {}
+        1 => {
+            return Err(DecodeError::new(
+                group_start,
+                "Trailing Ascii85 character does not form a complete byte",
+            ))
+        }
+        _ => decoded.extend(
+            base85_group(&digits)
+                .ok_or_else(|| DecodeError::new(group_start, "Ascii85 group overflows 32 bits"))?,
+        ),
+    }
+    Ok(decoded)
+}
+
+/// Encodes bytes into Z85, the ZeroMQ Base85 variant.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode; Z85 requires a length that is a multiple of 4.
+///
+/// # Returns
+///
+/// * A `Result<String, DecodeError>` containing the Z85 encoded bytes, or an error positioned at the end of the input if its length is not a multiple of 4.
+///
+/// # Examples
+///
+/// ```
+/// let bytes = [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B];
+/// let result = loki_text::encoding::encode_z85(&bytes);
+/// assert_eq!(result, Ok("HelloWorld".to_string()));
+/// assert_eq!(loki_text::encoding::encode_z85(b"abc").unwrap_err().position, 3);
+/// ```
+pub fn encode_z85(bytes: &[u8]) -> Result<String, DecodeError> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err(DecodeError::new(
+            bytes.len(),
+            format!("Z85 input length must be a multiple of 4, got {}", bytes.len()),
+        ));
+    }
+    Ok(encode_base85_with(bytes, Z85_ALPHABET))
+}
+
+/// Decodes Z85 into bytes.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the Z85 encoded data; its length must be a multiple of 5.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes or the first error found.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::decode_z85("HelloWorld");
+/// assert_eq!(result, Ok(vec![0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B]));
+/// ```
+pub fn decode_z85(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    if !encoded.len().is_multiple_of(5) {
+        return Err(DecodeError::new(
+            encoded.len(),
+            format!("Z85 input length must be a multiple of 5, got {}", encoded.len()),
+        ));
+    }
+    decode_base85_with(encoded, Z85_ALPHABET, "Z85")
+}
+
+/// Encodes bytes with the RFC 1924 Base85 alphabet, as used by git binary patches and Python's `b85encode`.
+///
+/// A final partial group of `n` bytes is written as `n + 1` characters.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the Base85 encoded bytes.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::encode_base85(b"hello");
+/// assert_eq!(result, "Xk~0{Zv");
+/// ```
+pub fn encode_base85(bytes: &[u8]) -> String {
+    encode_base85_with(bytes, BASE85_ALPHABET)
+}
+
+/// Decodes RFC 1924 Base85 into bytes.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the Base85 encoded data.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes or the first error found.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::decode_base85("Xk~0{Zv");
+/// assert_eq!(result, Ok(b"hello".to_vec()));
+/// ```
+pub fn decode_base85(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    decode_base85_with(encoded, BASE85_ALPHABET, "Base85")
+}
+
+/// Encodes a string into Hex.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the Hex encoded text.
+///
+/// # Examples
+///
+/// ```
+/// let text = "hello world";
+/// let result = loki_text::encoding::encode_hex(text);
+/// assert_eq!(result, "68656c6c6f20776f726c64");
+/// ```
+pub fn encode_hex(text: &str) -> String {
+    encode_hex_bytes(text.as_bytes())
+}
+
+/// Decodes a Hex string into a regular string.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the Hex encoded text to decode.
+///
+/// # Returns
+///
+/// * A `Result<String, String>` containing the decoded text or an error.
+///
+/// # Examples
+///
+/// ```
+/// let encoded = "68656c6c6f20776f726c64";
+/// let result = loki_text::encoding::decode_hex(encoded);
+/// assert_eq!(result, Ok("hello world".to_string()));
+/// ```
+pub fn decode_hex(encoded: &str) -> Result<String, String> {
+    decode_hex_bytes(encoded).map_err(|e| e.to_string()).and_then(utf8_string)
+}
+
+/// Encodes bytes into lowercase Hex.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+///
+/// # Returns
+///
+/// * A `String` containing two Hex digits per byte.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::encode_hex_bytes(&[0xde, 0xad, 0xbe, 0xef]);
+/// assert_eq!(result, "deadbeef");
+/// ```
+pub fn encode_hex_bytes(bytes: &[u8]) -> String {
+    const HEX_DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut encoded = String::with_capacity(bytes.len() * 2);
+    for &b in bytes {
+        encoded.push(HEX_DIGITS[(b >> 4) as usize] as char);
+        encoded.push(HEX_DIGITS[(b & 0x0F) as usize] as char);
+    }
+    encoded
+}
+
+/// Decodes Hex, in either case, into bytes.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the Hex encoded data.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes, or an error locating an invalid digit or an odd length.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::decode_hex_bytes("DEADbeef");
+/// assert_eq!(result, Ok(vec![0xde, 0xad, 0xbe, 0xef]));
+/// assert_eq!(loki_text::encoding::decode_hex_bytes("0g").unwrap_err().position, 1);
+/// ```
+pub fn decode_hex_bytes(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    let digits = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(digits.len() / 2);
+    let digit = |i: usize| {
+        (digits[i] as char)
+            .to_digit(16)
+            .map(|d| d as u8)
+            .ok_or_else(|| DecodeError::new(i, format!("Invalid hex character '{}'", char_at(encoded, i))))
+    };
+    for i in (0..digits.len()).step_by(2) {
+        let high = digit(i)?;
+        if i + 1 == digits.len() {
+            return Err(DecodeError::new(digits.len(), "Invalid hex string length"));
+        }
+        decoded.push((high << 4) | digit(i + 1)?);
+    }
+    Ok(decoded)
+}
+
+/// Encodes a string using URL encoding (percent encoding).
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the URL encoded text.
+///
+/// # Examples
+///
+/// ```
+/// let text = "hello world!";
+/// let result = loki_text::encoding::encode_url(text);
+/// assert_eq!(result, "hello%20world%21");
+/// ```
+pub fn encode_url(text: &str) -> String {
+    encode_url_bytes(text.as_bytes())
+}
+
+/// Decodes a URL encoded string.
+///
+/// `+` is decoded as a space, as in form data; use [`decode_url_with`] to decode
+/// paths and other parts where `+` is literal.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the URL encoded text to decode.
+///
+/// # Returns
+///
+/// * A `Result<String, String>` containing the decoded text or an error.
+///
+/// # Examples
+///
+/// ```
+/// let encoded = "hello%20world%21";
+/// let result = loki_text::encoding::decode_url(encoded);
+/// assert_eq!(result, Ok("hello world!".to_string()));
+/// ```
+pub fn decode_url(encoded: &str) -> Result<String, String> {
+    decode_url_bytes(encoded).map_err(|e| e.to_string()).and_then(utf8_string)
+}
+
+/// Percent-encodes bytes, leaving only RFC 3986 unreserved characters as-is.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the URL encoded bytes.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::encode_url_bytes(&[b'a', b' ', 0xff]);
+/// assert_eq!(result, "a%20%FF");
+/// ```
+pub fn encode_url_bytes(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for &b in bytes {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(b as char),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+/// Decodes a URL encoded string into bytes, turning `+` into a space.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the URL encoded data.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes, or an error locating a malformed percent sequence.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::decode_url_bytes("a+%FF");
+/// assert_eq!(result, Ok(vec![b'a', b' ', 0xff]));
+/// ```
+pub fn decode_url_bytes(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    percent_decode(encoded, true)
+}
+
+// Decodes percent sequences, optionally turning `+` into a space.
+fn percent_decode(encoded: &str, plus_as_space: bool) -> Result<Vec<u8>, DecodeError> {
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let hex = bytes
+                    .get(i + 1..i + 3)
+                    .ok_or_else(|| DecodeError::new(i, "Invalid URL encoding: incomplete percent sequence"))?;
+                let byte = std::str::from_utf8(hex)
+                    .ok()
+                    .filter(|h| h.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+                    .ok_or_else(|| {
+                        DecodeError::new(i, format!("Invalid hex sequence: {}", String::from_utf8_lossy(hex)))
+                    })?;
+                decoded.push(byte);
+                i += 3;
+            }
+            b'+' if plus_as_space => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            b => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    Ok(decoded)
+}
+
+/// The set of bytes left unencoded by [`encode_url_with`], chosen by where the text goes in a URL.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UrlEncodeSet {
+    /// A single path segment: RFC 3986 `pchar`, so `/`, `?` and `#` are encoded.
+    PathSegment,
+    /// A whole query string: `pchar` plus `/` and `?`, so `&` and `=` are kept.
+    Query,
+    /// A fragment: `pchar` plus `/` and `?`.
+    Fragment,
+    /// A user name or password: unreserved characters and sub-delimiters, so `:` and `@` are encoded.
+    Userinfo,
+    /// Any URL component: only RFC 3986 unreserved characters are kept, as in [`encode_url`].
+    Component,
+    /// WHATWG `application/x-www-form-urlencoded`: spaces become `+`, and only `*-._` and alphanumerics are kept.
+    Form,
+}
+
+impl UrlEncodeSet {
+    fn keeps(self, b: u8) -> bool {
+        let unreserved = b.is_ascii_alphanumeric() || matches!(b, b'-' | b'.' | b'_' | b'~');
+        let sub_delim = matches!(b, b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'=');
+        let pchar = unreserved || sub_delim || b == b':' || b == b'@';
+        match self {
+            UrlEncodeSet::PathSegment => pchar,
+            UrlEncodeSet::Query | UrlEncodeSet::Fragment => pchar || b == b'/' || b == b'?',
+            UrlEncodeSet::Userinfo => unreserved || sub_delim,
+            UrlEncodeSet::Component => unreserved,
+            UrlEncodeSet::Form => b.is_ascii_alphanumeric() || matches!(b, b'*' | b'-' | b'.' | b'_'),
+        }
+    }
+}
+
+/// Percent-encodes bytes for a specific part of a URL.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode, usually UTF-8 text.
+/// * `set` - The URL part the result is inserted into.
+///
+/// # Returns
+///
+/// * A `String` containing the percent-encoded bytes.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{encode_url_with, UrlEncodeSet};
+///
+/// assert_eq!(encode_url_with("a/b c".as_bytes(), UrlEncodeSet::PathSegment), "a%2Fb%20c");
+/// assert_eq!(encode_url_with("q=a b&x".as_bytes(), UrlEncodeSet::Query), "q=a%20b&x");
+/// assert_eq!(encode_url_with("a b&c".as_bytes(), UrlEncodeSet::Form), "a+b%26c");
+/// ```
+pub fn encode_url_with(bytes: &[u8], set: UrlEncodeSet) -> String {
+    let mut encoded = String::with_capacity(bytes.len());
+    for &b in bytes {
+        if set.keeps(b) {
+            encoded.push(b as char);
+        } else if b == b' ' && set == UrlEncodeSet::Form {
+            encoded.push('+');
+        } else {
+            encoded.push_str(&format!("%{:02X}", b));
+        }
+    }
+    encoded
+}
+
+/// Decodes percent-encoded text from a specific part of a URL into bytes.
+///
+/// Only [`UrlEncodeSet::Form`] turns `+` into a space; in every other part `+`
+/// is a literal plus sign. Non-ASCII characters are kept as their UTF-8 bytes.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the percent-encoded text.
+/// * `set` - The URL part the text comes from.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes, or an error locating a malformed percent sequence.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{decode_url_with, UrlEncodeSet};
+///
+/// assert_eq!(decode_url_with("c++%20x", UrlEncodeSet::PathSegment), Ok(b"c++ x".to_vec()));
+/// assert_eq!(decode_url_with("c++%20x", UrlEncodeSet::Form), Ok(b"c   x".to_vec()));
+/// ```
+pub fn decode_url_with(encoded: &str, set: UrlEncodeSet) -> Result<Vec<u8>, DecodeError> {
+    percent_decode(encoded, set == UrlEncodeSet::Form)
+}
+
+// Percent-decodes the way the WHATWG URL parser does: malformed sequences are kept literally.
+fn percent_decode_lossy(input: &[u8]) -> Vec<u8> {
+    let mut decoded = Vec::with_capacity(input.len());
+    let mut i = 0;
+    while i < input.len() {
+        let hex = input.get(i + 1..i + 3).filter(|h| h.iter().all(u8::is_ascii_hexdigit));
+        match (input[i], hex) {
+            (b'%', Some(hex)) => {
+                let digit = |d: u8| (d as char).to_digit(16).unwrap_or(0) as u8;
+                decoded.push((digit(hex[0]) << 4) | digit(hex[1]));
+                i += 3;
+            }
+            (b, _) => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    decoded
+}
+
+/// Parses an `application/x-www-form-urlencoded` string into ordered key/value pairs.
+///
+/// Parsing follows the WHATWG URL standard: it never fails, empty pairs are
+/// skipped, a pair without `=` gets an empty value, malformed percent sequences
+/// are kept as-is and invalid UTF-8 becomes U+FFFD.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that holds the form data, without a leading `?`.
+///
+/// # Returns
+///
+/// * A `Vec<(String, String)>` containing the decoded pairs in order, including repeated keys.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::parse_form_urlencoded("q=caf%C3%A9+au+lait&tag=a&tag=b&flag");
+/// assert_eq!(result, vec![
+///     ("q".to_string(), "café au lait".to_string()),
+///     ("tag".to_string(), "a".to_string()),
+///     ("tag".to_string(), "b".to_string()),
+///     ("flag".to_string(), "".to_string()),
+/// ]);
+/// ```
+pub fn parse_form_urlencoded(input: &str) -> Vec<(String, String)> {
+    let decode = |part: &str| {
+        let bytes = percent_decode_lossy(part.replace('+', " ").as_bytes());
+        String::from_utf8_lossy(&bytes).into_owned()
+    };
+    input
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(name), decode(value))
+        })
+        .collect()
+}
+
+/// Serializes key/value pairs as an `application/x-www-form-urlencoded` string.
+///
+/// # Arguments
+///
+/// * `pairs` - The key/value pairs, serialized in order.
+///
+/// # Returns
+///
+/// * A `String` containing the form data, with pairs joined by `&`.
+///
+/// # Examples
+///
+/// ```
+/// let pairs = [("q", "café au lait"), ("a&b", "1=2")];
+/// let result = loki_text::encoding::serialize_form_urlencoded(&pairs);
+/// assert_eq!(result, "q=caf%C3%A9+au+lait&a%26b=1%3D2");
+/// ```
+pub fn serialize_form_urlencoded<K: AsRef<str>, V: AsRef<str>>(pairs: &[(K, V)]) -> String {
+    pairs
+        .iter()
+        .map(|(key, value)| {
+            format!(
+                "{}={}",
+                encode_url_with(key.as_ref().as_bytes(), UrlEncodeSet::Form),
+                encode_url_with(value.as_ref().as_bytes(), UrlEncodeSet::Form)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join("&")
+}
+
+// RFC 3492 Punycode parameters.
+const PUNYCODE_BASE: u32 = 36;
+const PUNYCODE_TMIN: u32 = 1;
+const PUNYCODE_TMAX: u32 = 26;
+const PUNYCODE_SKEW: u32 = 38;
+const PUNYCODE_DAMP: u32 = 700;
+const PUNYCODE_INITIAL_BIAS: u32 = 72;
+const PUNYCODE_INITIAL_N: u32 = 0x80;
+
+fn punycode_adapt(mut delta: u32, num_points: u32, first_time: bool) -> u32 {
+    delta /= if first_time { PUNYCODE_DAMP } else { 2 };
+    delta += delta / num_points;
+    let mut k = 0;
+    while delta > ((PUNYCODE_BASE - PUNYCODE_TMIN) * PUNYCODE_TMAX) / 2 {
+        delta /= PUNYCODE_BASE - PUNYCODE_TMIN;
+        k += PUNYCODE_BASE;
+    }
+    k + (PUNYCODE_BASE - PUNYCODE_TMIN + 1) * delta / (delta + PUNYCODE_SKEW)
+}
+
+fn punycode_threshold(k: u32, bias: u32) -> u32 {
+    k.saturating_sub(bias).clamp(PUNYCODE_TMIN, PUNYCODE_TMAX)
+}
+
+fn punycode_digit(d: u32) -> char {
+    (if d < 26 { b'a' + d as u8 } else { b'0' + (d - 26) as u8 }) as char
+}
+
+/// Encodes a string with Punycode (RFC 3492), as used in IDNA labels.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that holds the text to encode, usually a single domain label.
+///
+/// # Returns
+///
+/// * A `Result<String, DecodeError>` containing the Punycode encoding without the `xn--` prefix, or an overflow error positioned at the character that made the input too long to encode.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(loki_text::encoding::encode_punycode("münchen"), Ok("mnchen-3ya".to_string()));
+/// assert_eq!(loki_text::encoding::encode_punycode("abc"), Ok("abc-".to_string()));
+/// ```
+pub fn encode_punycode(input: &str) -> Result<String, DecodeError> {
+    let overflow = |position: usize| DecodeError::new(position, "Punycode overflow: input is too long");
+    let code_points: Vec<(usize, u32)> = input.char_indices().map(|(i, c)| (i, c as u32)).collect();
+    let mut output: String = input.chars().filter(char::is_ascii).collect();
+    let basic_count = output.len() as u32;
+    if basic_count > 0 {
+        output.push('-');
+    }
+
+    let mut n = PUNYCODE_INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = PUNYCODE_INITIAL_BIAS;
+    let mut handled = basic_count;
+    while (handled as usize) < code_points.len() {
+        let (m_position, m) = code_points
+            .iter()
+            .copied()
+            .filter(|&(_, c)| c >= n)
+            .min_by_key(|&(_, c)| c)
+            .unwrap_or((input.len(), n));
+        delta = (m - n)
+            .checked_mul(handled + 1)
+            .and_then(|d| delta.checked_add(d))
+            .ok_or_else(|| overflow(m_position))?;
+        n = m;
+        for &(position, c) in &code_points {
+            if c < n {
+                delta = delta.checked_add(1).ok_or_else(|| overflow(position))?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = PUNYCODE_BASE;
+                loop {
+                    let t = punycode_threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    output.push(punycode_digit(t + (q - t) % (PUNYCODE_BASE - t)));
+                    q = (q - t) / (PUNYCODE_BASE - t);
+                    k += PUNYCODE_BASE;
+                }
+                output.push(punycode_digit(q));
+                bias = punycode_adapt(delta, handled + 1, handled == basic_count);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta = delta.checked_add(1).ok_or_else(|| overflow(input.len()))?;
+        n += 1;
+    }
+    Ok(output)
+}
+
+/// Decodes a Punycode string (RFC 3492).
+///
+/// # Arguments
+///
+/// * `input` - A string slice that holds the Punycode text, without the `xn--` prefix.
+///
+/// # Returns
+///
+/// * A `Result<String, DecodeError>` containing the decoded text, or an error locating an invalid digit, a non-ASCII character or an overflow.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(loki_text::encoding::decode_punycode("mnchen-3ya"), Ok("münchen".to_string()));
+/// assert!(loki_text::encoding::decode_punycode("mnchen-3y!").is_err());
+/// ```
+pub fn decode_punycode(input: &str) -> Result<String, DecodeError> {
+    let (basic, extended, extended_start) = match input.rfind('-') {
+        Some(i) => (&input[..i], &input[i + 1..], i + 1),
+        None => ("", input, 0),
+    };
+    if let Some(i) = basic.bytes().position(|b| !b.is_ascii()) {
+        return Err(DecodeError::new(i, "Non-ASCII character before the Punycode delimiter"));
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+
+    let mut n = PUNYCODE_INITIAL_N;
+    let mut i: u32 = 0;
+    let mut bias = PUNYCODE_INITIAL_BIAS;
+    let mut digits = extended.bytes().enumerate().peekable();
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w: u32 = 1;
+        let mut k = PUNYCODE_BASE;
+        loop {
+            let (offset, byte) = digits
+                .next()
+                .ok_or_else(|| DecodeError::new(input.len(), "Incomplete Punycode sequence"))?;
+            let position = extended_start + offset;
+            let digit = match byte {
+                b'a'..=b'z' => (byte - b'a') as u32,
+                b'A'..=b'Z' => (byte - b'A') as u32,
+                b'0'..=b'9' => (byte - b'0') as u32 + 26,
+                _ => {
+                    return Err(DecodeError::new(
+                        position,
+                        format!("Invalid Punycode digit '{}'", char_at(input, position)),
+                    ))
+                }
+            };
+            let overflow = || DecodeError::new(position, "Punycode overflow");
+            i = digit.checked_mul(w).and_then(|d| i.checked_add(d)).ok_or_else(overflow)?;
+            let t = punycode_threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(PUNYCODE_BASE - t).ok_or_else(overflow)?;
+            k += PUNYCODE_BASE;
+        }
+        let length = output.len() as u32 + 1;
+        bias = punycode_adapt(i - old_i, length, old_i == 0);
+        n = n
+            .checked_add(i / length)
+            .ok_or_else(|| DecodeError::new(input.len(), "Punycode overflow"))?;
+        i %= length;
+        let c = char::from_u32(n)
+            .filter(|c| !c.is_ascii())
+            .ok_or_else(|| DecodeError::new(input.len(), format!("Invalid decoded code point U+{:X}", n)))?;
+        output.insert(i as usize, c);
+        i += 1;
+    }
+    Ok(output.into_iter().collect())
+}
+
+// Splits a domain on IDNA label separators, returning each label with its byte offset.
+fn domain_labels(domain: &str) -> Vec<(usize, &str)> {
+    let mut labels = Vec::new();
+    let mut start = 0;
+    for (i, c) in domain.char_indices() {
+        if matches!(c, '.' | '\u{3002}' | '\u{FF0E}' | '\u{FF61}') {
+            labels.push((start, &domain[start..i]));
+            start = i + c.len_utf8();
+        }
+    }
+    labels.push((start, &domain[start..]));
+    labels
+}
+
+// Checks the STD3 rules for an ASCII label: letters, digits and hyphens, with no leading or trailing hyphen.
+fn check_ascii_label(label: &str, position: usize) -> Result<(), DecodeError> {
+    if label.is_empty() {
+        return Err(DecodeError::new(position, "Empty domain label"));
+    }
+    if label.len() > 63 {
+        return Err(DecodeError::new(position, format!("Domain label is {} bytes long, the limit is 63", label.len())));
+    }
+    if let Some(i) = label.bytes().position(|b| !b.is_ascii_alphanumeric() && b != b'-') {
+        return Err(DecodeError::new(
+            position,
+            format!("Invalid character '{}' in domain label", char_at(label, i)),
+        ));
+    }
+    if label.starts_with('-') || label.ends_with('-') {
+        return Err(DecodeError::new(position, "Domain label starts or ends with a hyphen"));
+    }
+    Ok(())
+}
+
+// Checks the overall length of a domain, ignoring a trailing root dot.
+fn check_domain_length(domain: &str) -> Result<(), DecodeError> {
+    let length = domain.strip_suffix('.').unwrap_or(domain).len();
+    if length > 253 {
+        return Err(DecodeError::new(0, format!("Domain is {} bytes long, the limit is 253", length)));
+    }
+    Ok(())
+}
+
+/// Converts a domain name to its ASCII form (IDNA ToASCII), label by label.
+///
+/// Labels are lowercased and any label containing non-ASCII characters is
+/// Punycode-encoded with an `xn--` prefix. The ideographic full stops are
+/// accepted as separators, and a trailing root dot is kept. Each resulting
+/// label must follow the STD3 rules: 1 to 63 letters, digits or hyphens, not
+/// starting or ending with a hyphen. The whole name must be at most 253 bytes.
+/// Existing `xn--` labels must decode to non-ASCII text. The full UTS #46
+/// mapping table is not applied.
+///
+/// # Arguments
+///
+/// * `domain` - A string slice that holds the domain name.
+///
+/// # Returns
+///
+/// * A `Result<String, DecodeError>` containing the ASCII domain, or an error positioned at the start of the first invalid label.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::domain_to_ascii;
+///
+/// assert_eq!(domain_to_ascii("München.de"), Ok("xn--mnchen-3ya.de".to_string()));
+/// assert_eq!(domain_to_ascii("example..com").unwrap_err().position, 8);
+/// ```
+pub fn domain_to_ascii(domain: &str) -> Result<String, DecodeError> {
+    let labels = domain_labels(domain);
+    let last = labels.len() - 1;
+    let mut ascii_labels = Vec::with_capacity(labels.len());
+    for (index, (position, label)) in labels.into_iter().enumerate() {
+        if label.is_empty() && index == last && index > 0 {
+            ascii_labels.push(String::new());
+            continue;
+        }
+        let label = label.to_lowercase();
+        let ascii = if label.is_ascii() {
+            if let Some(encoded) = label.strip_prefix("xn--") {
+                let decoded = decode_punycode(encoded)
+                    .map_err(|e| DecodeError::new(position, format!("Invalid Punycode label: {}", e.message)))?;
+                if decoded.is_ascii() {
+                    return Err(DecodeError::new(position, "Punycode label decodes to plain ASCII"));
+                }
+            }
+            label
+        } else {
+            if let Some(c) = label.chars().find(|c| c.is_whitespace() || c.is_control()) {
+                return Err(DecodeError::new(position, format!("Invalid character {:?} in domain label", c)));
+            }
+            let encoded = encode_punycode(&label).map_err(|e| DecodeError::new(position, e.message))?;
+            format!("xn--{}", encoded)
+        };
+        check_ascii_label(&ascii, position)?;
+        ascii_labels.push(ascii);
+    }
+    let ascii = ascii_labels.join(".");
+    check_domain_length(&ascii)?;
+    Ok(ascii)
+}
+
+/// Converts a domain name to its Unicode form (IDNA ToUnicode), label by label.
+///
+/// Labels are lowercased and `xn--` labels are Punycode-decoded. Every label is
+/// checked as in [`domain_to_ascii`].
+///
+/// # Arguments
+///
+/// * `domain` - A string slice that holds the domain name.
+///
+/// # Returns
+///
+/// * A `Result<String, DecodeError>` containing the Unicode domain, or an error positioned at the start of the first invalid label.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::domain_to_unicode;
+///
+/// assert_eq!(domain_to_unicode("XN--MNCHEN-3YA.de"), Ok("münchen.de".to_string()));
+/// assert!(domain_to_unicode("xn--mnchen-3y!.de").is_err());
+/// ```
+pub fn domain_to_unicode(domain: &str) -> Result<String, DecodeError> {
+    let ascii = domain_to_ascii(domain)?;
+    let labels: Result<Vec<String>, DecodeError> = ascii
+        .split('.')
+        .map(|label| match label.strip_prefix("xn--") {
+            // Already validated by `domain_to_ascii`.
+            Some(encoded) => decode_punycode(encoded),
+            None => Ok(label.to_string()),
+        })
+        .collect();
+    Ok(labels?.join("."))
+}
+
+/// Encodes a string using HTML entity encoding.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the HTML entity encoded text.
+///
+/// # Examples
+///
+/// ```
+/// let text = "<script>alert('hello');</script>";
+/// let result = loki_text::encoding::encode_html_entities(text);
+/// assert_eq!(result, "&lt;script&gt;alert(&#x27;hello&#x27;);&lt;/script&gt;");
+/// ```
+pub fn encode_html_entities(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '<' => "&lt;".to_string(),
+            '>' => "&gt;".to_string(),
+            '&' => "&amp;".to_string(),
+            '"' => "&quot;".to_string(),
+            '\'' => "&#x27;".to_string(),
+            _ => c.to_string(),
+        })
+        .collect()
+}
+
+/// The output context that [`escape_for_context`] escapes for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeContext {
+    /// HTML text content: `&`, `<` and `>` are escaped.
+    HtmlText,
+    /// An HTML attribute value in double quotes: `&`, `"`, `<` and `>` are escaped.
+    DoubleQuotedAttribute,
+    /// An HTML attribute value in single quotes: `&`, `'`, `<` and `>` are escaped.
+    SingleQuotedAttribute,
+    /// An unquoted HTML attribute value: every ASCII character except letters and digits is escaped.
+    UnquotedAttribute,
+    /// XML 1.0 text or attribute values. Characters that XML 1.0 forbids, even as references, become U+FFFD.
+    Xml,
+    /// A JavaScript string literal inside a `<script>` element, safe in either quote style.
+    ScriptString,
+    /// A CSS string literal, safe in either quote style and inside a `<style>` element.
+    CssString,
+}
+
+// Returns `true` for characters that cannot appear in an XML 1.0 document at all.
+fn is_illegal_xml_char(c: char) -> bool {
+    matches!(c, '\u{0}'..='\u{8}' | '\u{B}' | '\u{C}' | '\u{E}'..='\u{1F}' | '\u{FFFE}' | '\u{FFFF}')
+}
+
+fn push_script_escape(escaped: &mut String, c: char) {
+    let mut units = [0u16; 2];
+    for unit in c.encode_utf16(&mut units) {
+        escaped.push_str(&format!("\\u{:04X}", unit));
+    }
+}
+
+/// Escapes text for insertion at a specific point in an HTML, XML, JavaScript or CSS document.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to escape.
+/// * `context` - Where the escaped text will be inserted.
+/// * `ascii_only` - Whether to also escape every non-ASCII character, as a numeric
+///   reference in HTML and XML, or as an escape sequence in JavaScript and CSS.
+///
+/// # Returns
+///
+/// * A `String` containing the escaped text.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{escape_for_context, EscapeContext};
+///
+/// assert_eq!(escape_for_context("a<b & \"c\"", EscapeContext::HtmlText, false), "a&lt;b &amp; \"c\"");
+/// assert_eq!(escape_for_context("it's", EscapeContext::SingleQuotedAttribute, false), "it&#39;s");
+/// assert_eq!(escape_for_context("</script>", EscapeContext::ScriptString, false), "\\u003C\\/script\\u003E");
+/// assert_eq!(escape_for_context("café", EscapeContext::HtmlText, true), "caf&#xE9;");
+/// ```
+pub fn escape_for_context(text: &str, context: EscapeContext, ascii_only: bool) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match context {
+            EscapeContext::HtmlText
+            | EscapeContext::DoubleQuotedAttribute
+            | EscapeContext::SingleQuotedAttribute
+            | EscapeContext::UnquotedAttribute => match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' if context == EscapeContext::DoubleQuotedAttribute => escaped.push_str("&quot;"),
+                '\'' if context == EscapeContext::SingleQuotedAttribute => escaped.push_str("&#39;"),
+                c if context == EscapeContext::UnquotedAttribute && c.is_ascii() && !c.is_ascii_alphanumeric() => {
+                    escaped.push_str(&format!("&#x{:X};", c as u32))
+                }
+                c if ascii_only && !c.is_ascii() => escaped.push_str(&format!("&#x{:X};", c as u32)),
+                c => escaped.push(c),
+            },
+            EscapeContext::Xml => match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                c if is_illegal_xml_char(c) => escaped.push('\u{FFFD}'),
+                // Escape other controls so they survive attribute-value normalization.
+                c if c.is_control() || (ascii_only && !c.is_ascii()) => {
+                    escaped.push_str(&format!("&#x{:X};", c as u32))
+                }
+                c => escaped.push(c),
+            },
+            EscapeContext::ScriptString => match c {
+                '\\' => escaped.push_str("\\\\"),
+                '"' => escaped.push_str("\\\""),
+                '\'' => escaped.push_str("\\'"),
+                '/' => escaped.push_str("\\/"),
+                '\n' => escaped.push_str("\\n"),
+                '\r' => escaped.push_str("\\r"),
+                '\t' => escaped.push_str("\\t"),
+                // `<`, `>` and `&` could close the script element or start a comment.
+                '<' | '>' | '&' | '\u{2028}' | '\u{2029}' => push_script_escape(&mut escaped, c),
+                c if c.is_control() || (ascii_only && !c.is_ascii()) => push_script_escape(&mut escaped, c),
+                c => escaped.push(c),
+            },
+            EscapeContext::CssString => {
+                let unsafe_ascii = c.is_ascii() && !c.is_ascii_alphanumeric() && c != ' ';
+                if unsafe_ascii || c.is_control() || (ascii_only && !c.is_ascii()) {
+                    // The trailing space ends the hex escape and is consumed by the CSS parser.
+                    escaped.push_str(&format!("\\{:X} ", c as u32));
+                } else {
+                    escaped.push(c);
+                }
+            }
+        }
+    }
+    escaped
+}
+
+/// Decodes HTML character references in text content.
+///
+/// This is a single-pass decoder that follows the HTML5 tokenizer. It knows
+/// all named references, including the legacy ones that still match without a
+/// trailing semicolon, such as `&copy` or `&amp`. It handles decimal and hex
+/// numeric references, with or without the semicolon. A code point of zero,
+/// a surrogate, or one outside Unicode becomes U+FFFD, and code points
+/// 0x80–0x9F are read as Windows-1252. Text that is not a reference is copied
+/// unchanged, so `&amp;lt;` decodes to `&lt;`.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the HTML entity encoded text to decode.
+///
+/// # Returns
+///
+/// * A `String` containing the decoded text.
+///
+/// # Examples
+///
+/// ```
+/// let encoded = "&lt;script&gt;alert(&#x27;hello&#x27;);&lt;/script&gt;";
+/// let result = loki_text::encoding::decode_html_entities(encoded);
+/// assert_eq!(result, "<script>alert('hello');</script>");
+///
+/// let result = loki_text::encoding::decode_html_entities("caf&eacute; &euro;5 &#x1F600; &amp;lt;");
+/// assert_eq!(result, "café €5 😀 &lt;");
+/// ```
+pub fn decode_html_entities(encoded: &str) -> String {
+    decode_character_references(encoded, false)
+}
+
+/// Decodes HTML character references in an attribute value.
+///
+/// This works like [`decode_html_entities`], except that a legacy named reference
+/// without its semicolon is left alone when it is followed by `=` or an
+/// alphanumeric character. That keeps query strings such as `?a=1&copy=2` intact.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the attribute value to decode.
+///
+/// # Returns
+///
+/// * A `String` containing the decoded attribute value.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::decode_html_attribute("/search?q=1&copy=2&amp;x=&copy");
+/// assert_eq!(result, "/search?q=1&copy=2&x=©");
+/// ```
+pub fn decode_html_attribute(encoded: &str) -> String {
+    decode_character_references(encoded, true)
+}
+
+// The characters that the bytes 0x80..=0x9F stand for in Windows-1252; the five
+// unassigned bytes map to the matching C1 control.
+const WINDOWS_1252_C1: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{2C6}', '\u{2030}', '\u{160}', '\u{2039}', '\u{152}', '\u{8D}', '\u{17D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{2DC}', '\u{2122}', '\u{161}', '\u{203A}', '\u{153}', '\u{9D}', '\u{17E}', '\u{178}',
+];
+
+// Looks up a named reference, including its semicolon if it has one.
+fn html_entity(name: &str) -> Option<&'static str> {
+    html_entities::ENTITIES
+        .binary_search_by(|(entity, _)| entity.as_bytes().cmp(name.as_bytes()))
+        .ok()
+        .map(|index| html_entities::ENTITIES[index].1)
+}
+
+// Maps a numeric character reference to the character the HTML5 tokenizer produces.
+fn numeric_reference_char(code: u32) -> char {
+    match code {
+        0x80..=0x9F => WINDOWS_1252_C1[(code - 0x80) as usize],
+        _ => char::from_u32(code).filter(|&c| c != '\0').unwrap_or('\u{FFFD}'),
+    }
+}
+
+// Decodes the reference starting at the `&` at byte `start`, returning the
+// replacement text and the byte length consumed.
+fn character_reference(text: &str, start: usize, in_attribute: bool) -> Option<(Cow<'static, str>, usize)> {
+    let bytes = text.as_bytes();
+    let mut i = start + 1;
+    if bytes.get(i) == Some(&b'#') {
+        i += 1;
+        let hex = matches!(bytes.get(i), Some(b'x' | b'X'));
+        if hex {
+            i += 1;
+        }
+        let radix = if hex { 16 } else { 10 };
+        let digits_start = i;
+        let mut code = 0u32;
+        while let Some(digit) = bytes.get(i).and_then(|&b| (b as char).to_digit(radix)) {
+            // Saturate above the Unicode range; the exact value no longer matters.
+            code = code.saturating_mul(radix).saturating_add(digit).min(0x11_0000);
+            i += 1;
+        }
+        if i == digits_start {
+            return None;
+        }
+        if bytes.get(i) == Some(&b';') {
+            i += 1;
+        }
+        return Some((Cow::Owned(numeric_reference_char(code).to_string()), i - start));
+    }
+
+    // The longest named reference is 32 bytes long, semicolon included.
+    let mut end = i;
+    while end < bytes.len() && end - i < 32 && bytes[end].is_ascii_alphanumeric() {
+        end += 1;
+    }
+    if end < bytes.len() && bytes[end] == b';' && end - i < 32 {
+        end += 1;
+    }
+    (i + 1..=end).rev().find_map(|candidate_end| {
+        let name = &text[i..candidate_end];
+        let value = html_entity(name)?;
+        let next = bytes.get(candidate_end).copied();
+        let ambiguous = !name.ends_with(';')
+            && next.is_some_and(|b| b == b'=' || b.is_ascii_alphanumeric());
+        if in_attribute && ambiguous {
+            return None;
+        }
+        Some((Cow::Borrowed(value), candidate_end - start))
+    })
+}
+
+fn decode_character_references(encoded: &str, in_attribute: bool) -> String {
+    let mut decoded = String::with_capacity(encoded.len());
+    let mut rest = 0;
+    let mut search = 0;
+    while let Some(offset) = encoded[search..].find('&') {
+        let start = search + offset;
+        match character_reference(encoded, start, in_attribute) {
+            Some((replacement, consumed)) => {
+                decoded.push_str(&encoded[rest..start]);
+                decoded.push_str(&replacement);
+                rest = start + consumed;
+                search = rest;
+            }
+            None => search = start + 1,
+        }
+    }
+    decoded.push_str(&encoded[rest..]);
+    decoded
+}
+
+/// Escapes text for use inside a JSON string literal, without the surrounding quotes.
+///
+/// Quotes, backslashes and control characters are always escaped, using the
+/// short forms `\b`, `\f`, `\n`, `\r` and `\t` where they exist.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to escape.
+/// * `ascii_only` - Whether to escape every non-ASCII character as `\uXXXX`, using surrogate pairs above U+FFFF.
+///
+/// # Returns
+///
+/// * A `String` containing the escaped text.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::escape_json_string("say \"hi\"\n", false);
+/// assert_eq!(result, "say \\\"hi\\\"\\n");
+///
+/// let result = loki_text::encoding::escape_json_string("é😀", true);
+/// assert_eq!(result, "\\u00e9\\ud83d\\ude00");
+/// ```
+pub fn escape_json_string(text: &str, ascii_only: bool) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{C}' => escaped.push_str("\\f"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 || (ascii_only && !c.is_ascii()) => {
+                let mut units = [0u16; 2];
+                for unit in c.encode_utf16(&mut units) {
+                    escaped.push_str(&format!("\\u{:04x}", unit));
+                }
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+// Reads the four hex digits of a `\u` escape whose backslash is at `start`.
+fn json_unicode_escape(escaped: &str, start: usize) -> Result<u16, DecodeError> {
+    escaped
+        .get(start + 2..start + 6)
+        .filter(|digits| digits.bytes().all(|b| b.is_ascii_hexdigit()))
+        .and_then(|digits| u16::from_str_radix(digits, 16).ok())
+        .ok_or_else(|| DecodeError::new(start, "Invalid \\u escape: expected 4 hex digits"))
+}
+
+/// Unescapes the contents of a JSON string literal, without the surrounding quotes.
+///
+/// Surrogate pairs written as two `\u` escapes are combined. Unknown escapes,
+/// malformed `\u` escapes, unpaired surrogates, raw control characters and
+/// unescaped quotes are all rejected with the byte offset of the problem.
+///
+/// # Arguments
+///
+/// * `escaped` - A string slice that holds the escaped JSON string contents.
+///
+/// # Returns
+///
+/// * A `Result<String, DecodeError>` containing the unescaped text or the first error found.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::unescape_json_string("caf\\u00e9 \\ud83d\\ude00\\n");
+/// assert_eq!(result, Ok("café 😀\n".to_string()));
+///
+/// let error = loki_text::encoding::unescape_json_string("bad \\q").unwrap_err();
+/// assert_eq!(error.position, 4);
+/// ```
+pub fn unescape_json_string(escaped: &str) -> Result<String, DecodeError> {
+    let mut unescaped = String::with_capacity(escaped.len());
+    let mut chars = escaped.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\\' => {
+                let (_, kind) = chars
+                    .next()
+                    .ok_or_else(|| DecodeError::new(i, "Incomplete escape sequence"))?;
+                match kind {
+                    '"' => unescaped.push('"'),
+                    '\\' => unescaped.push('\\'),
+                    '/' => unescaped.push('/'),
+                    'b' => unescaped.push('\u{8}'),
+                    'f' => unescaped.push('\u{C}'),
+                    'n' => unescaped.push('\n'),
+                    'r' => unescaped.push('\r'),
+                    't' => unescaped.push('\t'),
+                    'u' => {
+                        let unit = json_unicode_escape(escaped, i)?;
+                        chars.nth(3);
+                        let code = match unit {
+                            0xD800..=0xDBFF => {
+                                let low_start = i + 6;
+                                let low = if escaped[low_start..].starts_with("\\u") {
+                                    json_unicode_escape(escaped, low_start)?
+                                } else {
+                                    0
+                                };
+                                if !(0xDC00..=0xDFFF).contains(&low) {
+                                    return Err(DecodeError::new(i, "Unpaired high surrogate"));
+                                }
+                                chars.nth(5);
+                                0x10000 + (((unit as u32) - 0xD800) << 10) + ((low as u32) - 0xDC00)
+                            }
+                            0xDC00..=0xDFFF => return Err(DecodeError::new(i, "Unpaired low surrogate")),
+                            _ => unit as u32,
+                        };
+                        unescaped.push(char::from_u32(code).unwrap_or('\u{FFFD}'));
+                    }
+                    other => {
+                        return Err(DecodeError::new(i, format!("Invalid escape sequence '\\{}'", other)));
+                    }
+                }
+            }
+            '"' => return Err(DecodeError::new(i, "Unescaped quote")),
+            c if (c as u32) < 0x20 => {
+                return Err(DecodeError::new(i, format!("Unescaped control character U+{:04X}", c as u32)));
+            }
+            c => unescaped.push(c),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Quotes a string so a POSIX shell reads it back as a single word.
+///
+/// Strings made only of characters that are never special to the shell are
+/// returned unchanged. Anything else is wrapped in single quotes, with each
+/// embedded `'` written as `'\''`.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the word to quote.
+///
+/// # Returns
+///
+/// * A `String` containing the quoted word.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(loki_text::encoding::shell_quote("file.txt"), "file.txt");
+/// assert_eq!(loki_text::encoding::shell_quote("it's here"), "'it'\\''s here'");
+/// assert_eq!(loki_text::encoding::shell_quote(""), "''");
+/// ```
+pub fn shell_quote(text: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c);
+    if !text.is_empty() && text.chars().all(safe) {
+        return text.to_string();
+    }
+    format!("'{}'", text.replace('\'', "'\\''"))
+}
+
+/// Quotes each argument and joins them into a command line.
+///
+/// # Arguments
+///
+/// * `args` - The words of the command line.
+///
+/// # Returns
+///
+/// * A `String` containing the command line, which [`shell_split`] turns back into `args`.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::shell_join(&["git", "commit", "-m", "fix: it's done"]);
+/// assert_eq!(result, "git commit -m 'fix: it'\\''s done'");
+/// ```
+pub fn shell_join<S: AsRef<str>>(args: &[S]) -> String {
+    args.iter()
+        .map(|arg| shell_quote(arg.as_ref()))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Splits a command line into words the way a POSIX shell does, without expansions.
+///
+/// Words are separated by unquoted whitespace. Single quotes keep everything
+/// literally. Inside double quotes, a backslash escapes only `$`, `` ` ``, `"`,
+/// `\` and a newline. Outside quotes, a backslash escapes any character, and a
+/// backslash before a newline continues the line. A `#` at the start of a word
+/// comments out the rest of the line.
+///
+/// # Arguments
+///
+/// * `command` - A string slice that holds the command line.
+///
+/// # Returns
+///
+/// * A `Result<Vec<String>, DecodeError>` containing the words, or an error for an unterminated quote or a trailing backslash.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::shell_split(r#"cp "my file.txt" 'dest dir'/ # copy"#);
+/// assert_eq!(result, Ok(vec!["cp".to_string(), "my file.txt".to_string(), "dest dir/".to_string()]));
+///
+/// let error = loki_text::encoding::shell_split("echo 'oops").unwrap_err();
+/// assert_eq!(error.position, 5);
+/// ```
+pub fn shell_split(command: &str) -> Result<Vec<String>, DecodeError> {
+    let mut words = Vec::new();
+    let mut word: Option<String> = None;
+    let mut chars = command.char_indices().peekable();
+
+    while let Some((i, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {
+                if let Some(done) = word.take() {
+                    words.push(done);
+                }
+            }
+            '#' if word.is_none() => {
+                while chars.next_if(|&(_, c)| c != '\n').is_some() {}
+            }
+            '\\' => match chars.next() {
+                Some((_, '\n')) => {}
+                Some((_, escaped)) => word.get_or_insert_with(String::new).push(escaped),
+                None => return Err(DecodeError::new(i, "Trailing backslash")),
+            },
+            '\'' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '\'')) => break,
+                        Some((_, quoted)) => current.push(quoted),
+                        None => return Err(DecodeError::new(i, "Unterminated single quote")),
+                    }
+                }
+            }
+            '"' => {
+                let current = word.get_or_insert_with(String::new);
+                loop {
+                    match chars.next() {
+                        Some((_, '"')) => break,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, '\n')) => {}
+                            Some((_, escaped @ ('$' | '`' | '"' | '\\'))) => current.push(escaped),
+                            Some((_, other)) => {
+                                current.push('\\');
+                                current.push(other);
+                            }
+                            None => return Err(DecodeError::new(i, "Unterminated double quote")),
+                        },
+                        Some((_, quoted)) => current.push(quoted),
+                        None => return Err(DecodeError::new(i, "Unterminated double quote")),
+                    }
+                }
+            }
+            c => word.get_or_insert_with(String::new).push(c),
+        }
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Encodes bytes as MIME quoted-printable (RFC 2045).
+///
+/// Lines are kept to 76 characters with `=` soft line breaks. Spaces and tabs
+/// are encoded where they would end a line. In text mode, LF and CRLF in the
+/// input become CRLF hard line breaks. In binary mode every CR and LF is encoded,
+/// so any byte sequence round-trips.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+/// * `binary` - Whether to encode line breaks instead of treating them as hard breaks.
+///
+/// # Returns
+///
+/// * A `String` containing the quoted-printable encoding.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::encode_quoted_printable("café = 1 \nok".as_bytes(), false);
+/// assert_eq!(result, "caf=C3=A9 =3D 1=20\r\nok");
+/// ```
+pub fn encode_quoted_printable(bytes: &[u8], binary: bool) -> String {
+    const MAX_LINE: usize = 76;
+    let mut encoded = String::with_capacity(bytes.len() * 3 / 2);
+    let mut line_len = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        let b = bytes[i];
+        if !binary && (b == b'\n' || (b == b'\r' && bytes.get(i + 1) == Some(&b'\n'))) {
+            encoded.push_str("\r\n");
+            line_len = 0;
+            i += if b == b'\r' { 2 } else { 1 };
+            continue;
+        }
+        let at_line_end = i + 1 == bytes.len()
+            || (!binary && (bytes[i + 1] == b'\n' || bytes[i + 1..].starts_with(b"\r\n")));
+        let literal = matches!(b, b'!'..=b'<' | b'>'..=b'~') || ((b == b' ' || b == b'\t') && !at_line_end);
+        let token = if literal { (b as char).to_string() } else { format!("={:02X}", b) };
+
+        // Leave room for the `=` of a soft break unless this token ends the line.
+        let limit = if at_line_end { MAX_LINE } else { MAX_LINE - 1 };
+        if line_len + token.len() > limit {
+            encoded.push_str("=\r\n");
+            line_len = 0;
+        }
+        encoded.push_str(&token);
+        line_len += token.len();
+        i += 1;
+    }
+    encoded
+}
+
+/// Decodes MIME quoted-printable (RFC 2045) into bytes.
+///
+/// Soft line breaks (`=` at the end of a line) are removed, as is trailing
+/// whitespace on each line, which RFC 2045 treats as transport padding. Hard line
+/// breaks are kept as they appear. Hex digits are accepted in either case.
+///
+/// # Arguments
+///
+/// * `encoded` - A string slice that holds the quoted-printable text.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes, or an error locating a malformed `=` sequence.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::decode_quoted_printable("caf=C3=A9 long=\r\n line  \r\nend");
+/// assert_eq!(result, Ok("café long line\r\nend".as_bytes().to_vec()));
+/// ```
+pub fn decode_quoted_printable(encoded: &str) -> Result<Vec<u8>, DecodeError> {
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut offset = 0;
+    for line in encoded.split_inclusive('\n') {
+        let (content, newline) = match line.strip_suffix("\r\n").or_else(|| line.strip_suffix('\n')) {
+            Some(content) => (content, &line[content.len()..]),
+            None => (line, ""),
+        };
+        let content = content.trim_end_matches([' ', '\t']);
+        let (content, soft_break) = match content.strip_suffix('=') {
+            Some(content) => (content, true),
+            None => (content, false),
+        };
+
+        let bytes = content.as_bytes();
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'=' {
+                let byte = bytes
+                    .get(i + 1..i + 3)
+                    .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                    .and_then(|hex| u8::from_str_radix(std::str::from_utf8(hex).ok()?, 16).ok())
+                    .ok_or_else(|| DecodeError::new(offset + i, "Invalid quoted-printable escape"))?;
+                decoded.push(byte);
+                i += 3;
+            } else {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+        if !soft_break {
+            decoded.extend_from_slice(newline.as_bytes());
+        }
+        offset += line.len();
+    }
+    Ok(decoded)
+}
+
+/// The transfer encoding of an RFC 2047 encoded-word.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WordEncoding {
+    /// The `B` encoding: Base64.
+    Base64,
+    /// The `Q` encoding: quoted-printable with `_` for spaces.
+    Q,
+}
+
+// Q-encodes bytes for an encoded-word in any header position.
+fn q_encode(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            b' ' => "_".to_string(),
+            b if b.is_ascii_alphanumeric() || b"!*+-/".contains(&b) => (b as char).to_string(),
+            b => format!("={:02X}", b),
+        })
+        .collect()
+}
+
+// Decodes a Q-encoded payload, returning the offset of a malformed `=` sequence on error.
+fn q_decode(payload: &str) -> Result<Vec<u8>, usize> {
+    let bytes = payload.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'_' => decoded.push(b' '),
+            b'=' => {
+                let hex = bytes.get(i + 1..i + 3).ok_or(i)?;
+                let digit = |d: u8| (d as char).to_digit(16).ok_or(i);
+                decoded.push((digit(hex[0])? * 16 + digit(hex[1])?) as u8);
+                i += 2;
+            }
+            b => decoded.push(b),
+        }
+        i += 1;
+    }
+    Ok(decoded)
+}
+
+/// Encodes header text as one or more RFC 2047 UTF-8 encoded-words.
+///
+/// Each encoded-word is kept to 75 characters and never splits a character;
+/// consecutive words are separated by a folding `\r\n `.
 ///
 /// # Arguments
 ///
-/// * `text` - A string slice that holds the text to encode.
+/// * `text` - A string slice that holds the header text.
+/// * `encoding` - Whether to use the `B` (Base64) or `Q` encoding.
 ///
 /// # Returns
 ///
-/// * A `String` containing the HTML entity encoded text.
+/// * A `String` containing the encoded-words.
 ///
 /// # Examples
 ///
 /// ```
-/// let text = "<script>alert('hello');</script>";
-/// let result = loki_text::encoding::encode_html_entities(text);
-/// assert_eq!(result, "&lt;script&gt;alert(&#x27;hello&#x27;);&lt;/script&gt;");
+/// use loki_text::encoding::{encode_encoded_word, WordEncoding};
+///
+/// assert_eq!(encode_encoded_word("Grüße", WordEncoding::Q), "=?UTF-8?Q?Gr=C3=BC=C3=9Fe?=");
+/// assert_eq!(encode_encoded_word("Grüße", WordEncoding::Base64), "=?UTF-8?B?R3LDvMOfZQ==?=");
 /// ```
-pub fn encode_html_entities(text: &str) -> String {
-    text.chars()
-        .map(|c| match c {
-            '<' => "&lt;".to_string(),
-            '>' => "&gt;".to_string(),
-            '&' => "&amp;".to_string(),
-            '"' => "&quot;".to_string(),
-            '\'' => "&#x27;".to_string(),
-            _ => c.to_string(),
-        })
-        .collect()
+pub fn encode_encoded_word(text: &str, encoding: WordEncoding) -> String {
+    // 75 characters minus the `=?UTF-8?X?` prefix and the `?=` suffix.
+    const MAX_PAYLOAD: usize = 75 - 12;
+    let (tag, encode): (char, fn(&[u8]) -> String) = match encoding {
+        WordEncoding::Base64 => ('B', encode_base64_bytes),
+        WordEncoding::Q => ('Q', q_encode),
+    };
+
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut end = 0;
+    for (i, c) in text.char_indices() {
+        let next = i + c.len_utf8();
+        if end > start && encode(&text.as_bytes()[start..next]).len() > MAX_PAYLOAD {
+            words.push(&text[start..end]);
+            start = end;
+        }
+        end = next;
+    }
+    if end > start || words.is_empty() {
+        words.push(&text[start..end]);
+    }
+
+    words
+        .iter()
+        .map(|word| format!("=?UTF-8?{}?{}?=", tag, encode(word.as_bytes())))
+        .collect::<Vec<String>>()
+        .join("\r\n ")
+}
+
+// Converts bytes in a MIME charset to a `String`, or `None` if the charset is unknown or the bytes are invalid in it.
+fn decode_mime_charset(bytes: Vec<u8>, charset: &str) -> Option<String> {
+    let charset = charset::Charset::from_label(charset)?;
+    charset::decode(&bytes, charset, charset::ErrorMode::Strict).ok()
 }
 
-/// Decodes HTML entity encoded string.
+// Decodes the bytes gathered from adjacent encoded-words and appends them to `decoded`.
+fn flush_encoded_word(pending: &mut Option<(String, Vec<u8>, usize)>, decoded: &mut String) -> Result<(), DecodeError> {
+    if let Some((charset, bytes, position)) = pending.take() {
+        let text = decode_mime_charset(bytes, &charset)
+            .ok_or_else(|| DecodeError::new(position, format!("Cannot decode encoded-word as charset '{}'", charset)))?;
+        decoded.push_str(&text);
+    }
+    Ok(())
+}
+
+// Matches `=?charset[*language]?encoding?payload?=`, compiled once for all headers.
+fn encoded_word_regex() -> &'static Regex {
+    static WORD: OnceLock<Regex> = OnceLock::new();
+    WORD.get_or_init(|| {
+        Regex::new(r"=\?([^?\s*]+)(?:\*[^?\s]*)?\?([bBqQ])\?([^?\s]*)\?=").expect("valid encoded-word regex")
+    })
+}
+
+/// Decodes every RFC 2047 encoded-word in a header value.
+///
+/// Plain text is kept, whitespace between adjacent encoded-words is dropped,
+/// and the bytes of adjacent words in the same charset are joined before
+/// decoding, so a character split across two words still decodes. UTF-8,
+/// US-ASCII and ISO-8859-1 are supported.
 ///
 /// # Arguments
 ///
-/// * `encoded` - A string slice that holds the HTML entity encoded text to decode.
+/// * `header` - A string slice that holds the header value.
 ///
 /// # Returns
 ///
-/// * A `String` containing the decoded text.
+/// * A `Result<String, DecodeError>` containing the decoded header, or an error locating an encoded-word with an unsupported charset or invalid contents.
 ///
 /// # Examples
 ///
 /// ```
-/// let encoded = "&lt;script&gt;alert(&#x27;hello&#x27;);&lt;/script&gt;";
-/// let result = loki_text::encoding::decode_html_entities(encoded);
-/// assert_eq!(result, "<script>alert('hello');</script>");
+/// let header = "Re: =?UTF-8?Q?caf=C3=A9?= =?utf-8?b?IGNyw6htZQ==?= and more";
+/// let result = loki_text::encoding::decode_encoded_words(header);
+/// assert_eq!(result, Ok("Re: café crème and more".to_string()));
 /// ```
-pub fn decode_html_entities(encoded: &str) -> String {
-    encoded
-        .replace("&lt;", "<")
-        .replace("&gt;", ">")
-        .replace("&amp;", "&")
-        .replace("&quot;", "\"")
-        .replace("&#x27;", "'")
-        .replace("&#39;", "'")
+pub fn decode_encoded_words(header: &str) -> Result<String, DecodeError> {
+    let mut decoded = String::with_capacity(header.len());
+    let mut pending: Option<(String, Vec<u8>, usize)> = None;
+    let mut last_end = 0;
+
+    for captures in encoded_word_regex().captures_iter(header) {
+        let whole = captures.get(0).map_or(0..0, |m| m.range());
+        let charset = &captures[1];
+        let payload = captures.get(3).map_or("", |m| m.as_str());
+        let payload_start = captures.get(3).map_or(whole.start, |m| m.start());
+
+        let gap = &header[last_end..whole.start];
+        if pending.is_none() || !gap.chars().all(char::is_whitespace) {
+            flush_encoded_word(&mut pending, &mut decoded)?;
+            decoded.push_str(gap);
+        }
+
+        let bytes = if captures[2].eq_ignore_ascii_case("b") {
+            decode_base64_bytes(payload).map_err(|e| DecodeError::new(payload_start + e.position, e.message))?
+        } else {
+            q_decode(payload).map_err(|offset| DecodeError::new(payload_start + offset, "Invalid Q-encoded sequence"))?
+        };
+
+        match &mut pending {
+            Some((pending_charset, pending_bytes, _)) if pending_charset.eq_ignore_ascii_case(charset) => {
+                pending_bytes.extend(bytes);
+            }
+            _ => {
+                flush_encoded_word(&mut pending, &mut decoded)?;
+                pending = Some((charset.to_string(), bytes, whole.start));
+            }
+        }
+        last_end = whole.end;
+    }
+    flush_encoded_word(&mut pending, &mut decoded)?;
+    decoded.push_str(&header[last_end..]);
+    Ok(decoded)
 }
 
 /// Encodes a string using ROT13 cipher.
@@ -351,9 +2877,7 @@ pub fn decode_rot13(encoded: &str) -> String {
 /// assert_eq!(result, "0100100001101001");
 /// ```
 pub fn to_binary(text: &str) -> String {
-    text.bytes()
-        .map(|b| format!("{:08b}", b))
-        .collect()
+    to_binary_bytes(text.as_bytes())
 }
 
 /// Converts binary string back to text.
@@ -374,23 +2898,231 @@ pub fn to_binary(text: &str) -> String {
 /// assert_eq!(result, Ok("Hi".to_string()));
 /// ```
 pub fn from_binary(binary: &str) -> Result<String, String> {
-    if binary.len() % 8 != 0 {
-        return Err("Binary string length must be a multiple of 8".to_string());
-    }
-    
-    let bytes: Result<Vec<u8>, String> = (0..binary.len())
-        .step_by(8)
-        .map(|i| {
-            u8::from_str_radix(&binary[i..i + 8], 2)
-                .map_err(|_| format!("Invalid binary sequence at position {}", i))
-        })
+    from_binary_bytes(binary).map_err(|e| e.to_string()).and_then(utf8_string)
+}
+
+/// Converts bytes to their binary representation, eight digits per byte.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to convert.
+///
+/// # Returns
+///
+/// * A `String` containing the binary representation.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::to_binary_bytes(&[0xff, 0x01]);
+/// assert_eq!(result, "1111111100000001");
+/// ```
+pub fn to_binary_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:08b}", b)).collect()
+}
+
+/// Converts a binary string back to bytes.
+///
+/// # Arguments
+///
+/// * `binary` - A string slice that holds the binary data.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the decoded bytes, or an error locating an invalid digit or a length that is not a multiple of 8.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::encoding::from_binary_bytes("1111111100000001");
+/// assert_eq!(result, Ok(vec![0xff, 0x01]));
+/// ```
+pub fn from_binary_bytes(binary: &str) -> Result<Vec<u8>, DecodeError> {
+    let digits = binary.as_bytes();
+    if let Some(i) = digits.iter().position(|&b| b != b'0' && b != b'1') {
+        return Err(DecodeError::new(i, format!("Invalid binary character '{}'", char_at(binary, i))));
+    }
+    if !digits.len().is_multiple_of(8) {
+        return Err(DecodeError::new(
+            digits.len(),
+            "Binary string length must be a multiple of 8",
+        ));
+    }
+    Ok(digits
+        .chunks(8)
+        .map(|chunk| chunk.iter().fold(0u8, |byte, &d| (byte << 1) | (d - b'0')))
+        .collect())
+}
+
+/// A text encoding that [`detect_formats`] can recognise.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    /// Base64 in the standard or URL-safe alphabet, as read by [`decode_base64_with`].
+    Base64,
+    /// Hex digits, as read by [`decode_hex_bytes`].
+    Hex,
+    /// Percent-encoding, as read by [`decode_url_bytes`].
+    Url,
+    /// Groups of eight binary digits, as read by [`from_binary_bytes`].
+    Binary,
+    /// HTML character references, as read by [`decode_html_entities`].
+    Html,
+    /// ROT13-rotated English, as read by [`decode_rot13`].
+    Rot13,
+}
+
+// The fraction of characters that are printable or ordinary whitespace. Empty text counts as printable.
+fn printable_ratio(text: &str) -> f64 {
+    let total = text.chars().count();
+    if total == 0 {
+        return 1.0;
+    }
+    let printable = text
+        .chars()
+        .filter(|&c| c != '\u{FFFD}' && (!c.is_control() || matches!(c, '\n' | '\r' | '\t')))
+        .count();
+    printable as f64 / total as f64
+}
+
+// How much decoded bytes look like text: their printable ratio, halved when they are not UTF-8.
+fn text_likeness(bytes: &[u8]) -> f64 {
+    match std::str::from_utf8(bytes) {
+        Ok(text) => printable_ratio(text),
+        Err(_) => printable_ratio(&String::from_utf8_lossy(bytes)) / 2.0,
+    }
+}
+
+const COMMON_ENGLISH_WORDS: [&str; 32] = [
+    "the", "be", "to", "of", "and", "a", "in", "that", "have", "i", "it", "for", "not", "on", "with", "he",
+    "as", "you", "do", "at", "this", "but", "his", "by", "from", "they", "we", "is", "are", "was", "or", "all",
+];
+
+// Letter frequencies of English text, in percent, for `a` to `z`.
+const ENGLISH_LETTER_FREQUENCIES: [f64; 26] = [
+    8.2, 1.5, 2.8, 4.3, 12.7, 2.2, 2.0, 6.1, 7.0, 0.15, 0.77, 4.0, 2.4, 6.7, 7.5, 1.9, 0.095, 6.0, 6.3, 9.1,
+    2.8, 0.98, 2.4, 0.15, 2.0, 0.074,
+];
+
+// Scores how much text looks like English, from 0.0 to 1.0, mixing the share of
+// common words with the overlap between its letter frequencies and English ones.
+fn english_score(text: &str) -> f64 {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_lowercase)
         .collect();
-    
-    match bytes {
-        Ok(bytes) => String::from_utf8(bytes)
-            .map_err(|_| "Decoded bytes are not valid UTF-8".to_string()),
-        Err(e) => Err(e),
+    if words.is_empty() {
+        return 0.0;
+    }
+    let common = words.iter().filter(|word| COMMON_ENGLISH_WORDS.contains(&word.as_str())).count();
+
+    let mut counts = [0usize; 26];
+    for b in words.iter().flat_map(|word| word.bytes()) {
+        counts[(b - b'a') as usize] += 1;
+    }
+    let letters: usize = counts.iter().sum();
+    let overlap: f64 = counts
+        .iter()
+        .zip(ENGLISH_LETTER_FREQUENCIES)
+        .map(|(&count, expected)| (count as f64 / letters as f64).min(expected / 100.0))
+        .sum();
+
+    0.6 * (common as f64 / words.len() as f64) + 0.4 * overlap
+}
+
+/// Guesses which encodings a string might be in.
+///
+/// Each candidate format is checked by decoding the text and scoring how much
+/// the result looks like text. Line breaks are ignored for Base64, and all ASCII
+/// whitespace for hex and binary, since pasted blobs are often wrapped. ROT13 is only suggested when
+/// rotating the text makes it look more like English.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to inspect.
+///
+/// # Returns
+///
+/// * A `Vec<(Format, f64)>` of plausible formats with a confidence between 0.0 and 1.0, most likely first. It is empty if no format fits.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::{detect_formats, Format};
+///
+/// assert_eq!(detect_formats("aGVsbG8gd29ybGQ=")[0].0, Format::Base64);
+/// assert_eq!(detect_formats("68656c6c6f20776f726c64")[0].0, Format::Hex);
+/// assert_eq!(detect_formats("uryyb jbeyq, guvf vf n grfg")[0].0, Format::Rot13);
+/// assert!(detect_formats("hello world").is_empty());
+/// ```
+pub fn detect_formats(text: &str) -> Vec<(Format, f64)> {
+    let compact: String = text.chars().filter(|c| !c.is_ascii_whitespace()).collect();
+    let has_upper = compact.bytes().any(|b| b.is_ascii_uppercase());
+    let has_lower = compact.bytes().any(|b| b.is_ascii_lowercase());
+    let has_digit = compact.bytes().any(|b| b.is_ascii_digit());
+    let all_hex = compact.bytes().all(|b| b.is_ascii_hexdigit());
+    let mut guesses = Vec::new();
+
+    if compact.len() >= 8 && compact.bytes().all(|b| b == b'0' || b == b'1') {
+        if let Ok(bytes) = from_binary_bytes(&compact) {
+            guesses.push((Format::Binary, 0.6 + 0.39 * text_likeness(&bytes)));
+        }
     }
+
+    if compact.len() >= 2 && all_hex {
+        if let Ok(bytes) = decode_hex_bytes(&compact) {
+            // A run of decimal digits is more likely a number than hex.
+            let digits_only = if has_upper || has_lower { 1.0 } else { 0.6 };
+            guesses.push((Format::Hex, (0.5 + 0.45 * text_likeness(&bytes)) * digits_only));
+        }
+    }
+
+    // Base64 is wrapped at line breaks but never split by spaces.
+    let unwrapped: String = text.trim().chars().filter(|&c| c != '\r' && c != '\n').collect();
+    if unwrapped.len() >= 4 && !unwrapped.contains([' ', '\t']) {
+        let decoded = decode_base64_with(&unwrapped, &Base64Config::STANDARD)
+            .or_else(|_| decode_base64_with(&unwrapped, &Base64Config::URL_SAFE));
+        if let Ok(bytes) = decoded {
+            let mut confidence = 0.4 + 0.5 * text_likeness(&bytes);
+            // Plain words and hex strings are valid Base64 too, but real Base64 mixes case.
+            if !(has_upper && has_lower) || all_hex {
+                confidence *= 0.5;
+            }
+            if !has_digit && !compact.contains(['=', '+', '/', '-', '_']) {
+                confidence *= 0.8;
+            }
+            guesses.push((Format::Base64, confidence));
+        }
+    }
+
+    let escapes = text.match_indices('%').filter(|&(i, _)| {
+        text.as_bytes().get(i + 1..i + 3).is_some_and(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+    });
+    let escape_count = escapes.count();
+    if escape_count > 0 {
+        if let Ok(bytes) = decode_url_bytes(text) {
+            let utf8 = if std::str::from_utf8(&bytes).is_ok() { 1.0 } else { 0.7 };
+            guesses.push((Format::Url, (0.5 + 0.15 * escape_count as f64).min(0.95) * utf8));
+        }
+    }
+
+    let references = text
+        .match_indices('&')
+        .filter_map(|(start, _)| character_reference(text, start, false).map(|(_, length)| (start, length)))
+        .filter(|&(start, length)| text.as_bytes()[start + length - 1] == b';')
+        .count();
+    if references > 0 {
+        guesses.push((Format::Html, (0.5 + 0.15 * references as f64).min(0.95)));
+    }
+
+    let plain = english_score(text);
+    let rotated = english_score(&decode_rot13(text));
+    if rotated > plain + 0.1 {
+        guesses.push((Format::Rot13, (rotated + 0.2).min(0.95)));
+    }
+
+    guesses.sort_by(|a, b| b.1.total_cmp(&a.1));
+    guesses
 }
 
 #[cfg(test)]
@@ -409,6 +3141,240 @@ mod tests {
         let encoded = "aGVsbG8gd29ybGQ=";
         let result = decode_base64(encoded);
         assert_eq!(result, Ok("hello world".to_string()));
+
+        assert!(decode_base64("aGk=junk").is_err());
+        assert_eq!(decode_base64("/w=="), Err("Decoded bytes are not valid UTF-8".to_string()));
+    }
+
+    #[test]
+    fn test_encode_base64_with() {
+        assert_eq!(encode_base64_with(b"", &Base64Config::STANDARD), "");
+        assert_eq!(encode_base64_with(b"f", &Base64Config::STANDARD), "Zg==");
+        assert_eq!(encode_base64_with(b"fo", &Base64Config::STANDARD), "Zm8=");
+        assert_eq!(encode_base64_with(b"foo", &Base64Config::STANDARD), "Zm9v");
+        assert_eq!(encode_base64_with(&[0xfb, 0xff, 0xbf], &Base64Config::STANDARD), "+/+/");
+        assert_eq!(encode_base64_with(&[0xfb, 0xff, 0xbf], &Base64Config::URL_SAFE), "-_-_");
+        assert_eq!(encode_base64_with(b"fo", &Base64Config::URL_SAFE_NO_PAD), "Zm8");
+
+        let wrapped = encode_base64_with(&[0u8; 60], &Base64Config::MIME);
+        let lines: Vec<&str> = wrapped.split("\r\n").collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0].len(), 76);
+        assert_eq!(lines[1].len(), 4);
+    }
+
+    #[test]
+    fn test_decode_base64_with() {
+        let standard = Base64Config::STANDARD;
+        assert_eq!(decode_base64_with("Zm8=", &standard), Ok(b"fo".to_vec()));
+        assert_eq!(decode_base64_with("Zm8", &standard), Ok(b"fo".to_vec()));
+        assert_eq!(decode_base64_with("+/+/", &standard), Ok(vec![0xfb, 0xff, 0xbf]));
+        assert_eq!(decode_base64_with("-_-_", &Base64Config::URL_SAFE), Ok(vec![0xfb, 0xff, 0xbf]));
+
+        let required = Base64Config { padding: Padding::Required, ..standard };
+        assert_eq!(decode_base64_with("Zm8", &required).unwrap_err().position, 3);
+        assert_eq!(decode_base64_with("Zm8=", &Base64Config::URL_SAFE_NO_PAD).unwrap_err().position, 3);
+        assert_eq!(decode_base64_with("Zg=", &standard).unwrap_err().position, 2);
+        assert_eq!(decode_base64_with("Zm8=Zm8=", &standard).unwrap_err().position, 4);
+        assert_eq!(decode_base64_with("Zm9vZ", &standard).unwrap_err().position, 4);
+        // "Zm9=" carries non-zero bits after the last full byte.
+        assert_eq!(decode_base64_with("Zm9=", &standard).unwrap_err().position, 2);
+        let error = decode_base64_with("Zm é", &standard).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid Base64 character ' ' at position 2");
+        assert!(decode_base64_with("Zm 8=", &standard).is_err());
+        assert_eq!(decode_base64_with("Zm\r\n8=", &Base64Config::MIME), Ok(b"fo".to_vec()));
+
+        let lenient = Base64Config { mode: DecodeMode::Lenient, ..standard };
+        assert_eq!(decode_base64_with("Zm*9v-_ ==", &lenient), Ok(vec![b'f', b'o', b'o', 0xfb]));
+        assert_eq!(decode_base64_with("Zm9=", &lenient), Ok(b"fo".to_vec()));
+    }
+
+    #[test]
+    fn test_encode_base32() {
+        let vectors = [
+            ("", "", ""),
+            ("f", "MY======", "CO======"),
+            ("fo", "MZXQ====", "CPNG===="),
+            ("foo", "MZXW6===", "CPNMU==="),
+            ("foob", "MZXW6YQ=", "CPNMUOG="),
+            ("fooba", "MZXW6YTB", "CPNMUOJ1"),
+            ("foobar", "MZXW6YTBOI======", "CPNMUOJ1E8======"),
+        ];
+        for (input, standard, hex) in vectors {
+            assert_eq!(encode_base32(input.as_bytes(), Base32Alphabet::Rfc4648, Padding::Required), standard);
+            assert_eq!(encode_base32(input.as_bytes(), Base32Alphabet::Hex, Padding::Optional), hex);
+        }
+        assert_eq!(encode_base32(b"f", Base32Alphabet::Rfc4648, Padding::Forbidden), "MY");
+        assert_eq!(encode_base32(&[0xf0, 0xbf, 0xc7], Base32Alphabet::ZBase32, Padding::Forbidden), "6n9hq");
+    }
+
+    #[test]
+    fn test_decode_base32() {
+        for alphabet in [Base32Alphabet::Rfc4648, Base32Alphabet::Hex, Base32Alphabet::Crockford, Base32Alphabet::ZBase32] {
+            for len in 0..12 {
+                let bytes: Vec<u8> = (0..len).map(|i| (i * 37 + 11) as u8).collect();
+                let encoded = encode_base32(&bytes, alphabet, Padding::Required);
+                assert_eq!(decode_base32(&encoded, alphabet, Padding::Required), Ok(bytes.clone()));
+                let unpadded = encoded.trim_end_matches('=');
+                assert_eq!(decode_base32(unpadded, alphabet, Padding::Optional), Ok(bytes));
+            }
+        }
+        assert_eq!(decode_base32("6N9HQ", Base32Alphabet::ZBase32, Padding::Forbidden), Ok(vec![0xf0, 0xbf, 0xc7]));
+        assert_eq!(
+            decode_base32("CSQPY", Base32Alphabet::Crockford, Padding::Forbidden),
+            decode_base32("csqpy", Base32Alphabet::Crockford, Padding::Forbidden)
+        );
+        assert_eq!(decode_base32("oO", Base32Alphabet::Crockford, Padding::Forbidden), Ok(vec![0x00]));
+
+        let rfc = Base32Alphabet::Rfc4648;
+        assert_eq!(decode_base32("MY", rfc, Padding::Required).unwrap_err().position, 2);
+        assert_eq!(decode_base32("MY======", rfc, Padding::Forbidden).unwrap_err().position, 2);
+        assert_eq!(decode_base32("MY====", rfc, Padding::Optional).unwrap_err().position, 2);
+        assert_eq!(decode_base32("MZ======", rfc, Padding::Optional).unwrap_err().position, 1);
+        assert_eq!(decode_base32("MYZ", rfc, Padding::Optional).unwrap_err().position, 2);
+        assert_eq!(decode_base32("M1", rfc, Padding::Optional).unwrap_err().message, "Invalid Base32 character '1'");
+        assert!(decode_base32("CSQ-PY", rfc, Padding::Optional).is_err());
+    }
+
+    #[test]
+    fn test_crockford_check() {
+        assert_eq!(encode_crockford_check(&[0x04, 0xd2]), "0K90Q");
+        // 1234 written in Crockford Base32 is "16J"; its check is 1234 mod 37.
+        assert_eq!(crockford_check_value("16J"), 1234 % 37);
+        assert_eq!(crockford_check_value("0K90"), 19744 % 37);
+        assert_eq!(encode_crockford_check(&[]), "0");
+        for len in 0..10 {
+            let bytes: Vec<u8> = (0..len).map(|i| 255 - i as u8 * 13).collect();
+            assert_eq!(decode_crockford_check(&encode_crockford_check(&bytes)), Ok(bytes));
+        }
+        let error = decode_crockford_check("0K90E").unwrap_err();
+        assert_eq!(error.position, 4);
+        assert_eq!(error.message, "Check symbol mismatch: expected 'Q', found 'E'");
+        assert!(decode_crockford_check("").is_err());
+        assert!(decode_crockford_check("0K9U").is_err());
+    }
+
+    #[test]
+    fn test_encode_base58() {
+        let bitcoin = Base58Alphabet::Bitcoin;
+        assert_eq!(encode_base58(b"", bitcoin), "");
+        assert_eq!(encode_base58(b"Hello World!", bitcoin), "2NEpo7TZRRrLZSi2U");
+        assert_eq!(encode_base58(&[0x00, 0x00, 0x28, 0x7f, 0xb4, 0xcd], bitcoin), "11233QC4");
+        assert_eq!(encode_base58(&[0x00], bitcoin), "1");
+        assert_eq!(encode_base58(&[0x00], Base58Alphabet::Ripple), "r");
+        assert_eq!(encode_base58(&[57], Base58Alphabet::Flickr), "Z");
+        assert_eq!(encode_base58(&[57], bitcoin), "z");
+    }
+
+    #[test]
+    fn test_decode_base58() {
+        for alphabet in [Base58Alphabet::Bitcoin, Base58Alphabet::Ripple, Base58Alphabet::Flickr] {
+            for bytes in [vec![], vec![0], vec![0, 0, 255, 1], (0..=40).collect::<Vec<u8>>()] {
+                assert_eq!(decode_base58(&encode_base58(&bytes, alphabet), alphabet), Ok(bytes));
+            }
+        }
+        assert_eq!(decode_base58("11233QC4", Base58Alphabet::Bitcoin), Ok(vec![0x00, 0x00, 0x28, 0x7f, 0xb4, 0xcd]));
+        let error = decode_base58("abc0", Base58Alphabet::Bitcoin).unwrap_err();
+        assert_eq!(error.to_string(), "Invalid Base58 character '0' at position 3");
+        assert_eq!(decode_base58("1l", Base58Alphabet::Bitcoin).unwrap_err().position, 1);
+    }
+
+    #[test]
+    fn test_sha256() {
+        let hex = |data: &[u8]| encode_hex_bytes(&sha256(data));
+        assert_eq!(hex(b""), "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855");
+        assert_eq!(hex(b"abc"), "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad");
+        assert_eq!(
+            hex(b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq"),
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1"
+        );
+    }
+
+    #[test]
+    fn test_base58check() {
+        let bitcoin = Base58Alphabet::Bitcoin;
+        assert_eq!(encode_base58check(0x00, &[0u8; 20], bitcoin), "1111111111111111111114oLvT2");
+        let payload: Vec<u8> = (1..=32).collect();
+        let encoded = encode_base58check(0x80, &payload, Base58Alphabet::Ripple);
+        assert_eq!(decode_base58check(&encoded, Base58Alphabet::Ripple), Ok((0x80, payload)));
+
+        assert_eq!(
+            decode_base58check("1111111111111111111114oLvT3", bitcoin).unwrap_err().message,
+            "Base58Check checksum mismatch"
+        );
+        assert_eq!(decode_base58check("1111", bitcoin).unwrap_err().message, "Base58Check data is too short");
+        assert_eq!(decode_base58check("11I1", bitcoin).unwrap_err().position, 2);
+    }
+
+    #[test]
+    fn test_ascii85() {
+        let plain = Ascii85Config::default();
+        assert_eq!(encode_ascii85(b"Man is distinguished", &plain), "9jqo^BlbD-BleB1DJ+*+F(f,q");
+        assert_eq!(encode_ascii85(b"", &Ascii85Config::ADOBE), "<~~>");
+        let spaces = Ascii85Config { compress_spaces: true, ..plain };
+        assert_eq!(encode_ascii85(b"    ab", &spaces), "y@:B");
+        assert_eq!(encode_ascii85(b"    ab", &plain), "+<VdL@:B");
+
+        for bytes in [vec![], vec![0], vec![0, 0, 0, 0, 1], (0..=255).collect::<Vec<u8>>()] {
+            for config in [plain, spaces, Ascii85Config::ADOBE] {
+                assert_eq!(decode_ascii85(&encode_ascii85(&bytes, &config), &config), Ok(bytes.clone()));
+            }
+        }
+        assert_eq!(decode_ascii85(" <~9jqo^\r\nz~>\n", &Ascii85Config::ADOBE), Ok(b"Man \0\0\0\0".to_vec()));
+        assert_eq!(decode_ascii85("9jqo^z", &Ascii85Config::ADOBE).unwrap_err().position, 0);
+        assert_eq!(decode_ascii85("<~9jqo^", &Ascii85Config::ADOBE).unwrap_err().position, 7);
+        assert_eq!(decode_ascii85("9jzqo", &plain).unwrap_err().message, "'z' inside an Ascii85 group");
+        assert_eq!(decode_ascii85("y@:B", &plain).unwrap_err().position, 0);
+        assert_eq!(decode_ascii85("9jqo^B", &plain).unwrap_err().position, 5);
+        assert_eq!(decode_ascii85("s8W-\"", &plain).unwrap_err().message, "Ascii85 group overflows 32 bits");
+        assert_eq!(decode_ascii85("9jqv^", &plain).unwrap_err().message, "Invalid Ascii85 character 'v'");
+    }
+
+    #[test]
+    fn test_z85() {
+        let bytes = [0x86, 0x4F, 0xD2, 0x6F, 0xB5, 0x59, 0xF7, 0x5B];
+        assert_eq!(encode_z85(&bytes), Ok("HelloWorld".to_string()));
+        assert_eq!(decode_z85("HelloWorld"), Ok(bytes.to_vec()));
+        assert_eq!(
+            encode_z85(b"abcde"),
+            Err(DecodeError::new(5, "Z85 input length must be a multiple of 4, got 5"))
+        );
+        assert_eq!(decode_z85("Hello").map(|b| b.len()), Ok(4));
+        assert_eq!(decode_z85("HelloWorl").unwrap_err().position, 9);
+        assert_eq!(decode_z85("Hello~orld").unwrap_err().position, 5);
+        assert!(decode_z85("#####").is_err());
+    }
+
+    #[test]
+    fn test_base85() {
+        assert_eq!(encode_base85(b"hello"), "Xk~0{Zv");
+        let bytes: Vec<u8> = (0..20).collect();
+        assert_eq!(encode_base85(&bytes), "009C61O)~M2nh-c3=Iws5D^j+");
+        for len in 0..9 {
+            let bytes: Vec<u8> = (0..len).map(|i| 255 - i as u8).collect();
+            assert_eq!(decode_base85(&encode_base85(&bytes)), Ok(bytes));
+        }
+        assert_eq!(decode_base85("Xk~0{Z"), Err(DecodeError::new(5, "Trailing Base85 character does not form a complete byte")));
+        assert_eq!(decode_base85("Xk\"0{").unwrap_err().position, 2);
+    }
+
+    #[test]
+    fn test_detect_formats() {
+        let top = |text: &str| detect_formats(text).first().map(|&(format, _)| format);
+        assert_eq!(top("SGVsbG8sIFdvcmxkIQ=="), Some(Format::Base64));
+        assert_eq!(top("SGVsbG8s\r\nIFdvcmxkIQ"), Some(Format::Base64));
+        assert_eq!(top("48 65 6C 6C 6F"), Some(Format::Hex));
+        assert_eq!(top("01001000 01101001"), Some(Format::Binary));
+        assert_eq!(top("caf%C3%A9%20au%20lait"), Some(Format::Url));
+        assert_eq!(top("&lt;b&gt;Tom &amp; Jerry&lt;/b&gt;"), Some(Format::Html));
+        assert_eq!(top("Gur dhvpx oebja sbk whzcf bire gur ynml qbt"), Some(Format::Rot13));
+        assert_eq!(top("The quick brown fox jumps over the lazy dog"), None);
+        assert_eq!(top("AT&T costs 100%"), None);
+
+        let guesses = detect_formats("deadbeef");
+        assert!(guesses.iter().any(|&(format, _)| format == Format::Base64));
+        assert_eq!(guesses[0].0, Format::Hex);
+        assert!(guesses.iter().all(|&(_, confidence)| (0.0..=1.0).contains(&confidence)));
     }
 
     #[test]
@@ -443,6 +3409,136 @@ mod tests {
         assert_eq!(decode_url(encoded), Ok("test@example.com".to_string()));
     }
 
+    #[test]
+    fn test_base64_bytes() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let encoded = encode_base64_bytes(&bytes);
+        assert_eq!(decode_base64_bytes(&encoded), Ok(bytes));
+        assert_eq!(decode_base64_bytes("/w=="), Ok(vec![0xff]));
+    }
+
+    #[test]
+    fn test_hex_bytes() {
+        assert_eq!(encode_hex_bytes(&[0x00, 0x7f, 0xff]), "007fff");
+        assert_eq!(decode_hex_bytes("007FfF"), Ok(vec![0x00, 0x7f, 0xff]));
+        assert_eq!(decode_hex_bytes("abc").unwrap_err().position, 3);
+        assert_eq!(decode_hex_bytes("+f").unwrap_err().position, 0);
+        assert_eq!(decode_hex_bytes("aé").unwrap_err().message, "Invalid hex character 'é'");
+        assert_eq!(decode_hex("ff"), Err("Decoded bytes are not valid UTF-8".to_string()));
+    }
+
+    #[test]
+    fn test_url_bytes() {
+        assert_eq!(encode_url_bytes(&[0xff, b'~']), "%FF~");
+        assert_eq!(decode_url_bytes("%ff%2B+"), Ok(vec![0xff, b'+', b' ']));
+        assert_eq!(decode_url_bytes("ab%2").unwrap_err().position, 2);
+        assert_eq!(decode_url_bytes("%zz").unwrap_err().position, 0);
+        assert_eq!(decode_url("caf\u{e9}%20cr%C3%A8me"), Ok("caf\u{e9} cr\u{e8}me".to_string()));
+    }
+
+    #[test]
+    fn test_binary_bytes() {
+        assert_eq!(to_binary_bytes(&[0x80, 0x00]), "1000000000000000");
+        assert_eq!(from_binary_bytes("1000000000000000"), Ok(vec![0x80, 0x00]));
+        assert_eq!(from_binary_bytes("0101").unwrap_err().position, 4);
+        assert_eq!(from_binary_bytes("01012101").unwrap_err().position, 4);
+    }
+
+    #[test]
+    fn test_encode_url_with() {
+        let text = "a b/c?d#e&f=g+h:i@j~k%l'é";
+        assert_eq!(encode_url_with(text.as_bytes(), UrlEncodeSet::PathSegment), "a%20b%2Fc%3Fd%23e&f=g+h:i@j~k%25l'%C3%A9");
+        assert_eq!(encode_url_with(text.as_bytes(), UrlEncodeSet::Query), "a%20b/c?d%23e&f=g+h:i@j~k%25l'%C3%A9");
+        assert_eq!(encode_url_with(text.as_bytes(), UrlEncodeSet::Fragment), "a%20b/c?d%23e&f=g+h:i@j~k%25l'%C3%A9");
+        assert_eq!(encode_url_with(text.as_bytes(), UrlEncodeSet::Userinfo), "a%20b%2Fc%3Fd%23e&f=g+h%3Ai%40j~k%25l'%C3%A9");
+        assert_eq!(encode_url_with(text.as_bytes(), UrlEncodeSet::Component), encode_url(text));
+        assert_eq!(encode_url_with(text.as_bytes(), UrlEncodeSet::Form), "a+b%2Fc%3Fd%23e%26f%3Dg%2Bh%3Ai%40j%7Ek%25l%27%C3%A9");
+    }
+
+    #[test]
+    fn test_decode_url_with() {
+        assert_eq!(decode_url_with("/a+b%2B", UrlEncodeSet::PathSegment), Ok(b"/a+b+".to_vec()));
+        assert_eq!(decode_url_with("/a+b%2B", UrlEncodeSet::Form), Ok(b"/a b+".to_vec()));
+        assert_eq!(decode_url_with("caf%C3%A9+é", UrlEncodeSet::Query), Ok("café+é".as_bytes().to_vec()));
+        assert_eq!(decode_url_with("a+b%4", UrlEncodeSet::Query).unwrap_err().position, 3);
+        assert_eq!(decode_url("%C3%A9t%C3%A9 d'été"), Ok("été d'été".to_string()));
+    }
+
+    #[test]
+    fn test_form_urlencoded() {
+        assert_eq!(
+            parse_form_urlencoded("a=1&&b=%zz&c&=d&e=x=y&%FF=1"),
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "%zz".to_string()),
+                ("c".to_string(), "".to_string()),
+                ("".to_string(), "d".to_string()),
+                ("e".to_string(), "x=y".to_string()),
+                ("\u{FFFD}".to_string(), "1".to_string()),
+            ]
+        );
+        assert!(parse_form_urlencoded("").is_empty());
+
+        let pairs = vec![("name".to_string(), "Jöhn Doe".to_string()), ("q".to_string(), "1+1=2 & more".to_string())];
+        let serialized = serialize_form_urlencoded(&pairs);
+        assert_eq!(serialized, "name=J%C3%B6hn+Doe&q=1%2B1%3D2+%26+more");
+        assert_eq!(parse_form_urlencoded(&serialized), pairs);
+    }
+
+    #[test]
+    fn test_punycode() {
+        let samples = [
+            ("münchen", "mnchen-3ya"),
+            ("bücher", "bcher-kva"),
+            ("ليهمابتكلموشعربي؟", "egbpdaj6bu4bxfgehfvwxn"),
+            ("他们为什么不说中文", "ihqwcrb4cv8a8dqg056pqjye"),
+            ("3年B組金八先生", "3B-ww4c5e180e575a65lsy2b"),
+            ("", ""),
+            ("abc", "abc-"),
+        ];
+        for (unicode, puny) in samples {
+            assert_eq!(encode_punycode(unicode).as_deref(), Ok(puny));
+            assert_eq!(decode_punycode(puny).as_deref(), Ok(unicode));
+        }
+        assert_eq!(decode_punycode("MNCHEN-3YA").as_deref(), Ok("MüNCHEN"));
+        assert_eq!(decode_punycode("mnchen-3y!").unwrap_err().position, 9);
+        assert_eq!(decode_punycode("mnchen-3").unwrap_err().position, 8);
+        assert_eq!(decode_punycode("é-abc").unwrap_err().position, 0);
+        assert!(decode_punycode("99999999999").is_err());
+        let long = format!("{}\u{10FFFF}", "a".repeat(4000));
+        assert_eq!(
+            encode_punycode(&long),
+            Err(DecodeError::new(4000, "Punycode overflow: input is too long"))
+        );
+    }
+
+    #[test]
+    fn test_domain_to_ascii() {
+        assert_eq!(domain_to_ascii("www.Example.COM."), Ok("www.example.com.".to_string()));
+        assert_eq!(domain_to_ascii("bücher。example"), Ok("xn--bcher-kva.example".to_string()));
+        assert_eq!(domain_to_ascii("xn--bcher-kva.example"), Ok("xn--bcher-kva.example".to_string()));
+        assert_eq!(domain_to_ascii("").unwrap_err().message, "Empty domain label");
+        assert_eq!(domain_to_ascii("a.-b.c").unwrap_err().position, 2);
+        assert_eq!(domain_to_ascii("a.b_c").unwrap_err().message, "Invalid character '_' in domain label");
+        assert_eq!(domain_to_ascii("a.xn--abc-").unwrap_err().position, 2);
+        assert_eq!(domain_to_ascii("xn--zz9").unwrap_err().position, 0);
+        assert_eq!(domain_to_ascii("ex ample.é").unwrap_err().position, 0);
+        assert!(domain_to_ascii(&"a".repeat(64)).is_err());
+        assert!(domain_to_ascii(&"a".repeat(63)).is_ok());
+        let long = vec!["a".repeat(63); 4].join(".");
+        assert_eq!(domain_to_ascii(&long).unwrap_err().position, 0);
+        // Encoding can push a label over 63 bytes.
+        assert!(domain_to_ascii(&format!("{}ü", "a".repeat(60))).is_err());
+    }
+
+    #[test]
+    fn test_domain_to_unicode() {
+        assert_eq!(domain_to_unicode("xn--mnchen-3ya.DE"), Ok("münchen.de".to_string()));
+        assert_eq!(domain_to_unicode("münchen.de"), Ok("münchen.de".to_string()));
+        assert_eq!(domain_to_unicode("a.xn--ihqwcrb4cv8a8dqg056pqjye."), Ok("a.他们为什么不说中文.".to_string()));
+        assert_eq!(domain_to_unicode("ok.xn--a-").unwrap_err().position, 3);
+    }
+
     #[test]
     fn test_encode_html_entities() {
         let text = "<script>alert('hello');</script>";
@@ -452,6 +3548,32 @@ mod tests {
         assert_eq!(encode_html_entities(text), "Tom &amp; Jerry");
     }
 
+    #[test]
+    fn test_escape_for_context() {
+        let text = "<a href=\"x\">it's & é</a>";
+        assert_eq!(escape_for_context(text, EscapeContext::HtmlText, false), "&lt;a href=\"x\"&gt;it's &amp; é&lt;/a&gt;");
+        assert_eq!(
+            escape_for_context(text, EscapeContext::DoubleQuotedAttribute, false),
+            "&lt;a href=&quot;x&quot;&gt;it's &amp; é&lt;/a&gt;"
+        );
+        assert_eq!(
+            escape_for_context(text, EscapeContext::SingleQuotedAttribute, true),
+            "&lt;a href=\"x\"&gt;it&#39;s &amp; &#xE9;&lt;/a&gt;"
+        );
+        assert_eq!(escape_for_context("a b=c`é", EscapeContext::UnquotedAttribute, false), "a&#x20;b&#x3D;c&#x60;é");
+        assert_eq!(
+            escape_for_context("<x a='1'>\u{0}\u{B}\u{85}\u{FFFF}😀", EscapeContext::Xml, true),
+            "&lt;x a=&apos;1&apos;&gt;\u{FFFD}\u{FFFD}&#x85;\u{FFFD}&#x1F600;"
+        );
+        assert_eq!(
+            escape_for_context("\"</script><!--\\\n\u{2028}😀", EscapeContext::ScriptString, true),
+            "\\\"\\u003C\\/script\\u003E\\u003C!--\\\\\\n\\u2028\\uD83D\\uDE00"
+        );
+        assert_eq!(escape_for_context("é\u{1}", EscapeContext::ScriptString, false), "é\\u0001");
+        assert_eq!(escape_for_context("a\"b</style> é", EscapeContext::CssString, false), "a\\22 b\\3C \\2F style\\3E  é");
+        assert_eq!(escape_for_context("é", EscapeContext::CssString, true), "\\E9 ");
+    }
+
     #[test]
     fn test_decode_html_entities() {
         let encoded = "&lt;script&gt;alert(&#x27;hello&#x27;);&lt;/script&gt;";
@@ -461,6 +3583,147 @@ mod tests {
         assert_eq!(decode_html_entities(encoded), "Tom & Jerry");
     }
 
+    #[test]
+    fn test_decode_html_entities_html5() {
+        assert_eq!(html_entities::ENTITIES.len(), 2231);
+        assert!(html_entities::ENTITIES.windows(2).all(|w| w[0].0 < w[1].0));
+        assert_eq!(decode_html_entities("&amp;lt; &amp;amp;"), "&lt; &amp;");
+        assert_eq!(decode_html_entities("a&nbsp;b &eacute;&Eacute; &hellip;"), "a\u{a0}b éÉ …");
+        assert_eq!(decode_html_entities("&CounterClockwiseContourIntegral;"), "\u{2233}");
+        assert_eq!(decode_html_entities("&NotEqualTilde;"), "\u{2242}\u{338}");
+        assert_eq!(decode_html_entities("&#8364; &#x20ac; &#X20AC &#65&#66;"), "€ € € AB");
+        assert_eq!(decode_html_entities("&#x1F600;"), "😀");
+        assert_eq!(decode_html_entities("&#128;&#x9f;&#x81;"), "€Ÿ\u{81}");
+        assert_eq!(decode_html_entities("&#0;&#xD800;&#x110000;&#99999999999999;"), "\u{FFFD}".repeat(4));
+        assert_eq!(decode_html_entities("&#; &#x; &# &"), "&#; &#x; &# &");
+        assert_eq!(decode_html_entities("&notit; &notin; &ampx &copy2"), "¬it; ∉ &x ©2");
+        assert_eq!(decode_html_entities("&unknown; &amp"), "&unknown; &");
+        assert_eq!(decode_html_entities("&lt&gt;"), "<>");
+        assert_eq!(decode_html_entities("é&eacute"), "éé");
+    }
+
+    #[test]
+    fn test_decode_html_attribute() {
+        assert_eq!(decode_html_attribute("?a=1&copy=2&notit=3"), "?a=1&copy=2&notit=3");
+        assert_eq!(decode_html_attribute("&copy; &copy &copy."), "© © ©.");
+        assert_eq!(decode_html_attribute("&amp;copy=2"), "&copy=2");
+        assert_eq!(decode_html_entities("?a=1&copy=2"), "?a=1©=2");
+    }
+
+    #[test]
+    fn test_escape_json_string() {
+        assert_eq!(escape_json_string("a\"b\\c/d", false), "a\\\"b\\\\c/d");
+        assert_eq!(escape_json_string("\u{8}\u{C}\n\r\t\u{0}\u{1F}\u{7F}", false), "\\b\\f\\n\\r\\t\\u0000\\u001f\u{7F}");
+        assert_eq!(escape_json_string("é😀", false), "é😀");
+        assert_eq!(escape_json_string("é😀", true), "\\u00e9\\ud83d\\ude00");
+    }
+
+    #[test]
+    fn test_unescape_json_string() {
+        for text in ["", "plain", "a\"b\\c\n\u{0}\u{1F}", "é😀\u{FFFF}"] {
+            for ascii_only in [false, true] {
+                assert_eq!(unescape_json_string(&escape_json_string(text, ascii_only)).as_deref(), Ok(text));
+            }
+        }
+        assert_eq!(unescape_json_string("\\/\\u00E9"), Ok("/é".to_string()));
+        assert_eq!(unescape_json_string("ab\\"), Err(DecodeError::new(2, "Incomplete escape sequence")));
+        assert_eq!(unescape_json_string("\\x41").unwrap_err().message, "Invalid escape sequence '\\x'");
+        assert_eq!(unescape_json_string("é\\u12g4").unwrap_err().position, 2);
+        assert_eq!(unescape_json_string("\\u12").unwrap_err().position, 0);
+        assert_eq!(unescape_json_string("x\\ud83d").unwrap_err().message, "Unpaired high surrogate");
+        assert_eq!(unescape_json_string("x\\ud83d\\u0041").unwrap_err().position, 1);
+        assert_eq!(unescape_json_string("\\ud83d\\u00").unwrap_err().position, 6);
+        assert_eq!(unescape_json_string("\\ude00").unwrap_err().message, "Unpaired low surrogate");
+        assert_eq!(unescape_json_string("a\"b").unwrap_err().position, 1);
+        assert_eq!(unescape_json_string("a\nb").unwrap_err().message, "Unescaped control character U+000A");
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("a-b_c.d/e:f=g,h@i%j+k"), "a-b_c.d/e:f=g,h@i%j+k");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("'"), r#"''\'''"#);
+        assert_eq!(shell_quote("é"), "'é'");
+        assert_eq!(shell_join(&["echo", "", "a*b"]), "echo '' 'a*b'");
+    }
+
+    #[test]
+    fn test_shell_split() {
+        let split = |s: &str| shell_split(s).unwrap();
+        assert_eq!(split("  a  b\tc\n"), vec!["a", "b", "c"]);
+        assert_eq!(split("''"), vec![""]);
+        assert_eq!(split(r#"a'b'"c"d"#), vec!["abcd"]);
+        assert_eq!(split(r#"'a\b' "a\b" "\$x \" \\" a\ b"#), vec![r"a\b", r"a\b", r#"$x " \"#, "a b"]);
+        assert_eq!(split("a\\\nb \"c\\\nd\""), vec!["ab", "cd"]);
+        assert_eq!(split("a#b # comment\nc"), vec!["a#b", "c"]);
+        assert_eq!(split("# only a comment"), Vec::<String>::new());
+
+        let args = ["rm", "-rf", "it's a dir", "", "$(danger)", "tab\there", "new\nline"];
+        assert_eq!(split(&shell_join(&args)), args);
+
+        assert_eq!(shell_split("a \"b").unwrap_err(), DecodeError::new(2, "Unterminated double quote"));
+        assert_eq!(shell_split("a \"b\\").unwrap_err().position, 2);
+        assert_eq!(shell_split("é 'b").unwrap_err().position, 3);
+        assert_eq!(shell_split("ab\\").unwrap_err(), DecodeError::new(2, "Trailing backslash"));
+    }
+
+    #[test]
+    fn test_encode_quoted_printable() {
+        assert_eq!(encode_quoted_printable(b"a=b\tc \r\nd\t\ne ", false), "a=3Db\tc=20\r\nd=09\r\ne=20");
+        assert_eq!(encode_quoted_printable(b"a \r\nb", true), "a =0D=0Ab");
+
+        let long = "x".repeat(200);
+        let encoded = encode_quoted_printable(long.as_bytes(), false);
+        assert!(encoded.split("\r\n").all(|line| line.len() <= 76));
+        assert_eq!(encoded.split("\r\n").next(), Some(&*("x".repeat(75) + "=")));
+
+        // An escape sequence is never split across lines.
+        let bytes = [b'x'; 74].iter().chain(&[0xff, 0xff]).copied().collect::<Vec<u8>>();
+        assert_eq!(encode_quoted_printable(&bytes, false), "x".repeat(74) + "=\r\n=FF=FF");
+        assert_eq!(encode_quoted_printable(&[b'x'; 76], false), "x".repeat(76));
+    }
+
+    #[test]
+    fn test_decode_quoted_printable() {
+        let bytes: Vec<u8> = (0..=255).cycle().take(600).collect();
+        assert_eq!(decode_quoted_printable(&encode_quoted_printable(&bytes, true)), Ok(bytes));
+        let text = "Line one with trailing space \nline two\twith tab\t\n".repeat(5) + &"é".repeat(80);
+        let decoded = decode_quoted_printable(&encode_quoted_printable(text.as_bytes(), false)).unwrap();
+        assert_eq!(String::from_utf8(decoded).unwrap(), text.replace('\n', "\r\n"));
+
+        assert_eq!(decode_quoted_printable("a=3db =  \nc\n"), Ok(b"a=b c\n".to_vec()));
+        assert_eq!(decode_quoted_printable("ok\r\nbad=G1"), Err(DecodeError::new(7, "Invalid quoted-printable escape")));
+        assert_eq!(decode_quoted_printable("bad=4").unwrap_err().position, 3);
+    }
+
+    #[test]
+    fn test_encoded_words() {
+        let subject = "Réunion: ordre du jour — 😀 ".repeat(4);
+        for encoding in [WordEncoding::Base64, WordEncoding::Q] {
+            let encoded = encode_encoded_word(&subject, encoding);
+            assert!(encoded.split("\r\n ").all(|word| word.len() <= 75 && word.starts_with("=?UTF-8?")));
+            assert!(encoded.contains("\r\n "));
+            assert_eq!(decode_encoded_words(&encoded), Ok(subject.clone()));
+        }
+        assert_eq!(encode_encoded_word("", WordEncoding::Q), "=?UTF-8?Q??=");
+        assert_eq!(encode_encoded_word("a b_c?", WordEncoding::Q), "=?UTF-8?Q?a_b=5Fc=3F?=");
+
+        assert_eq!(decode_encoded_words("plain text"), Ok("plain text".to_string()));
+        assert_eq!(decode_encoded_words("=?ISO-8859-1?Q?Andr=E9?= Pirard"), Ok("André Pirard".to_string()));
+        assert_eq!(decode_encoded_words("=?windows-1252?Q?=80100?="), Ok("€100".to_string()));
+        assert_eq!(decode_encoded_words("(=?UTF-8*fr?Q?a?=  =?US-ASCII?Q?b?=)"), Ok("(ab)".to_string()));
+        // A character split across two words is joined before decoding.
+        assert_eq!(decode_encoded_words("=?UTF-8?Q?caf=C3?= =?UTF-8?Q?=A9?="), Ok("café".to_string()));
+        assert_eq!(decode_encoded_words("=?UTF-8?Q?50%?="), Ok("50%".to_string()));
+        assert_eq!(decode_encoded_words("=?UTF-8?x?abc?="), Ok("=?UTF-8?x?abc?=".to_string()));
+
+        assert_eq!(decode_encoded_words("Hi =?KOI8-R?Q?abc?=").unwrap_err().position, 3);
+        assert_eq!(decode_encoded_words("=?UTF-8?B?YW*j?=").unwrap_err().position, 12);
+        assert_eq!(decode_encoded_words("=?UTF-8?Q?a=Z1?=").unwrap_err().position, 11);
+        assert!(decode_encoded_words("=?UTF-8?Q?=FF?=").is_err());
+    }
+
     #[test]
     fn test_encode_rot13() {
         let text = "hello world";
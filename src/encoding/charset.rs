@@ -0,0 +1,587 @@
+//! Transcoding between text and legacy byte encodings.
+//!
+//! [`decode`] and [`encode`] convert between bytes and text in UTF-8, UTF-16,
+//! UTF-32, ISO-8859-1, ISO-8859-15 and Windows-1252, with an [`ErrorMode`]
+//! choosing what happens to bytes or characters that do not fit.
+//! [`detect_bom`] and [`decode_with_bom`] handle byte order marks, and
+//! [`detect_charset`] guesses the charset of unlabelled bytes.
+
+use super::{printable_ratio, DecodeError, WINDOWS_1252_C1};
+
+/// A character encoding supported by [`decode`] and [`encode`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Charset {
+    /// UTF-8.
+    Utf8,
+    /// UTF-16, little-endian.
+    Utf16Le,
+    /// UTF-16, big-endian.
+    Utf16Be,
+    /// UTF-32, little-endian.
+    Utf32Le,
+    /// UTF-32, big-endian.
+    Utf32Be,
+    /// ISO-8859-1 (Latin-1): each byte is the code point of the same value.
+    Latin1,
+    /// ISO-8859-15 (Latin-9): Latin-1 with the euro sign and eight other letters.
+    Latin9,
+    /// Windows-1252: Latin-1 with printable characters in 0x80–0x9F. The five
+    /// bytes Windows leaves undefined map to the C1 controls of the same value.
+    Windows1252,
+}
+
+impl Charset {
+    /// Looks up a charset by one of its common labels, ignoring case and surrounding whitespace.
+    ///
+    /// Unmarked `utf-16` and `utf-32` are little-endian, and `us-ascii` is read as UTF-8.
+    ///
+    /// # Arguments
+    ///
+    /// * `label` - A string slice that holds the label, such as `"latin1"` or `"cp1252"`.
+    ///
+    /// # Returns
+    ///
+    /// * An `Option<Charset>` containing the charset, or `None` if the label is unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::encoding::charset::Charset;
+    ///
+    /// assert_eq!(Charset::from_label(" Windows-1252 "), Some(Charset::Windows1252));
+    /// assert_eq!(Charset::from_label("koi8-r"), None);
+    /// ```
+    pub fn from_label(label: &str) -> Option<Charset> {
+        let charset = match label.trim().to_ascii_lowercase().as_str() {
+            "utf-8" | "utf8" | "us-ascii" | "ascii" => Charset::Utf8,
+            "utf-16" | "utf-16le" | "utf16" | "utf16le" => Charset::Utf16Le,
+            "utf-16be" | "utf16be" => Charset::Utf16Be,
+            "utf-32" | "utf-32le" | "utf32" | "utf32le" => Charset::Utf32Le,
+            "utf-32be" | "utf32be" => Charset::Utf32Be,
+            "iso-8859-1" | "iso8859-1" | "iso_8859-1" | "latin1" | "latin-1" | "l1" => Charset::Latin1,
+            "iso-8859-15" | "iso8859-15" | "iso_8859-15" | "latin9" | "latin-9" | "l9" => Charset::Latin9,
+            "windows-1252" | "cp1252" | "x-cp1252" => Charset::Windows1252,
+            _ => return None,
+        };
+        Some(charset)
+    }
+
+    /// Returns the canonical name of the charset, such as `"UTF-16LE"`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(loki_text::encoding::charset::Charset::Latin9.name(), "ISO-8859-15");
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Charset::Utf8 => "UTF-8",
+            Charset::Utf16Le => "UTF-16LE",
+            Charset::Utf16Be => "UTF-16BE",
+            Charset::Utf32Le => "UTF-32LE",
+            Charset::Utf32Be => "UTF-32BE",
+            Charset::Latin1 => "ISO-8859-1",
+            Charset::Latin9 => "ISO-8859-15",
+            Charset::Windows1252 => "Windows-1252",
+        }
+    }
+
+    /// Returns the byte order mark of the charset, or an empty slice for single-byte charsets.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::encoding::charset::Charset;
+    ///
+    /// assert_eq!(Charset::Utf16Be.bom(), &[0xFE, 0xFF]);
+    /// assert!(Charset::Latin1.bom().is_empty());
+    /// ```
+    pub fn bom(self) -> &'static [u8] {
+        match self {
+            Charset::Utf8 => &[0xEF, 0xBB, 0xBF],
+            Charset::Utf16Le => &[0xFF, 0xFE],
+            Charset::Utf16Be => &[0xFE, 0xFF],
+            Charset::Utf32Le => &[0xFF, 0xFE, 0x00, 0x00],
+            Charset::Utf32Be => &[0x00, 0x00, 0xFE, 0xFF],
+            Charset::Latin1 | Charset::Latin9 | Charset::Windows1252 => &[],
+        }
+    }
+}
+
+/// What a conversion does with bytes that cannot be decoded or characters that cannot be encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorMode {
+    /// Fail with the position of the first problem.
+    Strict,
+    /// Decode to U+FFFD, or encode as `?`.
+    Replace,
+    /// Decode each offending byte as the text `\xNN`, or encode a character as
+    /// `\xNN`, `\uNNNN` or `\UNNNNNNNN` like Python's `backslashreplace`.
+    Escape,
+}
+
+/// Detects a byte order mark at the start of the input.
+///
+/// A UTF-32LE mark is preferred over the UTF-16LE mark it begins with.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to inspect.
+///
+/// # Returns
+///
+/// * An `Option<(Charset, usize)>` containing the charset and the length of its mark, or `None` if there is no mark.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::charset::{detect_bom, Charset};
+///
+/// assert_eq!(detect_bom(b"\xEF\xBB\xBFhi"), Some((Charset::Utf8, 3)));
+/// assert_eq!(detect_bom(b"hi"), None);
+/// ```
+pub fn detect_bom(bytes: &[u8]) -> Option<(Charset, usize)> {
+    [Charset::Utf32Le, Charset::Utf32Be, Charset::Utf8, Charset::Utf16Le, Charset::Utf16Be]
+        .into_iter()
+        .find(|charset| bytes.starts_with(charset.bom()))
+        .map(|charset| (charset, charset.bom().len()))
+}
+
+/// Removes a byte order mark from the start of the input, if there is one.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to strip.
+///
+/// # Returns
+///
+/// * A `&[u8]` with any byte order mark removed.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(loki_text::encoding::charset::strip_bom(b"\xFF\xFEh\x00"), b"h\x00");
+/// ```
+pub fn strip_bom(bytes: &[u8]) -> &[u8] {
+    match detect_bom(bytes) {
+        Some((_, length)) => &bytes[length..],
+        None => bytes,
+    }
+}
+
+// Handles an undecodable run of bytes starting at `position`.
+fn decode_error(decoded: &mut String, invalid: &[u8], position: usize, charset: Charset, mode: ErrorMode) -> Result<(), DecodeError> {
+    match mode {
+        ErrorMode::Strict => {
+            return Err(DecodeError::new(position, format!("Invalid {} sequence", charset.name())));
+        }
+        ErrorMode::Replace => decoded.push('\u{FFFD}'),
+        ErrorMode::Escape => {
+            for byte in invalid {
+                decoded.push_str(&format!("\\x{:02x}", byte));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn decode_utf16(bytes: &[u8], big_endian: bool, mode: ErrorMode, decoded: &mut String) -> Result<(), DecodeError> {
+    let charset = if big_endian { Charset::Utf16Be } else { Charset::Utf16Le };
+    let unit_at = |i: usize| {
+        let pair = [bytes[i], bytes[i + 1]];
+        if big_endian { u16::from_be_bytes(pair) } else { u16::from_le_bytes(pair) }
+    };
+    let mut i = 0;
+    while i + 1 < bytes.len() {
+        let unit = unit_at(i);
+        let (code, length) = match unit {
+            0xD800..=0xDBFF if i + 3 < bytes.len() && (0xDC00..=0xDFFF).contains(&unit_at(i + 2)) => {
+                let low = unit_at(i + 2);
+                (0x10000 + ((unit as u32 - 0xD800) << 10) + (low as u32 - 0xDC00), 4)
+            }
+            _ => (unit as u32, 2),
+        };
+        match char::from_u32(code) {
+            Some(c) => decoded.push(c),
+            None => decode_error(decoded, &bytes[i..i + 2], i, charset, mode)?,
+        }
+        i += length;
+    }
+    if i < bytes.len() {
+        decode_error(decoded, &bytes[i..], i, charset, mode)?;
+    }
+    Ok(())
+}
+
+fn decode_utf32(bytes: &[u8], big_endian: bool, mode: ErrorMode, decoded: &mut String) -> Result<(), DecodeError> {
+    let charset = if big_endian { Charset::Utf32Be } else { Charset::Utf32Le };
+    let mut units = bytes.chunks_exact(4);
+    for (index, unit) in units.by_ref().enumerate() {
+        let quad = [unit[0], unit[1], unit[2], unit[3]];
+        let code = if big_endian { u32::from_be_bytes(quad) } else { u32::from_le_bytes(quad) };
+        match char::from_u32(code) {
+            Some(c) => decoded.push(c),
+            None => decode_error(decoded, unit, index * 4, charset, mode)?,
+        }
+    }
+    let rest = units.remainder();
+    if !rest.is_empty() {
+        decode_error(decoded, rest, bytes.len() - rest.len(), charset, mode)?;
+    }
+    Ok(())
+}
+
+// Maps a byte to its character in one of the single-byte charsets.
+fn single_byte_char(byte: u8, charset: Charset) -> char {
+    match (charset, byte) {
+        (Charset::Windows1252, 0x80..=0x9F) => WINDOWS_1252_C1[(byte - 0x80) as usize],
+        (Charset::Latin9, 0xA4) => '\u{20AC}',
+        (Charset::Latin9, 0xA6) => '\u{160}',
+        (Charset::Latin9, 0xA8) => '\u{161}',
+        (Charset::Latin9, 0xB4) => '\u{17D}',
+        (Charset::Latin9, 0xB8) => '\u{17E}',
+        (Charset::Latin9, 0xBC) => '\u{152}',
+        (Charset::Latin9, 0xBD) => '\u{153}',
+        (Charset::Latin9, 0xBE) => '\u{178}',
+        _ => byte as char,
+    }
+}
+
+/// Decodes bytes in the given charset.
+///
+/// A byte order mark is not removed; use [`decode_with_bom`] for that. The
+/// single-byte charsets map every byte, so only the Unicode encodings can fail.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to decode.
+/// * `charset` - The charset of the bytes.
+/// * `mode` - What to do with invalid sequences.
+///
+/// # Returns
+///
+/// * A `Result<String, DecodeError>` containing the decoded text, or an error locating the first invalid sequence in `Strict` mode.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::charset::{decode, Charset, ErrorMode};
+///
+/// assert_eq!(decode(b"caf\xE9 \x80", Charset::Windows1252, ErrorMode::Strict), Ok("café €".to_string()));
+/// assert_eq!(decode(b"h\x00i\x00", Charset::Utf16Le, ErrorMode::Strict), Ok("hi".to_string()));
+/// assert_eq!(decode(b"caf\xE9", Charset::Utf8, ErrorMode::Escape), Ok("caf\\xe9".to_string()));
+/// assert_eq!(decode(b"caf\xE9", Charset::Utf8, ErrorMode::Strict).unwrap_err().position, 3);
+/// ```
+pub fn decode(bytes: &[u8], charset: Charset, mode: ErrorMode) -> Result<String, DecodeError> {
+    let mut decoded = String::with_capacity(bytes.len());
+    match charset {
+        Charset::Utf8 => {
+            let mut position = 0;
+            for chunk in bytes.utf8_chunks() {
+                decoded.push_str(chunk.valid());
+                position += chunk.valid().len();
+                if !chunk.invalid().is_empty() {
+                    decode_error(&mut decoded, chunk.invalid(), position, charset, mode)?;
+                    position += chunk.invalid().len();
+                }
+            }
+        }
+        Charset::Utf16Le | Charset::Utf16Be => decode_utf16(bytes, charset == Charset::Utf16Be, mode, &mut decoded)?,
+        Charset::Utf32Le | Charset::Utf32Be => decode_utf32(bytes, charset == Charset::Utf32Be, mode, &mut decoded)?,
+        Charset::Latin1 | Charset::Latin9 | Charset::Windows1252 => {
+            decoded.extend(bytes.iter().map(|&byte| single_byte_char(byte, charset)));
+        }
+    }
+    Ok(decoded)
+}
+
+/// Decodes bytes using the charset named by their byte order mark, falling back to `fallback` when there is none.
+///
+/// The mark itself is not part of the result.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to decode.
+/// * `fallback` - The charset to use when there is no byte order mark.
+/// * `mode` - What to do with invalid sequences.
+///
+/// # Returns
+///
+/// * A `Result<(String, Charset), DecodeError>` containing the decoded text and the charset used. Error positions count from the start of `bytes`, including the mark.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::charset::{decode_with_bom, Charset, ErrorMode};
+///
+/// let bytes = b"\xFF\xFEh\x00i\x00";
+/// assert_eq!(decode_with_bom(bytes, Charset::Utf8, ErrorMode::Strict), Ok(("hi".to_string(), Charset::Utf16Le)));
+/// assert_eq!(decode_with_bom(b"hi", Charset::Latin1, ErrorMode::Strict), Ok(("hi".to_string(), Charset::Latin1)));
+/// ```
+pub fn decode_with_bom(bytes: &[u8], fallback: Charset, mode: ErrorMode) -> Result<(String, Charset), DecodeError> {
+    let (charset, length) = detect_bom(bytes).unwrap_or((fallback, 0));
+    let decoded = decode(&bytes[length..], charset, mode)
+        .map_err(|e| DecodeError::new(e.position + length, e.message))?;
+    Ok((decoded, charset))
+}
+
+// Maps a character to its byte in one of the single-byte charsets.
+fn single_byte_for(c: char, charset: Charset) -> Option<u8> {
+    let code = c as u32;
+    match charset {
+        Charset::Latin1 => u8::try_from(code).ok(),
+        Charset::Latin9 => (0..=0xFF).find(|&byte| single_byte_char(byte, charset) == c),
+        _ => match code {
+            0..=0x7F | 0xA0..=0xFF => Some(code as u8),
+            _ => WINDOWS_1252_C1
+                .iter()
+                .position(|&mapped| mapped == c)
+                .map(|index| 0x80 + index as u8),
+        },
+    }
+}
+
+/// Encodes text in the given charset.
+///
+/// No byte order mark is written; prepend [`Charset::bom`] if one is wanted.
+/// Only the single-byte charsets can fail, on characters they cannot represent.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to encode.
+/// * `charset` - The charset to encode into.
+/// * `mode` - What to do with characters the charset cannot represent.
+///
+/// # Returns
+///
+/// * A `Result<Vec<u8>, DecodeError>` containing the encoded bytes, or an error at the byte offset of the first unrepresentable character in `Strict` mode.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::charset::{encode, Charset, ErrorMode};
+///
+/// assert_eq!(encode("€5", Charset::Windows1252, ErrorMode::Strict), Ok(b"\x805".to_vec()));
+/// assert_eq!(encode("hi", Charset::Utf16Be, ErrorMode::Strict), Ok(b"\x00h\x00i".to_vec()));
+/// assert_eq!(encode("€5", Charset::Latin1, ErrorMode::Escape), Ok(b"\\u20ac5".to_vec()));
+/// assert_eq!(encode("5€", Charset::Latin1, ErrorMode::Strict).unwrap_err().position, 1);
+/// ```
+pub fn encode(text: &str, charset: Charset, mode: ErrorMode) -> Result<Vec<u8>, DecodeError> {
+    let mut encoded = Vec::with_capacity(text.len());
+    match charset {
+        Charset::Utf8 => encoded.extend_from_slice(text.as_bytes()),
+        Charset::Utf16Le => encoded.extend(text.encode_utf16().flat_map(u16::to_le_bytes)),
+        Charset::Utf16Be => encoded.extend(text.encode_utf16().flat_map(u16::to_be_bytes)),
+        Charset::Utf32Le => encoded.extend(text.chars().flat_map(|c| (c as u32).to_le_bytes())),
+        Charset::Utf32Be => encoded.extend(text.chars().flat_map(|c| (c as u32).to_be_bytes())),
+        Charset::Latin1 | Charset::Latin9 | Charset::Windows1252 => {
+            for (position, c) in text.char_indices() {
+                match (single_byte_for(c, charset), mode) {
+                    (Some(byte), _) => encoded.push(byte),
+                    (None, ErrorMode::Strict) => {
+                        return Err(DecodeError::new(
+                            position,
+                            format!("Character {:?} cannot be encoded in {}", c, charset.name()),
+                        ));
+                    }
+                    (None, ErrorMode::Replace) => encoded.push(b'?'),
+                    (None, ErrorMode::Escape) => {
+                        let escape = match c as u32 {
+                            code @ 0..=0xFF => format!("\\x{:02x}", code),
+                            code @ 0x100..=0xFFFF => format!("\\u{:04x}", code),
+                            code => format!("\\U{:08x}", code),
+                        };
+                        encoded.extend_from_slice(escape.as_bytes());
+                    }
+                }
+            }
+        }
+    }
+    Ok(encoded)
+}
+
+// Guesses UTF-32 or UTF-16 from where the zero bytes fall, for input without a byte order mark.
+fn detect_wide(bytes: &[u8]) -> Option<(Charset, f64)> {
+    let share = |count: usize, total: usize| count as f64 / total as f64;
+    if bytes.len() >= 4 && bytes.len().is_multiple_of(4) {
+        let units = bytes.len() / 4;
+        let le = bytes.chunks_exact(4).filter(|u| u[0] != 0 && u[2] == 0 && u[3] == 0).count();
+        let be = bytes.chunks_exact(4).filter(|u| u[0] == 0 && u[1] == 0 && u[3] != 0).count();
+        let (charset, count) = if le >= be { (Charset::Utf32Le, le) } else { (Charset::Utf32Be, be) };
+        if share(count, units) >= 0.5 {
+            if let Ok(text) = decode(bytes, charset, ErrorMode::Strict) {
+                return Some((charset, 0.95 * share(count, units) * printable_ratio(&text)));
+            }
+        }
+    }
+    if bytes.len() >= 2 && bytes.len().is_multiple_of(2) {
+        let units = bytes.len() / 2;
+        let even = bytes.iter().step_by(2).filter(|&&b| b == 0).count();
+        let odd = bytes.iter().skip(1).step_by(2).filter(|&&b| b == 0).count();
+        // ASCII-range text has a zero in every other byte; the other half is almost never zero.
+        let (charset, zeros, others) = if odd >= even { (Charset::Utf16Le, odd, even) } else { (Charset::Utf16Be, even, odd) };
+        if share(zeros, units) >= 0.3 && share(others, units) < 0.1 {
+            if let Ok(text) = decode(bytes, charset, ErrorMode::Strict) {
+                let confidence = 0.5 + 0.45 * share(zeros - others, units);
+                return Some((charset, confidence * printable_ratio(&text)));
+            }
+        }
+    }
+    None
+}
+
+/// Guesses the charset of some bytes.
+///
+/// A byte order mark is trusted outright. Otherwise zero bytes at regular
+/// offsets suggest UTF-16 or UTF-32, valid UTF-8 is taken as UTF-8 (more
+/// confidently the more multi-byte sequences it has), and anything else is a
+/// single-byte charset: Windows-1252 if it uses the printable characters in
+/// 0x80–0x9F, ISO-8859-1 if not. Confidence is lowered for control characters
+/// and for an implausible share of high bytes, which suggest binary data.
+///
+/// # Arguments
+///
+/// * `bytes` - A slice of bytes to inspect.
+///
+/// # Returns
+///
+/// * A `(Charset, f64)` containing the likeliest charset and a confidence between 0.0 and 1.0.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::charset::{detect_charset, Charset};
+///
+/// assert_eq!(detect_charset(b"h\x00i\x00!\x00").0, Charset::Utf16Le);
+/// assert_eq!(detect_charset("café".as_bytes()).0, Charset::Utf8);
+/// assert_eq!(detect_charset(b"\x93quoted\x94").0, Charset::Windows1252);
+/// assert_eq!(detect_charset(b"caf\xE9").0, Charset::Latin1);
+/// ```
+pub fn detect_charset(bytes: &[u8]) -> (Charset, f64) {
+    if let Some((charset, _)) = detect_bom(bytes) {
+        return (charset, 1.0);
+    }
+    if let Some(guess) = detect_wide(bytes) {
+        return guess;
+    }
+    if let Ok(text) = std::str::from_utf8(bytes) {
+        // Multi-byte sequences rarely form valid UTF-8 by chance.
+        let multibyte = text.chars().filter(|c| !c.is_ascii()).count().min(16) as i32;
+        let confidence = if multibyte == 0 { 1.0 } else { 0.99 - 0.5f64.powi(multibyte + 1) };
+        return (Charset::Utf8, confidence * printable_ratio(text));
+    }
+
+    let undefined_in_1252 = [0x81, 0x8D, 0x8F, 0x90, 0x9D];
+    let c1 = bytes.iter().filter(|&&b| (0x80..=0x9F).contains(&b)).count();
+    let (charset, mut confidence) = if c1 > 0 && !bytes.iter().any(|b| undefined_in_1252.contains(b)) {
+        (Charset::Windows1252, 0.8)
+    } else if c1 > 0 {
+        (Charset::Latin1, 0.5)
+    } else {
+        (Charset::Latin1, 0.8)
+    };
+    let high = bytes.iter().filter(|&&b| b >= 0x80).count();
+    if high as f64 / bytes.len() as f64 > 0.3 {
+        confidence *= 0.5;
+    }
+    let text = decode(bytes, charset, ErrorMode::Strict).unwrap_or_default();
+    (charset, confidence * printable_ratio(&text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_charset_labels() {
+        assert_eq!(Charset::from_label("UTF-16"), Some(Charset::Utf16Le));
+        assert_eq!(Charset::from_label("latin9"), Some(Charset::Latin9));
+        assert_eq!(Charset::from_label("cp1252").map(Charset::name), Some("Windows-1252"));
+        assert_eq!(Charset::from_label("shift_jis"), None);
+    }
+
+    #[test]
+    fn test_detect_bom() {
+        assert_eq!(detect_bom(b"\xFF\xFE\x00\x00"), Some((Charset::Utf32Le, 4)));
+        assert_eq!(detect_bom(b"\xFF\xFEh\x00"), Some((Charset::Utf16Le, 2)));
+        assert_eq!(detect_bom(b"\x00\x00\xFE\xFF"), Some((Charset::Utf32Be, 4)));
+        assert_eq!(detect_bom(b"\xFE\xFF"), Some((Charset::Utf16Be, 2)));
+        assert_eq!(detect_bom(b"\xEF\xBB"), None);
+        assert_eq!(strip_bom(b"\xEF\xBB\xBFok"), b"ok");
+        assert_eq!(strip_bom(b"ok"), b"ok");
+    }
+
+    #[test]
+    fn test_decode_unicode() {
+        let text = "a€😀";
+        for charset in [Charset::Utf8, Charset::Utf16Le, Charset::Utf16Be, Charset::Utf32Le, Charset::Utf32Be] {
+            let bytes = encode(text, charset, ErrorMode::Strict).unwrap();
+            assert_eq!(decode(&bytes, charset, ErrorMode::Strict).as_deref(), Ok(text));
+        }
+        assert_eq!(encode("😀", Charset::Utf16Le, ErrorMode::Strict), Ok(vec![0x3D, 0xD8, 0x00, 0xDE]));
+
+        // Unpaired surrogate followed by an odd trailing byte.
+        let bytes = b"a\x00\x00\xD8b\x00c";
+        assert_eq!(decode(bytes, Charset::Utf16Le, ErrorMode::Strict).unwrap_err().position, 2);
+        assert_eq!(decode(bytes, Charset::Utf16Le, ErrorMode::Replace).as_deref(), Ok("a\u{FFFD}b\u{FFFD}"));
+        assert_eq!(decode(bytes, Charset::Utf16Le, ErrorMode::Escape).as_deref(), Ok("a\\x00\\xd8b\\x63"));
+        let error = decode(b"a\x00\x00\x00\x00\x00\x11\x00", Charset::Utf32Le, ErrorMode::Strict).unwrap_err();
+        assert_eq!((error.position, error.message.as_str()), (4, "Invalid UTF-32LE sequence"));
+        assert_eq!(decode(b"a\x00\x00\x00b", Charset::Utf32Le, ErrorMode::Replace).as_deref(), Ok("a\u{FFFD}"));
+        assert_eq!(decode(b"a\xF0\x9F\x98b", Charset::Utf8, ErrorMode::Replace).as_deref(), Ok("a\u{FFFD}b"));
+        assert_eq!(decode(b"a\xF0\x9F\x98b", Charset::Utf8, ErrorMode::Escape).as_deref(), Ok("a\\xf0\\x9f\\x98b"));
+    }
+
+    #[test]
+    fn test_decode_single_byte() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        for charset in [Charset::Latin1, Charset::Latin9, Charset::Windows1252] {
+            let text = decode(&bytes, charset, ErrorMode::Strict).unwrap();
+            assert_eq!(text.chars().count(), 256);
+            assert_eq!(encode(&text, charset, ErrorMode::Strict), Ok(bytes.clone()));
+        }
+        assert_eq!(decode(b"\xA4\xBD", Charset::Latin1, ErrorMode::Strict).as_deref(), Ok("¤½"));
+        assert_eq!(decode(b"\xA4\xBD", Charset::Latin9, ErrorMode::Strict).as_deref(), Ok("€œ"));
+        assert_eq!(decode(b"\x93hi\x94 \x81", Charset::Windows1252, ErrorMode::Strict).as_deref(), Ok("\u{201C}hi\u{201D} \u{81}"));
+    }
+
+    #[test]
+    fn test_decode_with_bom() {
+        let bytes = b"\xFE\xFF\x00h\xD8\x00";
+        assert_eq!(decode_with_bom(bytes, Charset::Utf8, ErrorMode::Strict).unwrap_err().position, 4);
+        assert_eq!(decode_with_bom(b"\xEF\xBB\xBFok", Charset::Latin1, ErrorMode::Strict), Ok(("ok".to_string(), Charset::Utf8)));
+    }
+
+    #[test]
+    fn test_detect_charset() {
+        assert_eq!(detect_charset(b""), (Charset::Utf8, 1.0));
+        assert_eq!(detect_charset(b"\xFE\xFF\x00h"), (Charset::Utf16Be, 1.0));
+        assert_eq!(detect_charset(b"plain ascii"), (Charset::Utf8, 1.0));
+        assert_eq!(detect_charset(&encode("Grüße aus Köln", Charset::Utf16Be, ErrorMode::Strict).unwrap()).0, Charset::Utf16Be);
+        assert_eq!(detect_charset(&encode("hello", Charset::Utf32Le, ErrorMode::Strict).unwrap()).0, Charset::Utf32Le);
+        assert_eq!(detect_charset(&encode("hello", Charset::Utf32Be, ErrorMode::Strict).unwrap()).0, Charset::Utf32Be);
+
+        let (charset, many) = detect_charset("naïve café résumé".as_bytes());
+        let (_, one) = detect_charset("café".as_bytes());
+        assert_eq!(charset, Charset::Utf8);
+        assert!(many > one && one > 0.5);
+
+        let latin = encode("Grüße aus Köln", Charset::Latin1, ErrorMode::Strict).unwrap();
+        assert_eq!(detect_charset(&latin).0, Charset::Latin1);
+        let windows = encode("“Grüße” – 5€", Charset::Windows1252, ErrorMode::Strict).unwrap();
+        assert_eq!(detect_charset(&windows).0, Charset::Windows1252);
+        let (_, binary) = detect_charset(&[0xFF, 0x00, 0x9D, 0x01, 0xC0, 0x7F, 0x02, 0xAA]);
+        assert!(binary < 0.3);
+    }
+
+    #[test]
+    fn test_encode_single_byte() {
+        assert_eq!(encode("é¤", Charset::Latin9, ErrorMode::Replace), Ok(b"\xE9?".to_vec()));
+        assert_eq!(encode("é€", Charset::Latin1, ErrorMode::Replace), Ok(b"\xE9?".to_vec()));
+        assert_eq!(encode("Ā😀", Charset::Windows1252, ErrorMode::Escape), Ok(b"\\u0100\\U0001f600".to_vec()));
+        assert_eq!(
+            encode("ok…ā", Charset::Latin1, ErrorMode::Strict),
+            Err(DecodeError::new(2, "Character '…' cannot be encoded in ISO-8859-1"))
+        );
+    }
+}
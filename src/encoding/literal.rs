@@ -0,0 +1,342 @@
+//! Conversion between text and source-code string literals for C, Rust and Python.
+//!
+//! [`escape`] produces the body of a double-quoted literal and [`quote`] adds the
+//! quotes; [`unescape`] goes the other way on a literal body and reports
+//! malformed escapes with the byte offset of their backslash.
+
+use super::DecodeError;
+
+/// The programming language whose string-literal syntax is used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Language {
+    /// C (and C++) string literals. Non-ASCII characters are kept as UTF-8.
+    C,
+    /// Rust string literals.
+    Rust,
+    /// Python 3 `str` literals.
+    Python,
+}
+
+/// Escapes text as the body of a double-quoted string literal.
+///
+/// Quotes, backslashes and control characters are escaped with each language's
+/// short escapes where they exist, and otherwise with octal (C), `\u{...}` (Rust)
+/// or `\xNN`/`\uXXXX` (Python) escapes. Printable non-ASCII characters are kept.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to escape.
+/// * `language` - The language of the literal.
+///
+/// # Returns
+///
+/// * A `String` containing the literal body, without quotes.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::literal::{escape, Language};
+///
+/// assert_eq!(escape("tab\there \"q\"\u{1}", Language::C), "tab\\there \\\"q\\\"\\001");
+/// assert_eq!(escape("bell\u{7}", Language::Rust), "bell\\u{7}");
+/// assert_eq!(escape("bell\u{7}", Language::Python), "bell\\a");
+/// ```
+pub fn escape(text: &str, language: Language) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    let mut previous = '\0';
+    for c in text.chars() {
+        match (c, language) {
+            ('"', _) => escaped.push_str("\\\""),
+            ('\\', _) => escaped.push_str("\\\\"),
+            ('\n', _) => escaped.push_str("\\n"),
+            ('\r', _) => escaped.push_str("\\r"),
+            ('\t', _) => escaped.push_str("\\t"),
+            ('\u{7}', Language::C | Language::Python) => escaped.push_str("\\a"),
+            ('\u{8}', Language::C | Language::Python) => escaped.push_str("\\b"),
+            ('\u{B}', Language::C | Language::Python) => escaped.push_str("\\v"),
+            ('\u{C}', Language::C | Language::Python) => escaped.push_str("\\f"),
+            // Break up `??` so it cannot start a trigraph.
+            ('?', Language::C) if previous == '?' => escaped.push_str("\\?"),
+            ('\0', Language::Rust) => escaped.push_str("\\0"),
+            (c, Language::C) if c.is_control() => {
+                let mut buffer = [0u8; 4];
+                for byte in c.encode_utf8(&mut buffer).bytes() {
+                    escaped.push_str(&format!("\\{:03o}", byte));
+                }
+            }
+            (c, Language::Rust) if c.is_control() => escaped.push_str(&format!("\\u{{{:x}}}", c as u32)),
+            (c, Language::Python) if c.is_control() && (c as u32) < 0x100 => {
+                escaped.push_str(&format!("\\x{:02x}", c as u32))
+            }
+            (c, Language::Python) if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            (c, _) => escaped.push(c),
+        }
+        previous = c;
+    }
+    escaped
+}
+
+/// Escapes text and wraps it in double quotes, producing a complete string literal.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to quote.
+/// * `language` - The language of the literal.
+///
+/// # Returns
+///
+/// * A `String` containing the string literal.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::literal::{quote, Language};
+///
+/// assert_eq!(quote("say \"hi\"\n", Language::Python), "\"say \\\"hi\\\"\\n\"");
+/// ```
+pub fn quote(text: &str, language: Language) -> String {
+    format!("\"{}\"", escape(text, language))
+}
+
+/// Wraps text in a Rust raw string literal, choosing just enough `#` delimiters.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text.
+///
+/// # Returns
+///
+/// * An `Option<String>` containing the raw string literal, or `None` if the text contains a carriage return, which raw strings cannot hold.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::literal::rust_raw_string;
+///
+/// assert_eq!(rust_raw_string(r"C:\path"), Some(r#"r"C:\path""#.to_string()));
+/// assert_eq!(rust_raw_string("a\"#b"), Some("r##\"a\"#b\"##".to_string()));
+/// ```
+pub fn rust_raw_string(text: &str) -> Option<String> {
+    if text.contains('\r') {
+        return None;
+    }
+    // A raw string ends at a quote followed by as many `#` as it opened with.
+    let longest_run = text
+        .match_indices('"')
+        .map(|(i, _)| 1 + text[i + 1..].bytes().take_while(|&b| b == b'#').count())
+        .max()
+        .unwrap_or(0);
+    let hashes = "#".repeat(longest_run);
+    Some(format!("r{}\"{}\"{}", hashes, text, hashes))
+}
+
+// Appends the UTF-8 encoding of `c`.
+fn push_char(bytes: &mut Vec<u8>, c: char) {
+    bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+}
+
+// Reads up to `max` digits of `radix` starting at byte `start`, returning the value and digit count.
+fn read_digits(body: &str, start: usize, radix: u32, max: usize) -> (u32, usize) {
+    body[start..]
+        .chars()
+        .take(max)
+        .map_while(|c| c.to_digit(radix))
+        .fold((0, 0), |(value, count), digit| (value * radix + digit, count + 1))
+}
+
+/// Unescapes the body of a double-quoted string literal, without the quotes.
+///
+/// Each language's escapes are supported: C's short, octal and `\x` escapes,
+/// Rust's `\x`, `\u{...}` and line-continuation escapes, and Python's octal,
+/// `\x`, `\u`, `\U` and line-continuation escapes. Python's `\N{...}` name
+/// escapes need the Unicode name database and are reported as errors, and
+/// unknown Python escapes keep their backslash, as Python does.
+///
+/// # Arguments
+///
+/// * `body` - A string slice that holds the literal body.
+/// * `language` - The language of the literal.
+///
+/// # Returns
+///
+/// * A `Result<String, DecodeError>` containing the unescaped text, or an error at the first malformed escape.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::encoding::literal::{unescape, Language};
+///
+/// assert_eq!(unescape(r"caf\303\251\x21", Language::C), Ok("café!".to_string()));
+/// assert_eq!(unescape(r"\u{1F600}", Language::Rust), Ok("😀".to_string()));
+/// assert_eq!(unescape(r"\U0001F600\N", Language::Python).unwrap_err().position, 10);
+/// ```
+pub fn unescape(body: &str, language: Language) -> Result<String, DecodeError> {
+    // C escapes produce bytes; the others produce characters, stored here as UTF-8.
+    let mut bytes = Vec::with_capacity(body.len());
+    // The input offset that produced each output byte, for reporting invalid UTF-8.
+    let mut origins = Vec::with_capacity(body.len());
+    let mut i = 0;
+    while i < body.len() {
+        let c = body[i..].chars().next().unwrap_or('\0');
+        if c != '\\' {
+            bytes.extend_from_slice(&body.as_bytes()[i..i + c.len_utf8()]);
+            origins.resize(bytes.len(), i);
+            i += c.len_utf8();
+            continue;
+        }
+        let kind = body[i + 1..]
+            .chars()
+            .next()
+            .ok_or_else(|| DecodeError::new(i, "Incomplete escape sequence"))?;
+        let mut next = i + 1 + kind.len_utf8();
+        match (kind, language) {
+            ('"', _) => bytes.push(b'"'),
+            ('\'', _) => bytes.push(b'\''),
+            ('\\', _) => bytes.push(b'\\'),
+            ('n', _) => bytes.push(b'\n'),
+            ('r', _) => bytes.push(b'\r'),
+            ('t', _) => bytes.push(b'\t'),
+            ('a', Language::C | Language::Python) => bytes.push(0x07),
+            ('b', Language::C | Language::Python) => bytes.push(0x08),
+            ('v', Language::C | Language::Python) => bytes.push(0x0B),
+            ('f', Language::C | Language::Python) => bytes.push(0x0C),
+            ('?', Language::C) => bytes.push(b'?'),
+            ('0', Language::Rust) => bytes.push(0),
+            ('0'..='7', Language::C | Language::Python) => {
+                let (value, count) = read_digits(body, i + 1, 8, 3);
+                next = i + 1 + count;
+                match language {
+                    Language::C if value > 0xFF => {
+                        return Err(DecodeError::new(i, "Octal escape out of range"));
+                    }
+                    Language::C => bytes.push(value as u8),
+                    _ => push_char(&mut bytes, char::from_u32(value).unwrap_or('\u{FFFD}')),
+                }
+            }
+            ('x', _) => {
+                // C hex escapes take every following hex digit; Rust and Python take exactly two.
+                let max = if language == Language::C { 8 } else { 2 };
+                let (value, count) = read_digits(body, i + 2, 16, max);
+                let complete = if language == Language::C { count > 0 } else { count == 2 };
+                if !complete {
+                    return Err(DecodeError::new(i, "Invalid \\x escape: missing hex digits"));
+                }
+                next = i + 2 + count;
+                match language {
+                    Language::C if value > 0xFF || body[next..].starts_with(|c: char| c.is_ascii_hexdigit()) => {
+                        return Err(DecodeError::new(i, "Hex escape out of range"));
+                    }
+                    Language::C => bytes.push(value as u8),
+                    Language::Rust if value > 0x7F => {
+                        return Err(DecodeError::new(i, "Rust \\x escapes must be at most \\x7F"));
+                    }
+                    _ => push_char(&mut bytes, char::from_u32(value).unwrap_or('\u{FFFD}')),
+                }
+            }
+            ('u', Language::Rust) => {
+                let close = body[i..]
+                    .find('}')
+                    .filter(|_| body[i + 2..].starts_with('{'))
+                    .map(|offset| i + offset)
+                    .ok_or_else(|| DecodeError::new(i, "Invalid \\u escape: expected \\u{...}"))?;
+                let digits = body[i + 3..close].replace('_', "");
+                let code = (1..=6)
+                    .contains(&digits.len())
+                    .then(|| u32::from_str_radix(&digits, 16).ok())
+                    .flatten()
+                    .ok_or_else(|| DecodeError::new(i, "Invalid \\u escape: expected 1 to 6 hex digits"))?;
+                let c = char::from_u32(code)
+                    .ok_or_else(|| DecodeError::new(i, format!("Invalid Unicode scalar value U+{:X}", code)))?;
+                push_char(&mut bytes, c);
+                next = close + 1;
+            }
+            ('u' | 'U', Language::Python) => {
+                let width = if kind == 'u' { 4 } else { 8 };
+                let (code, count) = read_digits(body, i + 2, 16, width);
+                if count != width {
+                    return Err(DecodeError::new(i, format!("Invalid \\{} escape: expected {} hex digits", kind, width)));
+                }
+                let c = char::from_u32(code)
+                    .ok_or_else(|| DecodeError::new(i, format!("Invalid Unicode scalar value U+{:X}", code)))?;
+                push_char(&mut bytes, c);
+                next = i + 2 + width;
+            }
+            ('N', Language::Python) => {
+                return Err(DecodeError::new(i, "\\N{...} escapes are not supported"));
+            }
+            ('\n', Language::Rust) => {
+                // A line continuation also skips the next line's leading whitespace.
+                next += body[next..].len() - body[next..].trim_start().len();
+            }
+            ('\n', Language::Python) => {}
+            (other, Language::Python) => {
+                bytes.push(b'\\');
+                push_char(&mut bytes, other);
+            }
+            (other, _) => {
+                return Err(DecodeError::new(i, format!("Invalid escape sequence '\\{}'", other)));
+            }
+        }
+        origins.resize(bytes.len(), i);
+        i = next;
+    }
+    String::from_utf8(bytes).map_err(|e| {
+        DecodeError::new(origins[e.utf8_error().valid_up_to()], "Unescaped bytes are not valid UTF-8")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape() {
+        let text = "a\"b\\c\n\t\u{0}\u{7}??(é😀";
+        assert_eq!(escape(text, Language::C), r#"a\"b\\c\n\t\000\a?\?(é😀"#);
+        assert_eq!(escape(text, Language::Rust), r#"a\"b\\c\n\t\0\u{7}??(é😀"#);
+        assert_eq!(escape(text, Language::Python), r#"a\"b\\c\n\t\x00\a??(é😀"#);
+        assert_eq!(escape("\u{85}\u{2028}", Language::C), r"\302\205".to_string() + "\u{2028}");
+        assert_eq!(escape("\u{85}", Language::Python), r"\x85");
+        assert_eq!(escape("\u{85}", Language::Rust), r"\u{85}");
+        assert_eq!(quote("x", Language::Rust), "\"x\"");
+    }
+
+    #[test]
+    fn test_rust_raw_string() {
+        assert_eq!(rust_raw_string("plain"), Some("r\"plain\"".to_string()));
+        assert_eq!(rust_raw_string("a\"b"), Some("r#\"a\"b\"#".to_string()));
+        assert_eq!(rust_raw_string("\"##\"#"), Some("r###\"\"##\"#\"###".to_string()));
+        assert_eq!(rust_raw_string("a\r\nb"), None);
+    }
+
+    #[test]
+    fn test_unescape() {
+        let samples = ["", "plain", "a\"b\\c\n\r\t\u{0}\u{7}\u{B}\u{C}", "??(é😀\u{85}\u{1F}"];
+        for text in samples {
+            for language in [Language::C, Language::Rust, Language::Python] {
+                assert_eq!(unescape(&escape(text, language), language).as_deref(), Ok(text), "{:?}", language);
+            }
+        }
+
+        assert_eq!(unescape(r"\x41\101\0", Language::C), Ok("AA\0".to_string()));
+        assert_eq!(unescape(r"\x4142", Language::C).unwrap_err().message, "Hex escape out of range");
+        assert_eq!(unescape(r"\400", Language::C).unwrap_err().message, "Octal escape out of range");
+        assert_eq!(unescape(r"ab\q", Language::C).unwrap_err(), DecodeError::new(2, "Invalid escape sequence '\\q'"));
+        assert_eq!(unescape(r"\xff", Language::C).unwrap_err(), DecodeError::new(0, "Unescaped bytes are not valid UTF-8"));
+        assert_eq!(unescape(r"é \303\251 \303(", Language::C).unwrap_err().position, 12);
+
+        assert_eq!(unescape("\\u{1_F600} \\x7f a\\\n    b", Language::Rust), Ok("😀 \u{7f} ab".to_string()));
+        assert_eq!(unescape(r"\x80", Language::Rust).unwrap_err().position, 0);
+        assert_eq!(unescape(r"é\u{D800}", Language::Rust).unwrap_err().position, 2);
+        assert_eq!(unescape(r"\u{1234567}", Language::Rust).unwrap_err().position, 0);
+        assert_eq!(unescape(r"\u1234", Language::Rust).unwrap_err().position, 0);
+        assert_eq!(unescape(r"\a", Language::Rust).unwrap_err().position, 0);
+
+        assert_eq!(unescape("\\xe9\\u00e9\\351\\q\\\nx", Language::Python), Ok("ééé\\qx".to_string()));
+        assert_eq!(unescape(r"\x4", Language::Python).unwrap_err().position, 0);
+        assert_eq!(unescape(r"a\u12", Language::Python).unwrap_err().position, 1);
+        assert_eq!(unescape(r"\U00110000", Language::Python).unwrap_err().message, "Invalid Unicode scalar value U+110000");
+        assert_eq!(unescape(r"\N{BULLET}", Language::Python).unwrap_err().message, "\\N{...} escapes are not supported");
+        assert_eq!(unescape("ab\\", Language::Python).unwrap_err(), DecodeError::new(2, "Incomplete escape sequence"));
+    }
+}
@@ -0,0 +1,489 @@
+//! Chains of encoding and transform steps, in the spirit of CyberChef recipes.
+//!
+//! A [`Recipe`] is parsed from step names separated by `|`, such as
+//! `"from_base64|url_decode|html_decode"`, and runs over bytes. A failing step
+//! is reported as a [`RecipeError`] naming the step. [`Recipe::magic`] builds a
+//! decoding recipe automatically using [`detect_formats`].
+
+use super::{
+    decode_base32, decode_base64_with, decode_hex_bytes, decode_html_entities, decode_quoted_printable, decode_url_bytes,
+    detect_formats, encode_base32, encode_base64_bytes, encode_hex_bytes, encode_html_entities,
+    encode_quoted_printable, encode_url_bytes, from_binary_bytes, to_binary_bytes, Base32Alphabet,
+    Base64Config, Format, Padding,
+};
+use crate::{basic, transform};
+use std::fmt;
+
+/// A single step of a [`Recipe`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Encodes bytes as standard Base64 (`to_base64`).
+    ToBase64,
+    /// Decodes standard or URL-safe Base64 (`from_base64`).
+    FromBase64,
+    /// Encodes bytes as RFC 4648 Base32 (`to_base32`).
+    ToBase32,
+    /// Decodes RFC 4648 Base32 (`from_base32`).
+    FromBase32,
+    /// Encodes bytes as lowercase hex (`to_hex`).
+    ToHex,
+    /// Decodes hex (`from_hex`).
+    FromHex,
+    /// Percent-encodes bytes (`url_encode`).
+    UrlEncode,
+    /// Decodes percent-encoding, with `+` as a space (`url_decode`).
+    UrlDecode,
+    /// Escapes HTML special characters (`html_encode`).
+    HtmlEncode,
+    /// Decodes HTML character references (`html_decode`).
+    HtmlDecode,
+    /// Encodes bytes as quoted-printable (`to_quoted_printable`).
+    ToQuotedPrintable,
+    /// Decodes quoted-printable (`from_quoted_printable`).
+    FromQuotedPrintable,
+    /// Writes bytes as binary digits (`to_binary`).
+    ToBinary,
+    /// Reads binary digits back into bytes (`from_binary`).
+    FromBinary,
+    /// Rotates ASCII letters by 13 places (`rot13`).
+    Rot13,
+    /// Reverses the characters of the text (`reverse`).
+    Reverse,
+    /// Converts the text to uppercase (`upper`).
+    Upper,
+    /// Converts the text to lowercase (`lower`).
+    Lower,
+    /// Removes leading and trailing whitespace (`trim`).
+    Trim,
+    /// Removes all ASCII whitespace (`remove_whitespace`).
+    RemoveWhitespace,
+}
+
+const STEPS: [Step; 20] = [
+    Step::ToBase64,
+    Step::FromBase64,
+    Step::ToBase32,
+    Step::FromBase32,
+    Step::ToHex,
+    Step::FromHex,
+    Step::UrlEncode,
+    Step::UrlDecode,
+    Step::HtmlEncode,
+    Step::HtmlDecode,
+    Step::ToQuotedPrintable,
+    Step::FromQuotedPrintable,
+    Step::ToBinary,
+    Step::FromBinary,
+    Step::Rot13,
+    Step::Reverse,
+    Step::Upper,
+    Step::Lower,
+    Step::Trim,
+    Step::RemoveWhitespace,
+];
+
+// Reads the input of a text step as UTF-8.
+fn text_input(input: &[u8]) -> Result<&str, String> {
+    std::str::from_utf8(input).map_err(|e| format!("Input is not valid UTF-8 at position {}", e.valid_up_to()))
+}
+
+impl Step {
+    /// Looks up a step by name, ignoring case and treating `-` like `_`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - A string slice that holds the step name, such as `"from_base64"`.
+    ///
+    /// # Returns
+    ///
+    /// * An `Option<Step>` containing the step, or `None` if the name is unknown.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::encoding::recipe::Step;
+    ///
+    /// assert_eq!(Step::from_name("URL-Decode"), Some(Step::UrlDecode));
+    /// assert_eq!(Step::from_name("from_base99"), None);
+    /// ```
+    pub fn from_name(name: &str) -> Option<Step> {
+        let name = name.trim().to_ascii_lowercase().replace('-', "_");
+        STEPS.into_iter().find(|step| step.name() == name)
+    }
+
+    /// Returns the name of the step as used in recipe strings.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(loki_text::encoding::recipe::Step::FromHex.name(), "from_hex");
+    /// ```
+    pub fn name(self) -> &'static str {
+        match self {
+            Step::ToBase64 => "to_base64",
+            Step::FromBase64 => "from_base64",
+            Step::ToBase32 => "to_base32",
+            Step::FromBase32 => "from_base32",
+            Step::ToHex => "to_hex",
+            Step::FromHex => "from_hex",
+            Step::UrlEncode => "url_encode",
+            Step::UrlDecode => "url_decode",
+            Step::HtmlEncode => "html_encode",
+            Step::HtmlDecode => "html_decode",
+            Step::ToQuotedPrintable => "to_quoted_printable",
+            Step::FromQuotedPrintable => "from_quoted_printable",
+            Step::ToBinary => "to_binary",
+            Step::FromBinary => "from_binary",
+            Step::Rot13 => "rot13",
+            Step::Reverse => "reverse",
+            Step::Upper => "upper",
+            Step::Lower => "lower",
+            Step::Trim => "trim",
+            Step::RemoveWhitespace => "remove_whitespace",
+        }
+    }
+
+    /// Applies the step to some bytes.
+    ///
+    /// Steps that read text, which is every step except the encoders of binary
+    /// data, `rot13` and `remove_whitespace`, require their input to be UTF-8.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A slice of bytes to transform.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result<Vec<u8>, String>` containing the output bytes, or a description of why the step failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::encoding::recipe::Step;
+    ///
+    /// assert_eq!(Step::FromHex.apply(b"6869"), Ok(b"hi".to_vec()));
+    /// assert_eq!(Step::FromHex.apply(b"6x"), Err("Invalid hex character 'x' at position 1".to_string()));
+    /// ```
+    pub fn apply(self, input: &[u8]) -> Result<Vec<u8>, String> {
+        let output = match self {
+            Step::ToBase64 => encode_base64_bytes(input).into_bytes(),
+            Step::FromBase64 => {
+                let text = text_input(input)?;
+                decode_base64_with(text, &Base64Config::STANDARD)
+                    .or_else(|e| decode_base64_with(text, &Base64Config::URL_SAFE).map_err(|_| e))
+                    .map_err(|e| e.to_string())?
+            }
+            Step::ToBase32 => encode_base32(input, Base32Alphabet::Rfc4648, Padding::Required).into_bytes(),
+            Step::FromBase32 => {
+                decode_base32(text_input(input)?, Base32Alphabet::Rfc4648, Padding::Optional).map_err(|e| e.to_string())?
+            }
+            Step::ToHex => encode_hex_bytes(input).into_bytes(),
+            Step::FromHex => decode_hex_bytes(text_input(input)?).map_err(|e| e.to_string())?,
+            Step::UrlEncode => encode_url_bytes(input).into_bytes(),
+            Step::UrlDecode => decode_url_bytes(text_input(input)?).map_err(|e| e.to_string())?,
+            Step::HtmlEncode => encode_html_entities(text_input(input)?).into_bytes(),
+            Step::HtmlDecode => decode_html_entities(text_input(input)?).into_bytes(),
+            Step::ToQuotedPrintable => encode_quoted_printable(input, true).into_bytes(),
+            Step::FromQuotedPrintable => decode_quoted_printable(text_input(input)?).map_err(|e| e.to_string())?,
+            Step::ToBinary => to_binary_bytes(input).into_bytes(),
+            Step::FromBinary => from_binary_bytes(text_input(input)?).map_err(|e| e.to_string())?,
+            // ROT13 only touches ASCII letters, so it works on any bytes.
+            Step::Rot13 => input
+                .iter()
+                .map(|&b| match b {
+                    b'a'..=b'z' => (b - b'a' + 13) % 26 + b'a',
+                    b'A'..=b'Z' => (b - b'A' + 13) % 26 + b'A',
+                    _ => b,
+                })
+                .collect(),
+            Step::Reverse => transform::reverse_string(text_input(input)?).into_bytes(),
+            Step::Upper => basic::to_uppercase(text_input(input)?).into_bytes(),
+            Step::Lower => basic::to_lowercase(text_input(input)?).into_bytes(),
+            Step::Trim => basic::trim_whitespace(text_input(input)?).into_bytes(),
+            Step::RemoveWhitespace => input.iter().copied().filter(|b| !b.is_ascii_whitespace()).collect(),
+        };
+        Ok(output)
+    }
+
+    // The step that decodes a detected format.
+    fn decoder_for(format: Format) -> Step {
+        match format {
+            Format::Base64 => Step::FromBase64,
+            Format::Hex => Step::FromHex,
+            Format::Url => Step::UrlDecode,
+            Format::Binary => Step::FromBinary,
+            Format::Html => Step::HtmlDecode,
+            Format::Rot13 => Step::Rot13,
+        }
+    }
+}
+
+/// An error from [`Recipe::run`], naming the step that failed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecipeError {
+    /// The zero-based index of the failing step.
+    pub step: usize,
+    /// The name of the failing step.
+    pub name: &'static str,
+    /// A description of the problem.
+    pub message: String,
+}
+
+impl fmt::Display for RecipeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Step {} ({}) failed: {}", self.step + 1, self.name, self.message)
+    }
+}
+
+impl std::error::Error for RecipeError {}
+
+/// A sequence of [`Step`]s applied one after another.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Recipe {
+    steps: Vec<Step>,
+}
+
+impl Recipe {
+    /// Creates a recipe from a list of steps.
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - The steps, in the order they run.
+    ///
+    /// # Returns
+    ///
+    /// * A new `Recipe`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::encoding::recipe::{Recipe, Step};
+    ///
+    /// let recipe = Recipe::new(vec![Step::ToHex, Step::Upper]);
+    /// assert_eq!(recipe.run(b"hi"), Ok(b"6869".to_vec()));
+    /// ```
+    pub fn new(steps: Vec<Step>) -> Self {
+        Recipe { steps }
+    }
+
+    /// Parses a recipe from step names separated by `|`.
+    ///
+    /// # Arguments
+    ///
+    /// * `recipe` - A string slice such as `"from_base64|url_decode|html_decode"`. An empty string is an empty recipe.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result<Recipe, String>` containing the recipe, or an error naming the first unknown or empty step.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::encoding::recipe::{Recipe, Step};
+    ///
+    /// let recipe = Recipe::parse("from_base64 | url_decode").unwrap();
+    /// assert_eq!(recipe.steps(), &[Step::FromBase64, Step::UrlDecode]);
+    /// assert_eq!(Recipe::parse("from_base64|unzip"), Err("Unknown recipe step 'unzip'".to_string()));
+    /// ```
+    pub fn parse(recipe: &str) -> Result<Recipe, String> {
+        if recipe.trim().is_empty() {
+            return Ok(Recipe::default());
+        }
+        let steps = recipe
+            .split('|')
+            .enumerate()
+            .map(|(index, name)| match name.trim() {
+                "" => Err(format!("Empty recipe step at index {}", index)),
+                name => Step::from_name(name).ok_or_else(|| format!("Unknown recipe step '{}'", name)),
+            })
+            .collect::<Result<Vec<Step>, String>>()?;
+        Ok(Recipe { steps })
+    }
+
+    /// Returns the steps of the recipe.
+    pub fn steps(&self) -> &[Step] {
+        &self.steps
+    }
+
+    /// Runs the recipe over some bytes.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A slice of bytes to transform.
+    ///
+    /// # Returns
+    ///
+    /// * A `Result<Vec<u8>, RecipeError>` containing the output of the last step, or an error naming the step that failed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::encoding::recipe::Recipe;
+    ///
+    /// let recipe = Recipe::parse("from_base64|url_decode|html_decode").unwrap();
+    /// assert_eq!(recipe.run(b"JTI2bHQlM0JiJTI2Z3QlM0I="), Ok(b"<b>".to_vec()));
+    ///
+    /// let error = recipe.run(b"JTI2bHQlM0").unwrap_err();
+    /// assert_eq!((error.step, error.name), (0, "from_base64"));
+    /// ```
+    pub fn run(&self, input: &[u8]) -> Result<Vec<u8>, RecipeError> {
+        let mut data = input.to_vec();
+        for (index, step) in self.steps.iter().enumerate() {
+            data = step.apply(&data).map_err(|message| RecipeError {
+                step: index,
+                name: step.name(),
+                message,
+            })?;
+        }
+        Ok(data)
+    }
+
+    /// Builds a decoding recipe automatically, the way CyberChef's "magic" operation does.
+    ///
+    /// At each round the current data is read as text and passed to
+    /// [`detect_formats`]. The most confident format (at least 0.5) whose
+    /// decoder succeeds and changes the data is applied; a `remove_whitespace`
+    /// step is added first when Base64, hex or binary data is wrapped. The
+    /// search stops when the data is not UTF-8, no format fits, or `max_steps`
+    /// decoding steps have been added. ROT13 is never applied twice in a row.
+    ///
+    /// # Arguments
+    ///
+    /// * `input` - A slice of bytes to decode.
+    /// * `max_steps` - The maximum number of decoding steps to add.
+    ///
+    /// # Returns
+    ///
+    /// * A `(Recipe, Vec<u8>)` containing the recipe that was found and its output. The recipe is empty if nothing was decoded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::encoding::recipe::Recipe;
+    ///
+    /// let (recipe, output) = Recipe::magic(b"JTI2bHQlM0JiJTI2Z3QlM0I=", 5);
+    /// assert_eq!(recipe.to_string(), "from_base64|url_decode|html_decode");
+    /// assert_eq!(output, b"<b>");
+    /// ```
+    pub fn magic(input: &[u8], max_steps: usize) -> (Recipe, Vec<u8>) {
+        let mut steps = Vec::new();
+        let mut data = input.to_vec();
+        let mut decoded = 0;
+        while decoded < max_steps {
+            let Ok(text) = std::str::from_utf8(&data) else {
+                break;
+            };
+            let wrapped = text.trim().bytes().any(|b| b.is_ascii_whitespace());
+            let next = detect_formats(text)
+                .into_iter()
+                .filter(|&(format, confidence)| confidence >= 0.5 && !(format == Format::Rot13 && steps.last() == Some(&Step::Rot13)))
+                .find_map(|(format, _)| {
+                    let mut candidate = Vec::new();
+                    if wrapped && matches!(format, Format::Base64 | Format::Hex | Format::Binary) {
+                        candidate.push(Step::RemoveWhitespace);
+                    }
+                    candidate.push(Step::decoder_for(format));
+                    let output = Recipe::new(candidate.clone()).run(&data).ok()?;
+                    (output != data).then_some((candidate, output))
+                });
+            match next {
+                Some((candidate, output)) => {
+                    steps.extend(candidate);
+                    data = output;
+                    decoded += 1;
+                }
+                None => break,
+            }
+        }
+        (Recipe { steps }, data)
+    }
+}
+
+impl fmt::Display for Recipe {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.steps.iter().map(|step| step.name()).collect();
+        write!(f, "{}", names.join("|"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_step_names() {
+        for step in STEPS {
+            assert_eq!(Step::from_name(step.name()), Some(step));
+        }
+        assert_eq!(Step::from_name(" From-Base64 "), Some(Step::FromBase64));
+        assert_eq!(Step::from_name("gunzip"), None);
+    }
+
+    #[test]
+    fn test_step_apply() {
+        let input = "Tom & Jerry €".as_bytes();
+        for (encode, decode) in [
+            (Step::ToBase64, Step::FromBase64),
+            (Step::ToBase32, Step::FromBase32),
+            (Step::ToHex, Step::FromHex),
+            (Step::UrlEncode, Step::UrlDecode),
+            (Step::HtmlEncode, Step::HtmlDecode),
+            (Step::ToQuotedPrintable, Step::FromQuotedPrintable),
+            (Step::ToBinary, Step::FromBinary),
+            (Step::Rot13, Step::Rot13),
+            (Step::Reverse, Step::Reverse),
+        ] {
+            let encoded = encode.apply(input).unwrap();
+            assert_eq!(decode.apply(&encoded).as_deref(), Ok(input), "{}", encode.name());
+        }
+        assert_eq!(Step::FromBase64.apply(b"_-8"), Ok(vec![0xFF, 0xEF]));
+        assert_eq!(Step::Upper.apply(b"\xFFab"), Err("Input is not valid UTF-8 at position 0".to_string()));
+        assert_eq!(Step::Rot13.apply(b"\xFFab"), Ok(b"\xFFno".to_vec()));
+        assert_eq!(Step::RemoveWhitespace.apply(b" 68\n69 "), Ok(b"6869".to_vec()));
+        assert_eq!(Step::Trim.apply(b"  x "), Ok(b"x".to_vec()));
+        assert_eq!(Step::Lower.apply("ÀB".as_bytes()), Ok("àb".as_bytes().to_vec()));
+    }
+
+    #[test]
+    fn test_recipe_parse() {
+        assert_eq!(Recipe::parse("").map(|r| r.steps().len()), Ok(0));
+        assert_eq!(Recipe::parse("to_hex|upper").unwrap().to_string(), "to_hex|upper");
+        assert_eq!(Recipe::parse("to_hex||upper"), Err("Empty recipe step at index 1".to_string()));
+        assert_eq!(Recipe::parse("to_hex|nope"), Err("Unknown recipe step 'nope'".to_string()));
+    }
+
+    #[test]
+    fn test_recipe_run() {
+        let recipe = Recipe::parse("from_hex|from_base64|rot13").unwrap();
+        assert_eq!(recipe.run(b"64584a356557493d"), Ok(b"hello".to_vec()));
+        assert_eq!(Recipe::default().run(b"same"), Ok(b"same".to_vec()));
+
+        let error = recipe.run(b"64584a356557493").unwrap_err();
+        assert_eq!(error.to_string(), "Step 1 (from_hex) failed: Invalid hex string length at position 15");
+        let error = Recipe::parse("from_hex|html_decode").unwrap().run(b"ff").unwrap_err();
+        assert_eq!(error, RecipeError { step: 1, name: "html_decode", message: "Input is not valid UTF-8 at position 0".to_string() });
+    }
+
+    #[test]
+    fn test_recipe_magic() {
+        let secret = b"<b>Tom & Jerry</b>";
+        let encoded = Recipe::parse("html_encode|url_encode|to_base64|to_hex").unwrap().run(secret).unwrap();
+        let (recipe, output) = Recipe::magic(&encoded, 10);
+        assert_eq!(recipe.to_string(), "from_hex|from_base64|url_decode|html_decode");
+        assert_eq!(output, secret);
+
+        let (recipe, output) = Recipe::magic(b"01101000 01101001", 10);
+        assert_eq!(recipe.to_string(), "remove_whitespace|from_binary");
+        assert_eq!(output, b"hi");
+
+        let (recipe, output) = Recipe::magic(b"Gur dhvpx oebja sbk whzcf bire gur ynml qbt", 10);
+        assert_eq!(recipe.steps(), &[Step::Rot13]);
+        assert_eq!(output, b"The quick brown fox jumps over the lazy dog");
+
+        let (recipe, _) = Recipe::magic(&encoded, 2);
+        assert_eq!(recipe.to_string(), "from_hex|from_base64");
+        let (recipe, output) = Recipe::magic(b"plain text", 10);
+        assert!(recipe.steps().is_empty());
+        assert_eq!(output, b"plain text");
+    }
+}
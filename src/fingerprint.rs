@@ -0,0 +1,483 @@
+use std::collections::{HashMap, HashSet};
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hashes bytes with 64-bit FNV-1a so fingerprints are stable across runs and platforms.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    let mut hash = FNV_OFFSET;
+    for &b in bytes {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(FNV_PRIME);
+    }
+    hash
+}
+
+/// Scrambles a 64-bit value (SplitMix64 finalizer), used to derive independent hash functions.
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
+
+/// Splits text into lowercase alphanumeric words.
+fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(|w| w.to_lowercase())
+        .collect()
+}
+
+/// Builds the word k-shingles of a text.
+///
+/// The text is lowercased and split on non-alphanumeric characters before the
+/// shingles are formed, so punctuation and spacing differences are ignored.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to shingle.
+/// * `k` - The number of consecutive words in each shingle.
+///
+/// # Returns
+///
+/// * A `Vec<String>` containing the shingles in order of appearance. Texts shorter than `k` words yield a single shingle.
+///
+/// # Examples
+///
+/// ```
+/// let text = "The quick, brown fox";
+/// let result = loki_text::fingerprint::shingles(text, 2);
+/// assert_eq!(result, vec!["the quick", "quick brown", "brown fox"]);
+/// ```
+pub fn shingles(text: &str, k: usize) -> Vec<String> {
+    let words = tokenize(text);
+    if words.is_empty() || k == 0 {
+        return Vec::new();
+    }
+    if words.len() <= k {
+        return vec![words.join(" ")];
+    }
+    words.windows(k).map(|w| w.join(" ")).collect()
+}
+
+/// Builds the character k-shingles of a text.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to shingle.
+/// * `k` - The number of consecutive characters in each shingle.
+///
+/// # Returns
+///
+/// * A `Vec<String>` containing the shingles in order of appearance.
+///
+/// # Examples
+///
+/// ```
+/// let text = "abcd";
+/// let result = loki_text::fingerprint::char_shingles(text, 3);
+/// assert_eq!(result, vec!["abc", "bcd"]);
+/// ```
+pub fn char_shingles(text: &str, k: usize) -> Vec<String> {
+    let chars: Vec<char> = text.chars().collect();
+    if k == 0 || chars.len() < k {
+        return Vec::new();
+    }
+    chars.windows(k).map(|w| w.iter().collect()).collect()
+}
+
+/// Computes the exact Jaccard similarity of two shingle sets.
+///
+/// # Arguments
+///
+/// * `a` - The shingles of the first text.
+/// * `b` - The shingles of the second text.
+///
+/// # Returns
+///
+/// * A `f64` between 0.0 and 1.0. Two empty sets are considered identical.
+///
+/// # Examples
+///
+/// ```
+/// let a = loki_text::fingerprint::shingles("a b c", 1);
+/// let b = loki_text::fingerprint::shingles("b c d", 1);
+/// let result = loki_text::fingerprint::jaccard_similarity(&a, &b);
+/// assert_eq!(result, 0.5);
+/// ```
+pub fn jaccard_similarity(a: &[String], b: &[String]) -> f64 {
+    let a: HashSet<&String> = a.iter().collect();
+    let b: HashSet<&String> = b.iter().collect();
+    let union = a.union(&b).count();
+    if union == 0 {
+        return 1.0;
+    }
+    a.intersection(&b).count() as f64 / union as f64
+}
+
+/// Computes the MinHash signature of a shingle set.
+///
+/// # Arguments
+///
+/// * `shingles` - The shingles of the text, for example from [`shingles`].
+/// * `num_hashes` - The number of hash functions, i.e. the signature length.
+///
+/// # Returns
+///
+/// * A `Vec<u64>` of length `num_hashes` holding the minimum hash per function.
+///
+/// # Examples
+///
+/// ```
+/// let a = loki_text::fingerprint::shingles("the quick brown fox", 2);
+/// let sig = loki_text::fingerprint::minhash_signature(&a, 64);
+/// assert_eq!(sig.len(), 64);
+/// assert_eq!(loki_text::fingerprint::minhash_similarity(&sig, &sig), 1.0);
+/// ```
+pub fn minhash_signature(shingles: &[String], num_hashes: usize) -> Vec<u64> {
+    let base: Vec<u64> = shingles.iter().map(|s| fnv1a(s.as_bytes())).collect();
+    (0..num_hashes)
+        .map(|i| {
+            let seed = mix(i as u64);
+            base.iter().map(|&h| mix(h ^ seed)).min().unwrap_or(u64::MAX)
+        })
+        .collect()
+}
+
+/// Estimates the Jaccard similarity of two texts from their MinHash signatures.
+///
+/// # Arguments
+///
+/// * `a` - The signature of the first text.
+/// * `b` - The signature of the second text, built with the same number of hashes.
+///
+/// # Returns
+///
+/// * A `f64` between 0.0 and 1.0: the fraction of positions where the signatures agree.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::fingerprint::minhash_similarity(&[1, 2, 3, 4], &[1, 2, 0, 0]);
+/// assert_eq!(result, 0.5);
+/// ```
+pub fn minhash_similarity(a: &[u64], b: &[u64]) -> f64 {
+    let len = a.len().min(b.len());
+    if len == 0 {
+        return 0.0;
+    }
+    let equal = a.iter().zip(b).filter(|(x, y)| x == y).count();
+    equal as f64 / len as f64
+}
+
+/// A locality-sensitive hashing index over MinHash signatures.
+///
+/// Signatures are cut into `bands` bands of `rows` values each; two documents
+/// become candidates when at least one of their bands is identical.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::fingerprint::{minhash_signature, shingles, MinHashLsh};
+///
+/// let mut lsh = MinHashLsh::new(16, 4);
+/// let a = minhash_signature(&shingles("my printer is out of paper again", 2), 64);
+/// let b = minhash_signature(&shingles("the weather is nice today", 2), 64);
+/// lsh.insert(0, &a);
+/// lsh.insert(1, &b);
+/// assert_eq!(lsh.candidates(&a), vec![0]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct MinHashLsh {
+    bands: usize,
+    rows: usize,
+    buckets: HashMap<(usize, u64), Vec<usize>>,
+}
+
+impl MinHashLsh {
+    /// Creates an empty index for signatures of length `bands * rows`.
+    pub fn new(bands: usize, rows: usize) -> Self {
+        MinHashLsh {
+            bands,
+            rows,
+            buckets: HashMap::new(),
+        }
+    }
+
+    fn band_keys<'a>(&'a self, signature: &'a [u64]) -> impl Iterator<Item = (usize, u64)> + 'a {
+        (0..self.bands).filter_map(move |band| {
+            let start = band * self.rows;
+            let slice = signature.get(start..start + self.rows)?;
+            let key = slice.iter().fold(FNV_OFFSET, |acc, &v| mix(acc ^ v));
+            Some((band, key))
+        })
+    }
+
+    /// Adds a document signature to the index under the given id.
+    pub fn insert(&mut self, id: usize, signature: &[u64]) {
+        let keys: Vec<(usize, u64)> = self.band_keys(signature).collect();
+        for key in keys {
+            self.buckets.entry(key).or_default().push(id);
+        }
+    }
+
+    /// Returns the sorted, deduplicated ids sharing at least one band with the signature.
+    pub fn candidates(&self, signature: &[u64]) -> Vec<usize> {
+        let mut ids: Vec<usize> = self
+            .band_keys(signature)
+            .filter_map(|key| self.buckets.get(&key))
+            .flatten()
+            .copied()
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+}
+
+/// Computes the 64-bit SimHash of a text.
+///
+/// Each word contributes its hash weighted by its frequency, so texts that
+/// differ in a few words produce fingerprints with a small Hamming distance.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to fingerprint.
+///
+/// # Returns
+///
+/// * A `u64` containing the SimHash fingerprint.
+///
+/// # Examples
+///
+/// ```
+/// let a = loki_text::fingerprint::simhash("Hello World");
+/// let b = loki_text::fingerprint::simhash("hello, world!");
+/// assert_eq!(a, b);
+/// ```
+pub fn simhash(text: &str) -> u64 {
+    let mut weights = [0i64; 64];
+    let mut counts: HashMap<String, i64> = HashMap::new();
+    for word in tokenize(text) {
+        *counts.entry(word).or_insert(0) += 1;
+    }
+    for (word, count) in counts {
+        let hash = fnv1a(word.as_bytes());
+        for (bit, weight) in weights.iter_mut().enumerate() {
+            if hash >> bit & 1 == 1 {
+                *weight += count;
+            } else {
+                *weight -= count;
+            }
+        }
+    }
+    weights
+        .iter()
+        .enumerate()
+        .filter(|(_, &w)| w > 0)
+        .fold(0u64, |acc, (bit, _)| acc | 1 << bit)
+}
+
+/// Counts the differing bits between two fingerprints.
+///
+/// # Arguments
+///
+/// * `a` - The first fingerprint.
+/// * `b` - The second fingerprint.
+///
+/// # Returns
+///
+/// * A `u32` containing the Hamming distance.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::fingerprint::hamming_distance(0b1011, 0b0001);
+/// assert_eq!(result, 2);
+/// ```
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Computes the winnowing fingerprints of a text, as used by MOSS.
+///
+/// The text is reduced to its lowercase alphanumeric characters, every
+/// `k`-gram is hashed, and the minimum hash of each window of `window`
+/// consecutive hashes is kept.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to fingerprint.
+/// * `k` - The length of the hashed character grams (noise threshold).
+/// * `window` - The number of consecutive hashes per window.
+///
+/// # Returns
+///
+/// * A `Vec<(u64, usize)>` containing each selected hash with the byte offset of its k-gram in `text`.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::fingerprint::winnow("A do run run run, a do run run", 5, 4);
+/// assert!(!result.is_empty());
+/// ```
+pub fn winnow(text: &str, k: usize, window: usize) -> Vec<(u64, usize)> {
+    let kept: Vec<(usize, char)> = text
+        .char_indices()
+        .filter(|(_, c)| c.is_alphanumeric())
+        .flat_map(|(i, c)| c.to_lowercase().map(move |l| (i, l)))
+        .collect();
+    if k == 0 || window == 0 || kept.len() < k {
+        return Vec::new();
+    }
+
+    let hashes: Vec<(u64, usize)> = kept
+        .windows(k)
+        .map(|gram| {
+            let s: String = gram.iter().map(|&(_, c)| c).collect();
+            (fnv1a(s.as_bytes()), gram[0].0)
+        })
+        .collect();
+
+    let mut fingerprints = Vec::new();
+    let mut last_selected = None;
+    let window = window.min(hashes.len());
+    for start in 0..=hashes.len() - window {
+        // Take the rightmost minimum so runs of equal hashes select one fingerprint.
+        let mut min = start;
+        for i in start..start + window {
+            if hashes[i].0 <= hashes[min].0 {
+                min = i;
+            }
+        }
+        if last_selected != Some(min) {
+            fingerprints.push(hashes[min]);
+            last_selected = Some(min);
+        }
+    }
+    fingerprints
+}
+
+/// Finds locally matching passages between two documents using winnowing.
+///
+/// # Arguments
+///
+/// * `a` - A string slice that holds the first document.
+/// * `b` - A string slice that holds the second document.
+/// * `k` - The length of the hashed character grams.
+/// * `window` - The winnowing window size.
+///
+/// # Returns
+///
+/// * A `Vec<(usize, usize)>` containing pairs of byte offsets `(in a, in b)` whose fingerprints match, sorted.
+///
+/// # Examples
+///
+/// ```
+/// let a = "The quick brown fox jumps";
+/// let b = "Yesterday a quick brown fox slept";
+/// let result = loki_text::fingerprint::winnow_matches(a, b, 5, 3);
+/// assert!(!result.is_empty());
+/// ```
+pub fn winnow_matches(a: &str, b: &str, k: usize, window: usize) -> Vec<(usize, usize)> {
+    let mut index: HashMap<u64, Vec<usize>> = HashMap::new();
+    for (hash, pos) in winnow(b, k, window) {
+        index.entry(hash).or_default().push(pos);
+    }
+    let mut matches: Vec<(usize, usize)> = winnow(a, k, window)
+        .into_iter()
+        .filter_map(|(hash, pos_a)| index.get(&hash).map(|ps| (pos_a, ps)))
+        .flat_map(|(pos_a, ps)| ps.iter().map(move |&pos_b| (pos_a, pos_b)))
+        .collect();
+    matches.sort_unstable();
+    matches.dedup();
+    matches
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shingles() {
+        let text = "The quick, brown fox";
+        assert_eq!(shingles(text, 2), vec!["the quick", "quick brown", "brown fox"]);
+        assert_eq!(shingles("hi", 3), vec!["hi"]);
+        assert!(shingles("", 2).is_empty());
+    }
+
+    #[test]
+    fn test_char_shingles() {
+        assert_eq!(char_shingles("abcd", 3), vec!["abc", "bcd"]);
+        assert!(char_shingles("ab", 3).is_empty());
+    }
+
+    #[test]
+    fn test_jaccard_similarity() {
+        let a = shingles("a b c", 1);
+        let b = shingles("b c d", 1);
+        assert_eq!(jaccard_similarity(&a, &b), 0.5);
+    }
+
+    #[test]
+    fn test_minhash_similarity() {
+        let a = shingles("my printer is out of paper and will not print anything today", 2);
+        let b = shingles("my printer is out of paper and will not print anything now", 2);
+        let c = shingles("please reset the password for the admin account", 2);
+        let sig_a = minhash_signature(&a, 128);
+        let sig_b = minhash_signature(&b, 128);
+        let sig_c = minhash_signature(&c, 128);
+        assert!(minhash_similarity(&sig_a, &sig_b) > 0.6);
+        assert!(minhash_similarity(&sig_a, &sig_c) < 0.2);
+    }
+
+    #[test]
+    fn test_minhash_lsh() {
+        let mut lsh = MinHashLsh::new(32, 4);
+        let a = minhash_signature(&shingles("my printer is out of paper and will not print anything today", 2), 128);
+        let b = minhash_signature(&shingles("my printer is out of paper and will not print anything now", 2), 128);
+        let c = minhash_signature(&shingles("please reset the password for the admin account", 2), 128);
+        lsh.insert(0, &a);
+        lsh.insert(2, &c);
+        assert_eq!(lsh.candidates(&b), vec![0]);
+    }
+
+    #[test]
+    fn test_simhash() {
+        let a = simhash("the server returned an error while connecting to the database");
+        let b = simhash("the server returned an error while connecting to the database again");
+        let c = simhash("lunch menu for friday includes soup and salad");
+        assert!(hamming_distance(a, b) < hamming_distance(a, c));
+        assert_eq!(simhash("Hello World"), simhash("hello, world!"));
+    }
+
+    #[test]
+    fn test_hamming_distance() {
+        assert_eq!(hamming_distance(0b1011, 0b0001), 2);
+        assert_eq!(hamming_distance(u64::MAX, 0), 64);
+    }
+
+    #[test]
+    fn test_winnow() {
+        let text = "A do run run run, a do run run";
+        let fingerprints = winnow(text, 5, 4);
+        assert!(!fingerprints.is_empty());
+        for &(_, pos) in &fingerprints {
+            assert!(text.is_char_boundary(pos));
+        }
+        assert!(winnow("abc", 5, 4).is_empty());
+    }
+
+    #[test]
+    fn test_winnow_matches() {
+        let a = "The quick brown fox jumps";
+        let b = "Yesterday a quick brown fox slept";
+        let matches = winnow_matches(a, b, 5, 3);
+        assert!(!matches.is_empty());
+        for (pa, pb) in matches {
+            assert!(pa >= 4 && pb >= 12);
+        }
+        assert!(winnow_matches("abcdefgh", "zyxwvuts", 4, 2).is_empty());
+    }
+}
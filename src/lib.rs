@@ -1,5 +1,15 @@
+// The baseline tests compare predicates against `true`/`false` explicitly.
+#![cfg_attr(test, allow(clippy::bool_assert_comparison))]
+
 pub mod basic;
 pub mod search;
 pub mod transform;
 pub mod utils;
 pub mod encoding;
+pub mod fingerprint;
+pub mod diff;
+pub mod patch;
+pub mod trie;
+pub mod phonetic;
+#[cfg(feature = "parallel")]
+pub mod parallel;
@@ -0,0 +1,270 @@
+//! Multi-threaded variants of the `search` functions, enabled by the
+//! `parallel` cargo feature.
+//!
+//! Large texts are split into chunks that overlap by the length of the longest
+//! pattern minus one byte, so a match straddling a chunk boundary is still
+//! found. Each chunk only reports matches that *start* inside it, which keeps
+//! the merged results free of duplicates. Results are always returned in the
+//! same order as a sequential scan would produce, regardless of scheduling.
+
+use crate::search::{kmp_search_all, AhoCorasick};
+use std::thread;
+
+/// Returns the number of worker threads to use.
+fn worker_count() -> usize {
+    thread::available_parallelism().map(|n| n.get()).unwrap_or(1)
+}
+
+/// Moves `index` forward to the nearest char boundary of `text`.
+fn ceil_char_boundary(text: &str, mut index: usize) -> usize {
+    while index < text.len() && !text.is_char_boundary(index) {
+        index += 1;
+    }
+    index.min(text.len())
+}
+
+/// Splits `text` into `(start, end)` byte ranges of about `chunk_size` bytes,
+/// all starting and ending on char boundaries.
+fn chunk_bounds(text: &str, chunk_size: usize) -> Vec<(usize, usize)> {
+    let chunk_size = chunk_size.max(1);
+    let mut bounds = Vec::new();
+    let mut start = 0;
+    while start < text.len() {
+        let end = ceil_char_boundary(text, start + chunk_size);
+        bounds.push((start, end));
+        start = end;
+    }
+    bounds
+}
+
+/// Runs `task` on every item across worker threads and returns the results in
+/// item order.
+fn run_parallel<T, R, F>(items: &[T], task: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(usize, &T) -> R + Sync,
+{
+    if items.is_empty() {
+        return Vec::new();
+    }
+    let per_worker = items.len().div_ceil(worker_count());
+    let task = &task;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(per_worker)
+            .enumerate()
+            .map(|(group, batch)| {
+                scope.spawn(move || {
+                    batch
+                        .iter()
+                        .enumerate()
+                        .map(|(offset, item)| task(group * per_worker + offset, item))
+                        .collect::<Vec<R>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("search worker panicked"))
+            .collect()
+    })
+}
+
+/// Finds every, possibly overlapping, occurrence of a substring by searching
+/// overlapping chunks of the text on several threads.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to search within.
+/// * `pattern` - A string slice that holds the substring to search for.
+/// * `chunk_size` - The approximate number of bytes each chunk covers.
+///
+/// # Returns
+///
+/// * A `Vec<usize>` containing the absolute byte offsets of all occurrences, in increasing order.
+///
+/// # Examples
+///
+/// ```
+/// let text = "abcabcabcabc";
+/// let result = loki_text::parallel::par_find_all(text, "cab", 4);
+/// assert_eq!(result, vec![2, 5, 8]);
+/// ```
+pub fn par_find_all(text: &str, pattern: &str, chunk_size: usize) -> Vec<usize> {
+    if pattern.is_empty() {
+        return Vec::new();
+    }
+    let overlap = pattern.len() - 1;
+    let bounds = chunk_bounds(text, chunk_size);
+    run_parallel(&bounds, |_, &(start, end)| {
+        let scan_end = ceil_char_boundary(text, end + overlap);
+        kmp_search_all(&text[start..scan_end], pattern)
+            .into_iter()
+            .map(|offset| start + offset)
+            .filter(|&offset| offset < end)
+            .collect::<Vec<usize>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Finds all occurrences of several substrings with the Aho-Corasick algorithm,
+/// searching overlapping chunks of the text on several threads.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to search within.
+/// * `patterns` - A vector of string slices that holds the substrings to search for.
+/// * `chunk_size` - The approximate number of bytes each chunk covers.
+///
+/// # Returns
+///
+/// * A `Vec<(usize, &str)>` containing the absolute byte offsets and the matched patterns, sorted by offset and then by pattern order.
+///
+/// # Examples
+///
+/// ```
+/// let text = "The quick brown fox jumps over the lazy dog";
+/// let patterns = vec!["quick", "fox", "dog"];
+/// let result = loki_text::parallel::par_aho_corasick_search(text, patterns, 8);
+/// assert_eq!(result, vec![(4, "quick"), (16, "fox"), (40, "dog")]);
+/// ```
+pub fn par_aho_corasick_search<'a>(
+    text: &'a str,
+    patterns: Vec<&'a str>,
+    chunk_size: usize,
+) -> Vec<(usize, &'a str)> {
+    let overlap = patterns.iter().map(|p| p.len()).max().unwrap_or(0).saturating_sub(1);
+    let ac = AhoCorasick::new(patterns.clone());
+    let bounds = chunk_bounds(text, chunk_size);
+    let mut matches: Vec<(usize, usize)> = run_parallel(&bounds, |_, &(start, end)| {
+        let scan_end = ceil_char_boundary(text, end + overlap);
+        ac.find_iter(&text[start..scan_end])
+            .map(|(offset, index)| (start + offset, index))
+            .filter(|&(offset, _)| offset < end)
+            .collect::<Vec<(usize, usize)>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect();
+    matches.sort_unstable();
+    matches.dedup();
+    matches.into_iter().map(|(offset, index)| (offset, patterns[index])).collect()
+}
+
+/// Finds every occurrence of a substring in a batch of documents, searching the
+/// documents on several threads.
+///
+/// # Arguments
+///
+/// * `documents` - A slice of documents to search.
+/// * `pattern` - A string slice that holds the substring to search for.
+///
+/// # Returns
+///
+/// * A `Vec<(usize, usize)>` containing `(document index, byte offset)` pairs, ordered by document and then by offset.
+///
+/// # Examples
+///
+/// ```
+/// let documents = ["a fox", "no match", "fox and fox"];
+/// let result = loki_text::parallel::par_search_documents(&documents, "fox");
+/// assert_eq!(result, vec![(0, 2), (2, 0), (2, 8)]);
+/// ```
+pub fn par_search_documents<S: AsRef<str> + Sync>(documents: &[S], pattern: &str) -> Vec<(usize, usize)> {
+    run_parallel(documents, |index, document| {
+        kmp_search_all(document.as_ref(), pattern)
+            .into_iter()
+            .map(|offset| (index, offset))
+            .collect::<Vec<(usize, usize)>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+/// Finds all occurrences of several substrings in a batch of documents with the
+/// Aho-Corasick algorithm, searching the documents on several threads.
+///
+/// # Arguments
+///
+/// * `documents` - A slice of documents to search.
+/// * `patterns` - A vector of string slices that holds the substrings to search for.
+///
+/// # Returns
+///
+/// * A `Vec<(usize, usize, &str)>` containing `(document index, byte offset, pattern)` triples, ordered by document, offset and pattern order.
+///
+/// # Examples
+///
+/// ```
+/// let documents = ["the lazy dog", "a quick fox"];
+/// let result = loki_text::parallel::par_aho_corasick_documents(&documents, vec!["fox", "dog"]);
+/// assert_eq!(result, vec![(0, 9, "dog"), (1, 8, "fox")]);
+/// ```
+pub fn par_aho_corasick_documents<'a, S: AsRef<str> + Sync>(
+    documents: &[S],
+    patterns: Vec<&'a str>,
+) -> Vec<(usize, usize, &'a str)> {
+    let ac = AhoCorasick::new(patterns.clone());
+    run_parallel(documents, |index, document| {
+        let mut matches: Vec<(usize, usize)> = ac.find_iter(document.as_ref()).collect();
+        matches.sort_unstable();
+        matches.dedup();
+        matches
+            .into_iter()
+            .map(|(offset, pattern)| (index, offset, patterns[pattern]))
+            .collect::<Vec<(usize, usize, &'a str)>>()
+    })
+    .into_iter()
+    .flatten()
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::search::aho_corasick_search;
+
+    #[test]
+    fn test_par_find_all() {
+        let text = "abababab".repeat(50);
+        let expected = kmp_search_all(&text, "abab");
+        for chunk_size in [1, 3, 7, 64, 1000] {
+            assert_eq!(par_find_all(&text, "abab", chunk_size), expected);
+        }
+        assert_eq!(par_find_all("héllo wörld héllo", "héllo", 2), vec![0, 14]);
+        assert!(par_find_all("abc", "", 1).is_empty());
+    }
+
+    #[test]
+    fn test_par_aho_corasick_search() {
+        let text = "she sells sea shells by the sea shore; ".repeat(20);
+        let patterns = vec!["she", "he", "sea", "shells"];
+        let mut expected = aho_corasick_search(&text, patterns.clone());
+        expected.sort_by_key(|&(offset, pattern)| (offset, patterns.iter().position(|p| *p == pattern)));
+        for chunk_size in [1, 5, 16, 4096] {
+            assert_eq!(par_aho_corasick_search(&text, patterns.clone(), chunk_size), expected);
+        }
+    }
+
+    #[test]
+    fn test_par_search_documents() {
+        let documents: Vec<String> = (0..20).map(|i| "x".repeat(i) + "needle").collect();
+        let result = par_search_documents(&documents, "needle");
+        let expected: Vec<(usize, usize)> = (0..20).map(|i| (i, i)).collect();
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_par_aho_corasick_documents() {
+        let documents = ["ushers", "", "his hers"];
+        let result = par_aho_corasick_documents(&documents, vec!["he", "she", "his", "hers"]);
+        assert_eq!(
+            result,
+            vec![(0, 1, "she"), (0, 2, "he"), (0, 2, "hers"), (2, 0, "his"), (2, 4, "he"), (2, 4, "hers")]
+        );
+    }
+}
@@ -0,0 +1,584 @@
+use crate::diff::{diff_tokens, tokenize, Algorithm, Edit, Granularity};
+use regex::Regex;
+use std::fmt;
+
+/// A line of a unified diff hunk, including its trailing newline if it has one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HunkLine {
+    /// A line present in both the old and new text.
+    Context(String),
+    /// A line removed from the old text.
+    Delete(String),
+    /// A line added in the new text.
+    Insert(String),
+}
+
+/// A single `@@ ... @@` section of a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    /// The 1-based line where the hunk starts in the old text.
+    pub old_start: usize,
+    /// The number of old lines covered by the hunk.
+    pub old_count: usize,
+    /// The 1-based line where the hunk starts in the new text.
+    pub new_start: usize,
+    /// The number of new lines covered by the hunk.
+    pub new_count: usize,
+    /// The lines of the hunk in order.
+    pub lines: Vec<HunkLine>,
+}
+
+/// Describes a hunk that could not be applied.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RejectedHunk {
+    /// The 0-based index of the hunk within the patch.
+    pub index: usize,
+    /// The line where the hunk was expected in the old text.
+    pub old_start: usize,
+    /// Why the hunk was rejected.
+    pub reason: String,
+}
+
+/// An error raised while parsing or applying a patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PatchError {
+    /// The patch is not a well-formed unified diff.
+    Malformed {
+        /// The 1-based line of the patch where parsing failed.
+        line: usize,
+        /// A description of the problem.
+        message: String,
+    },
+    /// Some hunks did not apply.
+    Rejected {
+        /// The text with every applicable hunk applied.
+        partial: String,
+        /// The hunks that could not be applied.
+        hunks: Vec<RejectedHunk>,
+    },
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PatchError::Malformed { line, message } => {
+                write!(f, "Malformed patch at line {}: {}", line, message)
+            }
+            PatchError::Rejected { hunks, .. } => {
+                write!(f, "{} hunk(s) rejected", hunks.len())?;
+                for hunk in hunks {
+                    write!(f, "; hunk #{} at line {}: {}", hunk.index + 1, hunk.old_start, hunk.reason)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+/// Parses the hunks of a unified diff.
+///
+/// File header lines (`---`, `+++`, `diff`, `index`) are skipped.
+///
+/// # Arguments
+///
+/// * `patch` - A string slice that holds the unified diff.
+///
+/// # Returns
+///
+/// * A `Result<Vec<Hunk>, PatchError>` containing the hunks or the position of the first malformed line.
+///
+/// # Examples
+///
+/// ```
+/// let patch = "--- a\n+++ b\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n";
+/// let hunks = loki_text::patch::parse_patch(patch).unwrap();
+/// assert_eq!(hunks.len(), 1);
+/// assert_eq!(hunks[0].old_start, 1);
+/// ```
+pub fn parse_patch(patch: &str) -> Result<Vec<Hunk>, PatchError> {
+    let header = Regex::new(r"^@@ -(\d+)(?:,(\d+))? \+(\d+)(?:,(\d+))? @@").unwrap();
+    // Keep each line's own terminator so CRLF patches apply to CRLF texts.
+    let lines: Vec<&str> = patch.split_inclusive('\n').collect();
+    let mut hunks = Vec::new();
+    let mut i = 0;
+
+    while i < lines.len() {
+        let Some(caps) = header.captures(lines[i]) else {
+            if lines[i].starts_with("@@") {
+                return Err(PatchError::Malformed {
+                    line: i + 1,
+                    message: "invalid hunk header".to_string(),
+                });
+            }
+            i += 1;
+            continue;
+        };
+        // No text has more than `isize::MAX` lines, so larger numbers are out of bounds too.
+        let number = |n: usize, default: usize| {
+            caps.get(n).map_or(Ok(default), |m| m.as_str().parse::<isize>().map(|v| v as usize))
+        };
+        let malformed = |line: usize| PatchError::Malformed {
+            line,
+            message: "hunk range out of bounds".to_string(),
+        };
+        let mut hunk = Hunk {
+            old_start: number(1, 0).map_err(|_| malformed(i + 1))?,
+            old_count: number(2, 1).map_err(|_| malformed(i + 1))?,
+            new_start: number(3, 0).map_err(|_| malformed(i + 1))?,
+            new_count: number(4, 1).map_err(|_| malformed(i + 1))?,
+            lines: Vec::new(),
+        };
+        i += 1;
+
+        let (mut old_left, mut new_left) = (hunk.old_count, hunk.new_count);
+        while old_left > 0 || new_left > 0 {
+            let Some(&line) = lines.get(i) else {
+                return Err(PatchError::Malformed {
+                    line: i + 1,
+                    message: "unexpected end of hunk".to_string(),
+                });
+            };
+            let (prefix, body) = match line.chars().next() {
+                // Some tools strip the single space of empty context lines.
+                Some('\r' | '\n') | None => (' ', line),
+                Some(c) => (c, &line[c.len_utf8()..]),
+            };
+            let body = if body.ends_with('\n') {
+                body.to_string()
+            } else {
+                format!("{}\n", body)
+            };
+            match prefix {
+                ' ' if old_left > 0 && new_left > 0 => {
+                    old_left -= 1;
+                    new_left -= 1;
+                    hunk.lines.push(HunkLine::Context(body));
+                }
+                '-' if old_left > 0 => {
+                    old_left -= 1;
+                    hunk.lines.push(HunkLine::Delete(body));
+                }
+                '+' if new_left > 0 => {
+                    new_left -= 1;
+                    hunk.lines.push(HunkLine::Insert(body));
+                }
+                '\\' => strip_newline(&mut hunk.lines),
+                _ => {
+                    return Err(PatchError::Malformed {
+                        line: i + 1,
+                        message: "hunk line does not match the header counts".to_string(),
+                    })
+                }
+            }
+            i += 1;
+        }
+        if lines.get(i).is_some_and(|l| l.starts_with('\\')) {
+            strip_newline(&mut hunk.lines);
+            i += 1;
+        }
+        hunks.push(hunk);
+    }
+    Ok(hunks)
+}
+
+/// Applies a "\ No newline at end of file" marker to the preceding hunk line.
+fn strip_newline(lines: &mut [HunkLine]) {
+    if let Some(HunkLine::Context(s) | HunkLine::Delete(s) | HunkLine::Insert(s)) = lines.last_mut() {
+        if s.ends_with('\n') {
+            s.pop();
+        }
+    }
+}
+
+/// Applies a unified diff to a text, requiring every context line to match.
+///
+/// Hunks may still be found away from their recorded line numbers.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to patch.
+/// * `patch` - A string slice that holds the unified diff.
+///
+/// # Returns
+///
+/// * A `Result<String, PatchError>` containing the patched text or the reason it could not be patched.
+///
+/// # Examples
+///
+/// ```
+/// let text = "a\nb\nc\n";
+/// let patch = "--- a\n+++ b\n@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n";
+/// let result = loki_text::patch::apply_patch(text, patch);
+/// assert_eq!(result, Ok("a\nx\nc\n".to_string()));
+/// ```
+pub fn apply_patch(text: &str, patch: &str) -> Result<String, PatchError> {
+    apply_patch_with(text, patch, 0, usize::MAX)
+}
+
+/// Applies a unified diff to a text with fuzz and offset tolerance.
+///
+/// For each hunk the recorded position is tried first, then positions up to
+/// `max_offset` lines before and after it. If no exact match exists, up to
+/// `fuzz` leading and trailing context lines are ignored, like `patch -F`.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to patch.
+/// * `patch` - A string slice that holds the unified diff.
+/// * `fuzz` - The maximum number of outer context lines that may be ignored.
+/// * `max_offset` - The maximum distance in lines from the recorded position.
+///
+/// # Returns
+///
+/// * A `Result<String, PatchError>` containing the patched text or the reason it could not be patched.
+///
+/// # Examples
+///
+/// ```
+/// let text = "A\nb\nc\n";
+/// let patch = "@@ -1,3 +1,3 @@\n a\n b\n-c\n+x\n";
+/// let result = loki_text::patch::apply_patch_with(text, patch, 1, 0);
+/// assert_eq!(result, Ok("A\nb\nx\n".to_string()));
+/// ```
+pub fn apply_patch_with(text: &str, patch: &str, fuzz: usize, max_offset: usize) -> Result<String, PatchError> {
+    let hunks = parse_patch(patch)?;
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut output = String::new();
+    let mut cursor = 0;
+    let mut drift: isize = 0;
+    let mut rejected = Vec::new();
+
+    for (index, hunk) in hunks.iter().enumerate() {
+        match locate(&lines, hunk, cursor, drift, fuzz, max_offset) {
+            Some((pos, lead, trail)) => {
+                let kept = &hunk.lines[lead..hunk.lines.len() - trail];
+                let old_len = kept.iter().filter(|l| !matches!(l, HunkLine::Insert(_))).count();
+                output.extend(lines[cursor..pos].iter().copied());
+                for line in kept {
+                    if let HunkLine::Context(s) | HunkLine::Insert(s) = line {
+                        output.push_str(s);
+                    }
+                }
+                cursor = pos + old_len;
+                drift = pos as isize - (expected_position(hunk, lines.len()) + lead) as isize;
+            }
+            None => rejected.push(RejectedHunk {
+                index,
+                old_start: hunk.old_start,
+                reason: "context does not match".to_string(),
+            }),
+        }
+    }
+    output.extend(lines[cursor..].iter().copied());
+
+    if rejected.is_empty() {
+        Ok(output)
+    } else {
+        Err(PatchError::Rejected {
+            partial: output,
+            hunks: rejected,
+        })
+    }
+}
+
+/// The 0-based line index where a hunk's old lines begin, clamped to the end of the text.
+fn expected_position(hunk: &Hunk, len: usize) -> usize {
+    let start = if hunk.old_count == 0 {
+        hunk.old_start
+    } else {
+        hunk.old_start.saturating_sub(1)
+    };
+    start.min(len)
+}
+
+/// Finds where a hunk applies, returning the position and the number of leading and trailing lines ignored.
+fn locate(
+    lines: &[&str],
+    hunk: &Hunk,
+    cursor: usize,
+    drift: isize,
+    fuzz: usize,
+    max_offset: usize,
+) -> Option<(usize, usize, usize)> {
+    let leading = hunk.lines.iter().take_while(|l| matches!(l, HunkLine::Context(_))).count();
+    let trailing = hunk.lines.iter().rev().take_while(|l| matches!(l, HunkLine::Context(_))).count();
+
+    for level in 0..=fuzz {
+        let lead = level.min(leading);
+        let trail = level.min(trailing);
+        // Stop once fuzz would ignore every line, which only happens for all-context hunks.
+        if level > 0 && (lead + trail == 0 || lead + trail >= hunk.lines.len()) {
+            break;
+        }
+        let old: Vec<&str> = hunk.lines[lead..hunk.lines.len() - trail]
+            .iter()
+            .filter_map(|l| match l {
+                HunkLine::Context(s) | HunkLine::Delete(s) => Some(s.as_str()),
+                HunkLine::Insert(_) => None,
+            })
+            .collect();
+        let expected = (expected_position(hunk, lines.len()) + lead).saturating_add_signed(drift);
+        let matches_at = |pos: usize| {
+            pos >= cursor && pos + old.len() <= lines.len() && lines[pos..pos + old.len()] == old[..]
+        };
+
+        let limit = max_offset.min(lines.len() + hunk.lines.len());
+        for offset in 0..=limit {
+            if let Some(pos) = expected.checked_sub(offset).filter(|&pos| matches_at(pos)) {
+                return Some((pos, lead, trail));
+            }
+            if offset > 0 && matches_at(expected + offset) {
+                return Some((expected + offset, lead, trail));
+            }
+        }
+    }
+    None
+}
+
+/// The result of a three-way merge.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Merge {
+    /// The merged text, with conflict markers around unresolved regions.
+    pub text: String,
+    /// The number of conflicting regions.
+    pub conflicts: usize,
+}
+
+impl Merge {
+    /// Returns `true` if the merge completed without conflicts.
+    pub fn is_clean(&self) -> bool {
+        self.conflicts == 0
+    }
+}
+
+/// Maps each base line to its matching line on the other side, if unchanged.
+fn line_matches(base: &[&str], other: &[&str]) -> Vec<Option<usize>> {
+    let mut matches = vec![None; base.len()];
+    for edit in diff_tokens(base, other, Algorithm::Myers) {
+        if let Edit::Equal(i, j) = edit {
+            matches[i] = Some(j);
+        }
+    }
+    matches
+}
+
+/// Merges two edited versions of a common base text line by line.
+///
+/// Conflicts are marked with `<<<<<<< ours`, `=======` and `>>>>>>> theirs`.
+///
+/// # Arguments
+///
+/// * `base` - A string slice that holds the common ancestor.
+/// * `ours` - A string slice that holds our version.
+/// * `theirs` - A string slice that holds their version.
+///
+/// # Returns
+///
+/// * A `Merge` containing the merged text and the number of conflicts.
+///
+/// # Examples
+///
+/// ```
+/// let base = "a\nb\nc\n";
+/// let ours = "A\nb\nc\n";
+/// let theirs = "a\nb\nC\n";
+/// let result = loki_text::patch::merge3(base, ours, theirs);
+/// assert_eq!(result.text, "A\nb\nC\n");
+/// assert!(result.is_clean());
+/// ```
+pub fn merge3(base: &str, ours: &str, theirs: &str) -> Merge {
+    merge3_with_labels(base, ours, theirs, "ours", "theirs")
+}
+
+/// Merges two edited versions of a common base text with custom conflict labels.
+///
+/// # Arguments
+///
+/// * `base` - A string slice that holds the common ancestor.
+/// * `ours` - A string slice that holds our version.
+/// * `theirs` - A string slice that holds their version.
+/// * `ours_label` - The label written after `<<<<<<<`.
+/// * `theirs_label` - The label written after `>>>>>>>`.
+///
+/// # Returns
+///
+/// * A `Merge` containing the merged text and the number of conflicts.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::patch::merge3_with_labels("a\n", "b\n", "c\n", "local", "upstream");
+/// assert_eq!(result.text, "<<<<<<< local\nb\n=======\nc\n>>>>>>> upstream\n");
+/// assert_eq!(result.conflicts, 1);
+/// ```
+pub fn merge3_with_labels(base: &str, ours: &str, theirs: &str, ours_label: &str, theirs_label: &str) -> Merge {
+    let base = tokenize(base, Granularity::Line);
+    let ours = tokenize(ours, Granularity::Line);
+    let theirs = tokenize(theirs, Granularity::Line);
+    let in_ours = line_matches(&base, &ours);
+    let in_theirs = line_matches(&base, &theirs);
+
+    let mut text = String::new();
+    let mut conflicts = 0;
+    let (mut i, mut j, mut k) = (0, 0, 0);
+    loop {
+        // Find the next base line kept unchanged by both sides.
+        let stable = (i..base.len()).find_map(|b| Some((b, in_ours[b]?, in_theirs[b]?)));
+        let (b, o, t) = stable.unwrap_or((base.len(), ours.len(), theirs.len()));
+
+        if (b, o, t) != (i, j, k) {
+            let (base_chunk, ours_chunk, theirs_chunk) = (&base[i..b], &ours[j..o], &theirs[k..t]);
+            if ours_chunk == base_chunk || ours_chunk == theirs_chunk {
+                text.extend(theirs_chunk.iter().copied());
+            } else if theirs_chunk == base_chunk {
+                text.extend(ours_chunk.iter().copied());
+            } else {
+                conflicts += 1;
+                text.push_str(&format!("<<<<<<< {}\n", ours_label));
+                push_chunk(&mut text, ours_chunk);
+                text.push_str("=======\n");
+                push_chunk(&mut text, theirs_chunk);
+                text.push_str(&format!(">>>>>>> {}\n", theirs_label));
+            }
+        }
+
+        if stable.is_none() {
+            break;
+        }
+        text.push_str(base[b]);
+        (i, j, k) = (b + 1, o + 1, t + 1);
+    }
+    Merge { text, conflicts }
+}
+
+/// Writes the lines of a conflict side, making sure the marker that follows starts on its own line.
+fn push_chunk(text: &mut String, lines: &[&str]) {
+    text.extend(lines.iter().copied());
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::diff::unified_diff;
+
+    #[test]
+    fn test_parse_patch() {
+        let patch = "--- a\n+++ b\n@@ -1,2 +1,2 @@\n a\n-b\n+c\n\\ No newline at end of file\n";
+        let hunks = parse_patch(patch).unwrap();
+        assert_eq!(hunks.len(), 1);
+        assert_eq!(
+            hunks[0].lines,
+            vec![
+                HunkLine::Context("a\n".to_string()),
+                HunkLine::Delete("b\n".to_string()),
+                HunkLine::Insert("c".to_string()),
+            ]
+        );
+
+        let result = parse_patch("@@ -1,2 +1,2 @@\n a\n");
+        assert_eq!(
+            result,
+            Err(PatchError::Malformed {
+                line: 3,
+                message: "unexpected end of hunk".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_apply_patch() {
+        let old = "one\ntwo\nthree\nfour\nfive\nsix\nseven\n";
+        let new = "one\n2\nthree\nfour\nfive\nsix\n7\neight";
+        let patch = unified_diff(old, new, "a", "b", 1);
+        assert_eq!(apply_patch(old, &patch), Ok(new.to_string()));
+    }
+
+    #[test]
+    fn test_apply_patch_crlf() {
+        let (old, new) = ("a\r\nb\r\n", "a\r\nc\r\n");
+        let patch = unified_diff(old, new, "a", "b", 3);
+        assert_eq!(apply_patch(old, &patch), Ok(new.to_string()));
+        assert_eq!(parse_patch(&patch).unwrap()[0].lines[0], HunkLine::Context("a\r\n".to_string()));
+        assert!(apply_patch("a\nb\n", &patch).is_err());
+    }
+
+    #[test]
+    fn test_apply_patch_huge_start() {
+        let patch = "@@ -9223372036854775809,2 +1,2 @@\n a\n-b\n+c\n";
+        assert!(matches!(apply_patch("a\nb\n", patch), Err(PatchError::Malformed { line: 1, .. })));
+        let patch = "@@ -9223372036854775807,2 +1,2 @@\n a\n-b\n+c\n";
+        assert_eq!(apply_patch("a\nb\n", patch), Ok("a\nc\n".to_string()));
+        assert!(matches!(apply_patch_with("a\nb\n", patch, 0, 0), Err(PatchError::Rejected { .. })));
+    }
+
+    #[test]
+    fn test_apply_patch_offset() {
+        let patch = "@@ -1,3 +1,3 @@\n a\n-b\n+x\n c\n";
+        let text = "header\nmore\na\nb\nc\n";
+        assert_eq!(apply_patch(text, patch), Ok("header\nmore\na\nx\nc\n".to_string()));
+        assert!(apply_patch_with(text, patch, 0, 1).is_err());
+    }
+
+    #[test]
+    fn test_apply_patch_with() {
+        let text = "A\nb\nc\n";
+        let patch = "@@ -1,3 +1,3 @@\n a\n b\n-c\n+x\n";
+        assert_eq!(apply_patch_with(text, patch, 1, 0), Ok("A\nb\nx\n".to_string()));
+    }
+
+    #[test]
+    fn test_apply_patch_with_context_only_hunk() {
+        assert!(matches!(apply_patch_with("x\n", "@@ -1 +1 @@\n a\n", 1, 0), Err(PatchError::Rejected { .. })));
+        assert_eq!(apply_patch_with("a\n", "@@ -1 +1 @@\n a\n", 1, 0), Ok("a\n".to_string()));
+
+        let patch = "@@ -1,3 +1,3 @@\n a\n b\n c\n";
+        assert!(matches!(apply_patch_with("x\ny\nz\n", patch, 2, 0), Err(PatchError::Rejected { .. })));
+        assert_eq!(apply_patch_with("x\nb\nz\n", patch, 2, 0), Ok("x\nb\nz\n".to_string()));
+    }
+
+    #[test]
+    fn test_apply_patch_rejected() {
+        let text = "a\nb\nc\nd\n";
+        let patch = "@@ -1,2 +1,2 @@\n a\n-b\n+B\n@@ -4 +4 @@\n-z\n+Z\n";
+        match apply_patch(text, patch) {
+            Err(PatchError::Rejected { partial, hunks }) => {
+                assert_eq!(partial, "a\nB\nc\nd\n");
+                assert_eq!(hunks.len(), 1);
+                assert_eq!(hunks[0].index, 1);
+                assert_eq!(hunks[0].old_start, 4);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_merge3() {
+        let base = "a\nb\nc\n";
+        let result = merge3(base, "A\nb\nc\n", "a\nb\nC\n");
+        assert_eq!(result.text, "A\nb\nC\n");
+        assert!(result.is_clean());
+
+        let result = merge3(base, "a\nx\nc\n", "a\ny\nc\n");
+        assert_eq!(result.text, "a\n<<<<<<< ours\nx\n=======\ny\n>>>>>>> theirs\nc\n");
+        assert_eq!(result.conflicts, 1);
+
+        let result = merge3(base, "a\nx\nc\n", "a\nx\nc\n");
+        assert_eq!(result.text, "a\nx\nc\n");
+        assert!(result.is_clean());
+
+        let result = merge3(base, "a\nb\nc\nd\n", "z\na\nb\nc\n");
+        assert_eq!(result.text, "z\na\nb\nc\nd\n");
+    }
+
+    #[test]
+    fn test_merge3_with_labels() {
+        let result = merge3_with_labels("a\n", "b\n", "c\n", "local", "upstream");
+        assert_eq!(result.text, "<<<<<<< local\nb\n=======\nc\n>>>>>>> upstream\n");
+        assert_eq!(result.conflicts, 1);
+    }
+}
@@ -0,0 +1,1044 @@
+/// The phonetic algorithms available for comparing strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PhoneticAlgorithm {
+    /// American Soundex, see [`soundex`].
+    Soundex,
+    /// Refined Soundex, see [`refined_soundex`].
+    RefinedSoundex,
+    /// Original Metaphone, see [`metaphone`].
+    Metaphone,
+    /// Double Metaphone, see [`double_metaphone`]. Either key may match.
+    DoubleMetaphone,
+    /// NYSIIS, see [`nysiis`].
+    Nysiis,
+    /// Kölner Phonetik, see [`cologne_phonetic`].
+    Cologne,
+}
+
+/// Keeps the ASCII letters of a text, uppercased.
+fn ascii_letters(text: &str) -> Vec<char> {
+    text.chars()
+        .filter(|c| c.is_ascii_alphabetic())
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
+
+fn is_vowel(c: char) -> bool {
+    matches!(c, 'A' | 'E' | 'I' | 'O' | 'U')
+}
+
+/// Encodes a string with American Soundex.
+///
+/// The code is the first letter followed by three digits. `H` and `W` do not
+/// separate letters with the same code, vowels do.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the word to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the four-character code, or an empty string if the text has no letters.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::phonetic::soundex("Robert");
+/// assert_eq!(result, "R163");
+/// ```
+pub fn soundex(text: &str) -> String {
+    let code = |c: char| match c {
+        'B' | 'F' | 'P' | 'V' => '1',
+        'C' | 'G' | 'J' | 'K' | 'Q' | 'S' | 'X' | 'Z' => '2',
+        'D' | 'T' => '3',
+        'L' => '4',
+        'M' | 'N' => '5',
+        'R' => '6',
+        _ => '0',
+    };
+
+    let letters = ascii_letters(text);
+    let Some(&first) = letters.first() else {
+        return String::new();
+    };
+    let mut result = first.to_string();
+    let mut last = code(first);
+    for &c in &letters[1..] {
+        if c == 'H' || c == 'W' {
+            continue;
+        }
+        let digit = code(c);
+        if digit != '0' && digit != last {
+            result.push(digit);
+            if result.len() == 4 {
+                break;
+            }
+        }
+        last = digit;
+    }
+    while result.len() < 4 {
+        result.push('0');
+    }
+    result
+}
+
+/// Encodes a string with Refined Soundex.
+///
+/// Unlike [`soundex`], every letter is coded (the first one included), the
+/// code is not truncated, and letters are split into finer groups.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the word to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the code, or an empty string if the text has no letters.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::phonetic::refined_soundex("testing");
+/// assert_eq!(result, "T6036084");
+/// ```
+pub fn refined_soundex(text: &str) -> String {
+    const CODES: &[u8; 26] = b"01360240043788015936020505";
+
+    let letters = ascii_letters(text);
+    let Some(&first) = letters.first() else {
+        return String::new();
+    };
+    let mut result = first.to_string();
+    let mut last = None;
+    for c in letters {
+        let digit = CODES[(c as u8 - b'A') as usize] as char;
+        if last != Some(digit) {
+            result.push(digit);
+            last = Some(digit);
+        }
+    }
+    result
+}
+
+/// Encodes a string with the original Metaphone algorithm.
+///
+/// The full code is returned; truncate it if a fixed length is needed.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the word to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the Metaphone code, where `0` stands for "th".
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::phonetic::metaphone("character");
+/// assert_eq!(result, "KRKTR");
+/// ```
+pub fn metaphone(text: &str) -> String {
+    let mut word = ascii_letters(text);
+    if word.len() <= 1 {
+        return word.into_iter().collect();
+    }
+
+    // Initial letter exceptions.
+    match (word[0], word[1]) {
+        ('K' | 'G' | 'P', 'N') | ('A', 'E') | ('W', 'R') => {
+            word.remove(0);
+        }
+        ('W', 'H') => {
+            word.remove(1);
+        }
+        ('X', _) => word[0] = 'S',
+        _ => {}
+    }
+
+    let len = word.len();
+    let at = |i: usize| word.get(i).copied().unwrap_or('\0');
+    let region = |i: usize, s: &str| s.chars().enumerate().all(|(k, c)| at(i + k) == c);
+    let front_vowel = |c: char| matches!(c, 'E' | 'I' | 'Y');
+    let is_last = |i: usize| i + 1 == len;
+
+    let mut code = String::new();
+    let mut n = 0;
+    while n < len {
+        let c = word[n];
+        let previous = if n > 0 { at(n - 1) } else { '\0' };
+        if c != 'C' && previous == c {
+            n += 1;
+            continue;
+        }
+        match c {
+            'A' | 'E' | 'I' | 'O' | 'U' => {
+                if n == 0 {
+                    code.push(c);
+                }
+            }
+            'B' => {
+                if !(previous == 'M' && is_last(n)) {
+                    code.push('B');
+                }
+            }
+            'C' => {
+                if previous == 'S' && front_vowel(at(n + 1)) {
+                    // SCI, SCE and SCY are silent.
+                } else if region(n, "CIA") {
+                    code.push('X');
+                } else if front_vowel(at(n + 1)) {
+                    code.push('S');
+                } else if previous == 'S' && at(n + 1) == 'H' {
+                    code.push('K');
+                } else if at(n + 1) == 'H' {
+                    if n == 0 && len >= 3 && is_vowel(at(2)) {
+                        code.push('K');
+                    } else {
+                        code.push('X');
+                    }
+                } else {
+                    code.push('K');
+                }
+            }
+            'D' => {
+                if at(n + 1) == 'G' && front_vowel(at(n + 2)) {
+                    code.push('J');
+                    n += 2;
+                } else {
+                    code.push('T');
+                }
+            }
+            'G' => {
+                let silent_gh = at(n + 1) == 'H' && (is_last(n + 1) || !is_vowel(at(n + 2)));
+                let silent_gn = n > 0 && (region(n, "GN") || region(n, "GNED"));
+                if silent_gh || silent_gn {
+                    // Silent.
+                } else if front_vowel(at(n + 1)) && previous != 'G' {
+                    code.push('J');
+                } else {
+                    code.push('K');
+                }
+            }
+            'H' => {
+                if !is_last(n) && !matches!(previous, 'C' | 'S' | 'P' | 'T' | 'G') && is_vowel(at(n + 1)) {
+                    code.push('H');
+                }
+            }
+            'K' => {
+                if previous != 'C' {
+                    code.push('K');
+                }
+            }
+            'P' => code.push(if at(n + 1) == 'H' { 'F' } else { 'P' }),
+            'Q' => code.push('K'),
+            'S' => {
+                if region(n, "SH") || region(n, "SIO") || region(n, "SIA") {
+                    code.push('X');
+                } else {
+                    code.push('S');
+                }
+            }
+            'T' => {
+                if region(n, "TIA") || region(n, "TIO") {
+                    code.push('X');
+                } else if region(n, "TCH") {
+                    // Silent, the CH is coded next.
+                } else if region(n, "TH") {
+                    code.push('0');
+                } else {
+                    code.push('T');
+                }
+            }
+            'V' => code.push('F'),
+            'W' | 'Y' => {
+                if is_vowel(at(n + 1)) {
+                    code.push(c);
+                }
+            }
+            'X' => code.push_str("KS"),
+            'Z' => code.push('S'),
+            _ => code.push(c),
+        }
+        n += 1;
+    }
+    code
+}
+
+/// Accumulates the primary and alternate Double Metaphone keys.
+struct DoubleMetaphoneKeys {
+    primary: String,
+    alternate: String,
+}
+
+impl DoubleMetaphoneKeys {
+    const MAX_LENGTH: usize = 4;
+
+    fn both(&mut self, s: &str) {
+        self.primary(s);
+        self.alternate(s);
+    }
+
+    fn split(&mut self, primary: &str, alternate: &str) {
+        self.primary(primary);
+        self.alternate(alternate);
+    }
+
+    fn primary(&mut self, s: &str) {
+        for c in s.chars() {
+            if self.primary.len() < Self::MAX_LENGTH {
+                self.primary.push(c);
+            }
+        }
+    }
+
+    fn alternate(&mut self, s: &str) {
+        for c in s.chars() {
+            if self.alternate.len() < Self::MAX_LENGTH {
+                self.alternate.push(c);
+            }
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.primary.len() >= Self::MAX_LENGTH && self.alternate.len() >= Self::MAX_LENGTH
+    }
+}
+
+/// The uppercased word being encoded by Double Metaphone, with bounds-safe lookups.
+struct Word {
+    chars: Vec<char>,
+    slavo_germanic: bool,
+}
+
+impl Word {
+    fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    fn at(&self, i: isize) -> char {
+        if i < 0 {
+            return '\0';
+        }
+        self.chars.get(i as usize).copied().unwrap_or('\0')
+    }
+
+    fn vowel(&self, i: isize) -> bool {
+        matches!(self.at(i), 'A' | 'E' | 'I' | 'O' | 'U' | 'Y')
+    }
+
+    /// Returns `true` if the substring at `start` equals any of the candidates, which must share one length.
+    fn has(&self, start: isize, candidates: &[&str]) -> bool {
+        let Some(first) = candidates.first() else {
+            return false;
+        };
+        let length = first.chars().count() as isize;
+        if start < 0 || start + length > self.len() as isize {
+            return false;
+        }
+        let slice = &self.chars[start as usize..(start + length) as usize];
+        candidates.iter().any(|c| c.chars().eq(slice.iter().copied()))
+    }
+
+    fn last(&self) -> isize {
+        self.len() as isize - 1
+    }
+}
+
+/// Encodes a string with Lawrence Philips' Double Metaphone algorithm.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the word to encode.
+///
+/// # Returns
+///
+/// * A `(String, String)` containing the primary and alternate keys, each at most four characters.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::phonetic::double_metaphone("Smith");
+/// assert_eq!(result, ("SM0".to_string(), "XMT".to_string()));
+/// ```
+pub fn double_metaphone(text: &str) -> (String, String) {
+    let chars: Vec<char> = text.trim().to_uppercase().chars().collect();
+    let upper: String = chars.iter().collect();
+    let word = Word {
+        slavo_germanic: ["W", "K", "CZ", "WITZ"].iter().any(|p| upper.contains(p)),
+        chars,
+    };
+    let mut keys = DoubleMetaphoneKeys {
+        primary: String::new(),
+        alternate: String::new(),
+    };
+    if word.len() == 0 {
+        return (keys.primary, keys.alternate);
+    }
+
+    let mut i: isize = if word.has(0, &["GN", "KN", "PN", "WR", "PS"]) { 1 } else { 0 };
+    while !keys.is_complete() && i <= word.last() {
+        i = match word.at(i) {
+            'A' | 'E' | 'I' | 'O' | 'U' | 'Y' => {
+                if i == 0 {
+                    keys.both("A");
+                }
+                i + 1
+            }
+            'B' => {
+                keys.both("P");
+                if word.at(i + 1) == 'B' { i + 2 } else { i + 1 }
+            }
+            'Ç' => {
+                keys.both("S");
+                i + 1
+            }
+            'C' => dm_c(&word, &mut keys, i),
+            'D' => {
+                if word.has(i, &["DG"]) {
+                    if word.has(i + 2, &["I", "E", "Y"]) {
+                        keys.both("J");
+                        i + 3
+                    } else {
+                        keys.both("TK");
+                        i + 2
+                    }
+                } else if word.has(i, &["DT", "DD"]) {
+                    keys.both("T");
+                    i + 2
+                } else {
+                    keys.both("T");
+                    i + 1
+                }
+            }
+            'F' => {
+                keys.both("F");
+                if word.at(i + 1) == 'F' { i + 2 } else { i + 1 }
+            }
+            'G' => dm_g(&word, &mut keys, i),
+            'H' => {
+                if (i == 0 || word.vowel(i - 1)) && word.vowel(i + 1) {
+                    keys.both("H");
+                    i + 2
+                } else {
+                    i + 1
+                }
+            }
+            'J' => dm_j(&word, &mut keys, i),
+            'K' => {
+                keys.both("K");
+                if word.at(i + 1) == 'K' { i + 2 } else { i + 1 }
+            }
+            'L' => {
+                if word.at(i + 1) == 'L' {
+                    let spanish = (i == word.len() as isize - 3 && word.has(i - 1, &["ILLO", "ILLA", "ALLE"]))
+                        || ((word.has(word.len() as isize - 2, &["AS", "OS"]) || word.has(word.last(), &["A", "O"]))
+                            && word.has(i - 1, &["ALLE"]));
+                    if spanish {
+                        keys.primary("L");
+                    } else {
+                        keys.both("L");
+                    }
+                    i + 2
+                } else {
+                    keys.both("L");
+                    i + 1
+                }
+            }
+            'M' => {
+                keys.both("M");
+                let silent_b = word.has(i - 1, &["UMB"]) && (i + 1 == word.last() || word.has(i + 2, &["ER"]));
+                if word.at(i + 1) == 'M' || silent_b { i + 2 } else { i + 1 }
+            }
+            'N' => {
+                keys.both("N");
+                if word.at(i + 1) == 'N' { i + 2 } else { i + 1 }
+            }
+            'Ñ' => {
+                keys.both("N");
+                i + 1
+            }
+            'P' => {
+                if word.at(i + 1) == 'H' {
+                    keys.both("F");
+                    i + 2
+                } else {
+                    keys.both("P");
+                    if word.has(i + 1, &["P", "B"]) { i + 2 } else { i + 1 }
+                }
+            }
+            'Q' => {
+                keys.both("K");
+                if word.at(i + 1) == 'Q' { i + 2 } else { i + 1 }
+            }
+            'R' => {
+                // French words such as "Rogier" drop the final R in the primary key.
+                if i == word.last() && !word.slavo_germanic && word.has(i - 2, &["IE"]) && !word.has(i - 4, &["ME", "MA"]) {
+                    keys.alternate("R");
+                } else {
+                    keys.both("R");
+                }
+                if word.at(i + 1) == 'R' { i + 2 } else { i + 1 }
+            }
+            'S' => dm_s(&word, &mut keys, i),
+            'T' => dm_t(&word, &mut keys, i),
+            'V' => {
+                keys.both("F");
+                if word.at(i + 1) == 'V' { i + 2 } else { i + 1 }
+            }
+            'W' => dm_w(&word, &mut keys, i),
+            'X' => {
+                if i == 0 {
+                    keys.both("S");
+                    i + 1
+                } else {
+                    let french = i == word.last() && (word.has(i - 3, &["IAU", "EAU"]) || word.has(i - 2, &["AU", "OU"]));
+                    if !french {
+                        keys.both("KS");
+                    }
+                    if word.has(i + 1, &["C", "X"]) { i + 2 } else { i + 1 }
+                }
+            }
+            'Z' => {
+                if word.at(i + 1) == 'H' {
+                    keys.both("J");
+                    i + 2
+                } else {
+                    if word.has(i + 1, &["ZO", "ZI", "ZA"]) || (word.slavo_germanic && i > 0 && word.at(i - 1) != 'T') {
+                        keys.split("S", "TS");
+                    } else {
+                        keys.both("S");
+                    }
+                    if word.at(i + 1) == 'Z' { i + 2 } else { i + 1 }
+                }
+            }
+            _ => i + 1,
+        };
+    }
+    (keys.primary, keys.alternate)
+}
+
+fn dm_c(word: &Word, keys: &mut DoubleMetaphoneKeys, i: isize) -> isize {
+    // Germanic "ACH" as in "Bacher", "Macher".
+    let germanic_ach = word.has(i, &["CHIA"])
+        || (i > 1
+            && !word.vowel(i - 2)
+            && word.has(i - 1, &["ACH"])
+            && ((word.at(i + 2) != 'I' && word.at(i + 2) != 'E') || word.has(i - 2, &["BACHER", "MACHER"])));
+
+    if germanic_ach {
+        keys.both("K");
+        i + 2
+    } else if i == 0 && word.has(i, &["CAESAR"]) {
+        keys.both("S");
+        i + 2
+    } else if word.has(i, &["CH"]) {
+        dm_ch(word, keys, i)
+    } else if word.has(i, &["CZ"]) && !word.has(i - 2, &["WICZ"]) {
+        keys.split("S", "X");
+        i + 2
+    } else if word.has(i + 1, &["CIA"]) {
+        keys.both("X");
+        i + 3
+    } else if word.has(i, &["CC"]) && !(i == 1 && word.at(0) == 'M') {
+        if word.has(i + 2, &["I", "E", "H"]) && !word.has(i + 2, &["HU"]) {
+            if (i == 1 && word.at(i - 1) == 'A') || word.has(i - 1, &["UCCEE", "UCCES"]) {
+                keys.both("KS");
+            } else {
+                keys.both("X");
+            }
+            i + 3
+        } else {
+            keys.both("K");
+            i + 2
+        }
+    } else if word.has(i, &["CK", "CG", "CQ"]) {
+        keys.both("K");
+        i + 2
+    } else if word.has(i, &["CI", "CE", "CY"]) {
+        if word.has(i, &["CIO", "CIE", "CIA"]) {
+            keys.split("S", "X");
+        } else {
+            keys.both("S");
+        }
+        i + 2
+    } else {
+        keys.both("K");
+        if word.has(i + 1, &[" C", " Q", " G"]) {
+            i + 3
+        } else if word.has(i + 1, &["C", "K", "Q"]) && !word.has(i + 1, &["CE", "CI"]) {
+            i + 2
+        } else {
+            i + 1
+        }
+    }
+}
+
+fn dm_ch(word: &Word, keys: &mut DoubleMetaphoneKeys, i: isize) -> isize {
+    let greek_start = i == 0
+        && (word.has(i + 1, &["HARAC", "HARIS"]) || word.has(i + 1, &["HOR", "HYM", "HIA", "HEM"]))
+        && !word.has(0, &["CHORE"]);
+    let germanic = word.has(0, &["VAN ", "VON "])
+        || word.has(0, &["SCH"])
+        || word.has(i - 2, &["ORCHES", "ARCHIT", "ORCHID"])
+        || word.has(i + 2, &["T", "S"])
+        || ((word.has(i - 1, &["A", "O", "U", "E"]) || i == 0)
+            && (word.has(i + 2, &["L", "R", "N", "M", "B", "H", "F", "V", "W", " "]) || i + 1 == word.last()));
+
+    if i > 0 && word.has(i, &["CHAE"]) {
+        keys.split("K", "X");
+    } else if greek_start || germanic {
+        keys.both("K");
+    } else if i > 0 {
+        if word.has(0, &["MC"]) {
+            keys.both("K");
+        } else {
+            keys.split("X", "K");
+        }
+    } else {
+        keys.both("X");
+    }
+    i + 2
+}
+
+fn dm_g(word: &Word, keys: &mut DoubleMetaphoneKeys, i: isize) -> isize {
+    if word.at(i + 1) == 'H' {
+        if i > 0 && !word.vowel(i - 1) {
+            keys.both("K");
+        } else if i == 0 {
+            keys.both(if word.at(i + 2) == 'I' { "J" } else { "K" });
+        } else if (i > 1 && word.has(i - 2, &["B", "H", "D"]))
+            || (i > 2 && word.has(i - 3, &["B", "H", "D"]))
+            || (i > 3 && word.has(i - 4, &["B", "H"]))
+        {
+            // Parker's rule, as in "hugh".
+        } else if i > 2 && word.at(i - 1) == 'U' && word.has(i - 3, &["C", "G", "L", "R", "T"]) {
+            // "laugh", "cough", "tough".
+            keys.both("F");
+        } else if i > 0 && word.at(i - 1) != 'I' {
+            keys.both("K");
+        }
+        i + 2
+    } else if word.at(i + 1) == 'N' {
+        if i == 1 && word.vowel(0) && !word.slavo_germanic {
+            keys.split("KN", "N");
+        } else if !word.has(i + 2, &["EY"]) && word.at(i + 1) != 'Y' && !word.slavo_germanic {
+            keys.split("N", "KN");
+        } else {
+            keys.both("KN");
+        }
+        i + 2
+    } else if word.has(i + 1, &["LI"]) && !word.slavo_germanic {
+        keys.split("KL", "L");
+        i + 2
+    } else if (i == 0
+        && (word.at(i + 1) == 'Y'
+            || word.has(i + 1, &["ES", "EP", "EB", "EL", "EY", "IB", "IL", "IN", "IE", "EI", "ER"])))
+        || ((word.has(i + 1, &["ER"]) || word.at(i + 1) == 'Y')
+            && !word.has(0, &["DANGER", "RANGER", "MANGER"])
+            && !word.has(i - 1, &["E", "I"])
+            && !word.has(i - 1, &["RGY", "OGY"]))
+    {
+        keys.split("K", "J");
+        i + 2
+    } else if word.has(i + 1, &["E", "I", "Y"]) || word.has(i - 1, &["AGGI", "OGGI"]) {
+        if word.has(0, &["VAN ", "VON "]) || word.has(0, &["SCH"]) || word.has(i + 1, &["ET"]) {
+            keys.both("K");
+        } else if word.has(i + 1, &["IER"]) {
+            keys.both("J");
+        } else {
+            keys.split("J", "K");
+        }
+        i + 2
+    } else if word.at(i + 1) == 'G' {
+        keys.both("K");
+        i + 2
+    } else {
+        keys.both("K");
+        i + 1
+    }
+}
+
+fn dm_j(word: &Word, keys: &mut DoubleMetaphoneKeys, i: isize) -> isize {
+    if word.has(i, &["JOSE"]) || word.has(0, &["SAN "]) {
+        // Spanish, as in "Jose" or "San Jacinto".
+        if (i == 0 && word.at(i + 4) == ' ') || word.len() == 4 || word.has(0, &["SAN "]) {
+            keys.both("H");
+        } else {
+            keys.split("J", "H");
+        }
+        return i + 1;
+    }
+
+    if i == 0 {
+        keys.split("J", "A");
+    } else if word.vowel(i - 1) && !word.slavo_germanic && matches!(word.at(i + 1), 'A' | 'O') {
+        keys.split("J", "H");
+    } else if i == word.last() {
+        keys.primary("J");
+    } else if !word.has(i + 1, &["L", "T", "K", "S", "N", "M", "B", "Z"]) && !word.has(i - 1, &["S", "K", "L"]) {
+        keys.both("J");
+    }
+    if word.at(i + 1) == 'J' { i + 2 } else { i + 1 }
+}
+
+fn dm_s(word: &Word, keys: &mut DoubleMetaphoneKeys, i: isize) -> isize {
+    if word.has(i - 1, &["ISL", "YSL"]) {
+        // Silent, as in "island" or "carlisle".
+        i + 1
+    } else if i == 0 && word.has(i, &["SUGAR"]) {
+        keys.split("X", "S");
+        i + 1
+    } else if word.has(i, &["SH"]) {
+        if word.has(i + 1, &["HEIM", "HOEK", "HOLM", "HOLZ"]) {
+            keys.both("S");
+        } else {
+            keys.both("X");
+        }
+        i + 2
+    } else if word.has(i, &["SIO", "SIA"]) || word.has(i, &["SIAN"]) {
+        if word.slavo_germanic {
+            keys.both("S");
+        } else {
+            keys.split("S", "X");
+        }
+        i + 3
+    } else if (i == 0 && word.has(i + 1, &["M", "N", "L", "W"])) || word.has(i + 1, &["Z"]) {
+        keys.split("S", "X");
+        if word.has(i + 1, &["Z"]) { i + 2 } else { i + 1 }
+    } else if word.has(i, &["SC"]) {
+        if word.at(i + 2) == 'H' {
+            if word.has(i + 3, &["OO", "ER", "EN", "UY", "ED", "EM"]) {
+                if word.has(i + 3, &["ER", "EN"]) {
+                    keys.split("X", "SK");
+                } else {
+                    keys.both("SK");
+                }
+            } else if i == 0 && !word.vowel(3) && word.at(3) != 'W' {
+                keys.split("X", "S");
+            } else {
+                keys.both("X");
+            }
+        } else if word.has(i + 2, &["I", "E", "Y"]) {
+            keys.both("S");
+        } else {
+            keys.both("SK");
+        }
+        i + 3
+    } else {
+        // French, as in "Resnais" or "Artois".
+        if i == word.last() && word.has(i - 2, &["AI", "OI"]) {
+            keys.alternate("S");
+        } else {
+            keys.both("S");
+        }
+        if word.has(i + 1, &["S", "Z"]) { i + 2 } else { i + 1 }
+    }
+}
+
+fn dm_t(word: &Word, keys: &mut DoubleMetaphoneKeys, i: isize) -> isize {
+    if word.has(i, &["TION"]) || word.has(i, &["TIA", "TCH"]) {
+        keys.both("X");
+        i + 3
+    } else if word.has(i, &["TH"]) || word.has(i, &["TTH"]) {
+        if word.has(i + 2, &["OM", "AM"]) || word.has(0, &["VAN ", "VON "]) || word.has(0, &["SCH"]) {
+            keys.both("T");
+        } else {
+            keys.split("0", "T");
+        }
+        i + 2
+    } else {
+        keys.both("T");
+        if word.has(i + 1, &["T", "D"]) { i + 2 } else { i + 1 }
+    }
+}
+
+fn dm_w(word: &Word, keys: &mut DoubleMetaphoneKeys, i: isize) -> isize {
+    if word.has(i, &["WR"]) {
+        keys.both("R");
+        return i + 2;
+    }
+    if i == 0 && (word.vowel(i + 1) || word.has(i, &["WH"])) {
+        // "Wasserman" should match "Vasserman", "Womo" should match "Uomo".
+        if word.vowel(i + 1) {
+            keys.split("A", "F");
+        } else {
+            keys.both("A");
+        }
+        i + 1
+    } else if (i == word.last() && word.vowel(i - 1))
+        || word.has(i - 1, &["EWSKI", "EWSKY", "OWSKI", "OWSKY"])
+        || word.has(0, &["SCH"])
+    {
+        keys.alternate("F");
+        i + 1
+    } else if word.has(i, &["WICZ", "WITZ"]) {
+        keys.split("TS", "FX");
+        i + 4
+    } else {
+        i + 1
+    }
+}
+
+/// Encodes a string with the New York State Identification and Intelligence System algorithm.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the name to encode.
+///
+/// # Returns
+///
+/// * A `String` containing the NYSIIS code, truncated to six characters.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::phonetic::nysiis("Macintosh");
+/// assert_eq!(result, "MCANT");
+/// ```
+pub fn nysiis(text: &str) -> String {
+    let mut word: String = ascii_letters(text).into_iter().collect();
+    if word.is_empty() {
+        return word;
+    }
+
+    for (prefix, replacement) in [("MAC", "MCC"), ("KN", "NN"), ("K", "C"), ("PH", "FF"), ("PF", "FF"), ("SCH", "SSS")] {
+        if let Some(rest) = word.strip_prefix(prefix) {
+            word = format!("{}{}", replacement, rest);
+            break;
+        }
+    }
+    for (suffix, replacement) in [("EE", "Y"), ("IE", "Y"), ("DT", "D"), ("RT", "D"), ("RD", "D"), ("NT", "D"), ("ND", "D")] {
+        if let Some(rest) = word.strip_suffix(suffix) {
+            word = format!("{}{}", rest, replacement);
+            break;
+        }
+    }
+
+    let mut chars: Vec<char> = word.chars().collect();
+    let len = chars.len();
+    let mut key = vec![chars[0]];
+    for i in 1..len {
+        let prev = chars[i - 1];
+        let curr = chars[i];
+        let next = chars.get(i + 1).copied().unwrap_or(' ');
+        let after = chars.get(i + 2).copied().unwrap_or(' ');
+        let replacement: &[char] = match curr {
+            'E' if next == 'V' => &['A', 'F'],
+            c if is_vowel(c) => &['A'],
+            'Q' => &['G'],
+            'Z' => &['S'],
+            'M' => &['N'],
+            'K' if next == 'N' => &['N', 'N'],
+            'K' => &['C'],
+            'S' if next == 'C' && after == 'H' => &['S', 'S', 'S'],
+            'P' if next == 'H' => &['F', 'F'],
+            'H' if !is_vowel(prev) || !is_vowel(next) => &[prev],
+            'W' if is_vowel(prev) => &[prev],
+            _ => &[curr],
+        };
+        let replacement = replacement.to_vec();
+        chars[i..i + replacement.len()].copy_from_slice(&replacement);
+        if chars[i] != chars[i - 1] {
+            key.push(chars[i]);
+        }
+    }
+
+    if key.len() > 1 && key.last() == Some(&'S') {
+        key.pop();
+    }
+    if key.len() > 2 && key.ends_with(&['A', 'Y']) {
+        key.remove(key.len() - 2);
+    }
+    if key.len() > 1 && key.last() == Some(&'A') {
+        key.pop();
+    }
+    key.into_iter().take(6).collect()
+}
+
+/// Encodes a string with the Kölner Phonetik (Cologne phonetics), designed for German names.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the word to encode.
+///
+/// # Returns
+///
+/// * A `String` of digits.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::phonetic::cologne_phonetic("Müller-Lüdenscheidt");
+/// assert_eq!(result, "65752682");
+/// ```
+pub fn cologne_phonetic(text: &str) -> String {
+    let mut letters: Vec<char> = Vec::new();
+    for c in text.chars().flat_map(char::to_uppercase) {
+        match c {
+            'Ä' => letters.push('A'),
+            'Ö' => letters.push('O'),
+            'Ü' => letters.push('U'),
+            'ß' => letters.push('S'),
+            'A'..='Z' => letters.push(c),
+            _ => {}
+        }
+    }
+
+    let mut code = String::new();
+    let mut last_char = None;
+    let mut last_code = None;
+    for (i, &c) in letters.iter().enumerate() {
+        let next = letters.get(i + 1).copied().unwrap_or('\0');
+        let digits = match c {
+            'A' | 'E' | 'I' | 'J' | 'O' | 'U' | 'Y' => "0",
+            'H' => "",
+            'B' => "1",
+            'P' => if next == 'H' { "3" } else { "1" },
+            'D' | 'T' => if matches!(next, 'C' | 'S' | 'Z') { "8" } else { "2" },
+            'F' | 'V' | 'W' => "3",
+            'G' | 'K' | 'Q' => "4",
+            'C' => match last_char {
+                None if matches!(next, 'A' | 'H' | 'K' | 'L' | 'O' | 'Q' | 'R' | 'U' | 'X') => "4",
+                None => "8",
+                Some('S' | 'Z') => "8",
+                Some(_) if matches!(next, 'A' | 'H' | 'K' | 'O' | 'Q' | 'U' | 'X') => "4",
+                Some(_) => "8",
+            },
+            'X' => if matches!(last_char, Some('C' | 'K' | 'Q')) { "8" } else { "48" },
+            'L' => "5",
+            'M' | 'N' => "6",
+            'R' => "7",
+            _ => "8",
+        };
+
+        if digits.is_empty() {
+            // H is never coded; after the first letter it still separates repeated codes.
+            if last_char.is_none() {
+                continue;
+            }
+            last_code = Some('-');
+        }
+        for digit in digits.chars() {
+            if last_code != Some(digit) && (digit != '0' || last_code.is_none()) {
+                code.push(digit);
+            }
+            last_code = Some(digit);
+        }
+        last_char = Some(c);
+    }
+    code
+}
+
+/// Encodes a string with the chosen phonetic algorithm.
+///
+/// For [`PhoneticAlgorithm::DoubleMetaphone`] the primary key is returned.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the word to encode.
+/// * `algorithm` - The phonetic algorithm to use.
+///
+/// # Returns
+///
+/// * A `String` containing the phonetic key.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::phonetic::{phonetic_key, PhoneticAlgorithm};
+///
+/// let result = phonetic_key("Tymczak", PhoneticAlgorithm::Soundex);
+/// assert_eq!(result, "T522");
+/// ```
+pub fn phonetic_key(text: &str, algorithm: PhoneticAlgorithm) -> String {
+    match algorithm {
+        PhoneticAlgorithm::Soundex => soundex(text),
+        PhoneticAlgorithm::RefinedSoundex => refined_soundex(text),
+        PhoneticAlgorithm::Metaphone => metaphone(text),
+        PhoneticAlgorithm::DoubleMetaphone => double_metaphone(text).0,
+        PhoneticAlgorithm::Nysiis => nysiis(text),
+        PhoneticAlgorithm::Cologne => cologne_phonetic(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dm(text: &str) -> (String, String) {
+        double_metaphone(text)
+    }
+
+    #[test]
+    fn test_soundex() {
+        assert_eq!(soundex("Robert"), "R163");
+        assert_eq!(soundex("Rupert"), "R163");
+        assert_eq!(soundex("Ashcraft"), "A261");
+        assert_eq!(soundex("Tymczak"), "T522");
+        assert_eq!(soundex("Pfister"), "P236");
+        assert_eq!(soundex("Lee"), "L000");
+        assert_eq!(soundex(""), "");
+    }
+
+    #[test]
+    fn test_refined_soundex() {
+        assert_eq!(refined_soundex("testing"), "T6036084");
+        assert_eq!(refined_soundex("The"), "T60");
+        assert_eq!(refined_soundex("quick"), "Q503");
+        assert_eq!(refined_soundex("brown"), "B1908");
+    }
+
+    #[test]
+    fn test_metaphone() {
+        assert_eq!(metaphone("Thompson"), "0MPSN");
+        assert_eq!(metaphone("knight"), "NT");
+        assert_eq!(metaphone("Wright"), "RT");
+        assert_eq!(metaphone("Xavier"), "SFR");
+        assert_eq!(metaphone("school"), "SKL");
+        assert_eq!(metaphone("character"), "KRKTR");
+        assert_eq!(metaphone("Whistle"), "WSTL");
+        assert_eq!(metaphone("dumb"), "TM");
+    }
+
+    #[test]
+    fn test_double_metaphone() {
+        assert_eq!(dm("Smith"), ("SM0".to_string(), "XMT".to_string()));
+        assert_eq!(dm("Schmidt"), ("XMT".to_string(), "SMT".to_string()));
+        assert_eq!(dm("Thompson"), ("TMPS".to_string(), "TMPS".to_string()));
+        assert_eq!(dm("Gallegos"), ("KLKS".to_string(), "KKS".to_string()));
+        assert_eq!(dm("Xavier"), ("SF".to_string(), "SFR".to_string()));
+        assert_eq!(dm("Jose"), ("HS".to_string(), "HS".to_string()));
+        assert_eq!(dm("Caesar"), ("SSR".to_string(), "SSR".to_string()));
+        assert_eq!(dm("laugh"), ("LF".to_string(), "LF".to_string()));
+        assert_eq!(dm("Wasserman"), ("ASRM".to_string(), "FSRM".to_string()));
+        assert_eq!(dm(""), (String::new(), String::new()));
+    }
+
+    #[test]
+    fn test_nysiis() {
+        assert_eq!(nysiis("Macintosh"), "MCANT");
+        assert_eq!(nysiis("Bishop"), "BASAP");
+        assert_eq!(nysiis("Knuth"), "NAT");
+        assert_eq!(nysiis("Brown"), "BRAN");
+        assert_eq!(nysiis("Kelly"), "CALY");
+        assert_eq!(nysiis(""), "");
+    }
+
+    #[test]
+    fn test_cologne_phonetic() {
+        assert_eq!(cologne_phonetic("Müller-Lüdenscheidt"), "65752682");
+        assert_eq!(cologne_phonetic("Wikipedia"), "3412");
+        assert_eq!(cologne_phonetic("Breschnew"), "17863");
+        assert_eq!(cologne_phonetic("Meier"), "67");
+        assert_eq!(cologne_phonetic("Mayr"), "67");
+        assert_eq!(cologne_phonetic("Xaver"), "4837");
+        assert_eq!(cologne_phonetic("Heinz"), "068");
+    }
+
+    #[test]
+    fn test_phonetic_key() {
+        assert_eq!(phonetic_key("Tymczak", PhoneticAlgorithm::Soundex), "T522");
+        assert_eq!(phonetic_key("Smith", PhoneticAlgorithm::DoubleMetaphone), "SM0");
+    }
+}
@@ -1,5 +1,8 @@
-use regex::Regex;
+use regex::{Regex, RegexSet};
+use std::collections::hash_map::Entry;
 use std::collections::{HashMap, VecDeque};
+use std::ops::{Bound, RangeBounds};
+use std::sync::OnceLock;
 
 /// Finds the first occurrence of a pattern in the text and returns the captured group.
 ///
@@ -97,25 +100,8 @@ pub fn count_pattern(text: &str, pattern: &str) -> usize {
 pub fn kmp_search(text: &str, pattern: &str) -> Option<usize> {
     let text_bytes = text.as_bytes();
     let pattern_bytes = pattern.as_bytes();
-    let mut lps = vec![0; pattern_bytes.len()];
-    let mut j = 0;
-
     // Preprocess the pattern to compute the lps (longest prefix suffix) array
-    let mut i = 1;
-    while i < pattern_bytes.len() {
-        if pattern_bytes[i] == pattern_bytes[j] {
-            j += 1;
-            lps[i] = j;
-            i += 1;
-        } else {
-            if j != 0 {
-                j = lps[j - 1];
-            } else {
-                lps[i] = 0;
-                i += 1;
-            }
-        }
-    }
+    let lps = kmp_failure(pattern_bytes);
 
     // Search the pattern in the text
     let mut i = 0;
@@ -138,6 +124,67 @@ pub fn kmp_search(text: &str, pattern: &str) -> Option<usize> {
     None
 }
 
+// Computes the KMP failure table: for each `i`, the length of the longest proper
+// prefix of `pattern[..=i]` that is also a suffix of it.
+fn kmp_failure(pattern: &[u8]) -> Vec<usize> {
+    let mut lps = vec![0; pattern.len()];
+    let mut j = 0;
+    for i in 1..pattern.len() {
+        while j > 0 && pattern[i] != pattern[j] {
+            j = lps[j - 1];
+        }
+        if pattern[i] == pattern[j] {
+            j += 1;
+        }
+        lps[i] = j;
+    }
+    lps
+}
+
+/// Finds every occurrence of a substring using the Knuth-Morris-Pratt (KMP) algorithm.
+///
+/// Overlapping occurrences are all reported.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to search within.
+/// * `pattern` - A string slice that holds the substring to search for.
+///
+/// # Returns
+///
+/// * A `Vec<usize>` containing the starting byte indices of all occurrences, in increasing order. An empty pattern yields no matches.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::search::kmp_search_all("abababa", "aba");
+/// assert_eq!(result, vec![0, 2, 4]);
+/// ```
+pub fn kmp_search_all(text: &str, pattern: &str) -> Vec<usize> {
+    let text_bytes = text.as_bytes();
+    let pattern_bytes = pattern.as_bytes();
+    let m = pattern_bytes.len();
+    if m == 0 {
+        return Vec::new();
+    }
+    let lps = kmp_failure(pattern_bytes);
+    let mut results = Vec::new();
+    let mut j = 0;
+    for (i, &b) in text_bytes.iter().enumerate() {
+        while j > 0 && b != pattern_bytes[j] {
+            j = lps[j - 1];
+        }
+        if b == pattern_bytes[j] {
+            j += 1;
+        }
+        if j == m {
+            results.push(i + 1 - m);
+            j = lps[j - 1];
+        }
+    }
+    results
+}
+
 /// Finds the first occurrence of a substring using the Boyer-Moore algorithm.
 ///
 /// # Arguments
@@ -171,13 +218,13 @@ pub fn boyer_moore_search(text: &str, pattern: &str) -> Option<usize> {
 
     // Preprocess the pattern to compute the bad character heuristic
     for i in 0..m {
-        bad_char[pattern_bytes[i] as usize] = (m - 1 - i) as usize;
+        bad_char[pattern_bytes[i] as usize] = m - 1 - i;
     }
 
     let mut s = 0;
     while s <= (n - m) {
         let mut j = m - 1;
-        while j > 0 && pattern_bytes[j as usize] == text_bytes[s + j] {
+        while j > 0 && pattern_bytes[j] == text_bytes[s + j] {
             j -= 1;
         }
         if j == 0 {
@@ -238,6 +285,179 @@ pub fn boyer_moore_horspool_search(text: &str, pattern: &str) -> Option<usize> {
     None
 }
 
+/// Finds the last occurrence of a substring using the Knuth-Morris-Pratt (KMP) algorithm.
+///
+/// The text and the pattern are scanned right to left, so the search stops at the
+/// last occurrence instead of walking through every earlier one.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to search within.
+/// * `pattern` - A string slice that holds the substring to search for.
+///
+/// # Returns
+///
+/// * An `Option<usize>` containing the starting index of the last occurrence of the substring, or `None` if not found.
+///
+/// # Examples
+///
+/// ```
+/// let text = "path/to/some/file.txt";
+/// let result = loki_text::search::kmp_rsearch(text, "/");
+/// assert_eq!(result, Some(12));
+/// ```
+pub fn kmp_rsearch(text: &str, pattern: &str) -> Option<usize> {
+    let text_bytes = text.as_bytes();
+    let reversed: Vec<u8> = pattern.bytes().rev().collect();
+    let m = reversed.len();
+    if m == 0 || m > text_bytes.len() {
+        return None;
+    }
+    let lps = kmp_failure(&reversed);
+    let mut j = 0;
+    for (i, &b) in text_bytes.iter().enumerate().rev() {
+        while j > 0 && b != reversed[j] {
+            j = lps[j - 1];
+        }
+        if b == reversed[j] {
+            j += 1;
+        }
+        if j == m {
+            return Some(i);
+        }
+    }
+    None
+}
+
+/// Finds the last occurrence of a substring using a reverse Boyer-Moore-Horspool algorithm.
+///
+/// The window slides from the end of the text towards its start, shifting on the
+/// byte under the first position of the window.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to search within.
+/// * `pattern` - A string slice that holds the substring to search for.
+///
+/// # Returns
+///
+/// * An `Option<usize>` containing the starting index of the last occurrence of the substring, or `None` if not found.
+///
+/// # Examples
+///
+/// ```
+/// let text = "The quick brown fox jumps over the quick dog";
+/// let result = loki_text::search::boyer_moore_horspool_rsearch(text, "quick");
+/// assert_eq!(result, Some(35));
+/// ```
+pub fn boyer_moore_horspool_rsearch(text: &str, pattern: &str) -> Option<usize> {
+    let text_bytes = text.as_bytes();
+    let pattern_bytes = pattern.as_bytes();
+    let m = pattern_bytes.len();
+    let n = text_bytes.len();
+
+    if m == 0 || n == 0 || m > n {
+        return None;
+    }
+
+    // Distance from the start of the pattern to the first later occurrence of each byte.
+    let mut shift_table = vec![m; 256];
+    for i in (1..m).rev() {
+        shift_table[pattern_bytes[i] as usize] = i;
+    }
+
+    let mut s = n - m;
+    loop {
+        if &text_bytes[s..s + m] == pattern_bytes {
+            return Some(s);
+        }
+        let shift = shift_table[text_bytes[s] as usize];
+        if shift > s {
+            return None;
+        }
+        s -= shift;
+    }
+}
+
+// Resolves a range against `text`, checking that it is in bounds and that both
+// ends fall on char boundaries.
+fn resolve_range<R: RangeBounds<usize>>(text: &str, range: R) -> Result<(usize, usize), String> {
+    let start = match range.start_bound() {
+        Bound::Included(&s) => s,
+        Bound::Excluded(&s) => s.saturating_add(1),
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&e) => e.saturating_add(1),
+        Bound::Excluded(&e) => e,
+        Bound::Unbounded => text.len(),
+    };
+    if start > end {
+        return Err(format!("Range start {} is greater than range end {}", start, end));
+    }
+    if end > text.len() {
+        return Err(format!("Range end {} is out of bounds for text of length {}", end, text.len()));
+    }
+    for index in [start, end] {
+        if !text.is_char_boundary(index) {
+            return Err(format!("Index {} is not on a char boundary", index));
+        }
+    }
+    Ok((start, end))
+}
+
+/// Finds the first occurrence of a substring within a byte range of the text.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to search within.
+/// * `range` - The byte range of `text` to search, such as `10..`, `..20` or `4..=9`.
+/// * `pattern` - A string slice that holds the substring to search for.
+///
+/// # Returns
+///
+/// * A `Result<Option<usize>, String>` containing the absolute starting index of the first occurrence lying entirely inside the range, or an error if the range is out of bounds or does not fall on char boundaries.
+///
+/// # Examples
+///
+/// ```
+/// let text = "key=value; key=other";
+/// let result = loki_text::search::find_in(text, 5.., "key");
+/// assert_eq!(result, Ok(Some(11)));
+/// assert!(loki_text::search::find_in(text, 5..100, "key").is_err());
+/// ```
+pub fn find_in<R: RangeBounds<usize>>(text: &str, range: R, pattern: &str) -> Result<Option<usize>, String> {
+    let (start, end) = resolve_range(text, range)?;
+    if pattern.is_empty() {
+        return Ok(None);
+    }
+    Ok(kmp_search(&text[start..end], pattern).map(|offset| start + offset))
+}
+
+/// Finds the last occurrence of a substring within a byte range of the text.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to search within.
+/// * `range` - The byte range of `text` to search, such as `10..`, `..20` or `4..=9`.
+/// * `pattern` - A string slice that holds the substring to search for.
+///
+/// # Returns
+///
+/// * A `Result<Option<usize>, String>` containing the absolute starting index of the last occurrence lying entirely inside the range, or an error if the range is out of bounds or does not fall on char boundaries.
+///
+/// # Examples
+///
+/// ```
+/// let text = "a.b.c.d";
+/// let result = loki_text::search::rfind_in(text, ..5, ".");
+/// assert_eq!(result, Ok(Some(3)));
+/// ```
+pub fn rfind_in<R: RangeBounds<usize>>(text: &str, range: R, pattern: &str) -> Result<Option<usize>, String> {
+    let (start, end) = resolve_range(text, range)?;
+    Ok(boyer_moore_horspool_rsearch(&text[start..end], pattern).map(|offset| start + offset))
+}
+
 /// Finds the first occurrence of a substring using the Z algorithm.
 ///
 /// # Arguments
@@ -291,16 +511,11 @@ pub fn z_algorithm_search(text: &str, pattern: &str) -> Option<usize> {
         }
     }
 
-    for i in m..n {
-        if z[i] == m {
-            return Some(i - m);
-        }
-    }
-    None
+    (m..n).find(|&i| z[i] == m).map(|i| i - m)
 }
 
 #[derive(Default)]
-struct AhoCorasick {
+pub(crate) struct AhoCorasick {
     goto: HashMap<(usize, char), usize>,
     output: Vec<Vec<usize>>,
     fail: Vec<usize>,
@@ -308,7 +523,7 @@ struct AhoCorasick {
 }
 
 impl AhoCorasick {
-    fn new(patterns: Vec<&str>) -> Self {
+    pub(crate) fn new(patterns: Vec<&str>) -> Self {
         let mut ac = AhoCorasick::default();
         ac.build(patterns);
         ac
@@ -325,9 +540,9 @@ impl AhoCorasick {
         for (i, pattern) in patterns.iter().enumerate() {
             let mut current_state = 0;
             for c in pattern.chars() {
-                if !self.goto.contains_key(&(current_state, c)) {
+                if let Entry::Vacant(e) = self.goto.entry((current_state, c)) {
                     new_state += 1;
-                    self.goto.insert((current_state, c), new_state);
+                    e.insert(new_state);
                     // Ajouter un nouveau vecteur vide pour le nouvel état
                     self.output.push(vec![]);
                 }
@@ -346,35 +561,40 @@ impl AhoCorasick {
         }
         
         while let Some(state) = queue.pop_front() {
-            for (&(_, c), _) in self.goto.iter().filter(|(&(_, c), _)| c != '\0') {
-                if let Some(&next_state) = self.goto.get(&(state, c)) {
-                    let mut fail_state = self.fail[state];
-                    while !self.goto.contains_key(&(fail_state, c)) && fail_state != 0 {
-                        fail_state = self.fail[fail_state];
-                    }
-                    self.fail[next_state] = self.goto.get(&(fail_state, c)).copied().unwrap_or(0);
-                    
-                    let fail_outputs = self.output[self.fail[next_state]].clone();
-                    self.output[next_state].extend_from_slice(&fail_outputs);
-                    
-                    queue.push_back(next_state);
+            let transitions: Vec<(char, usize)> = self
+                .goto
+                .iter()
+                .filter(|(&(from, c), _)| from == state && c != '\0')
+                .map(|(&(_, c), &next)| (c, next))
+                .collect();
+            for (c, next_state) in transitions {
+                let mut fail_state = self.fail[state];
+                while !self.goto.contains_key(&(fail_state, c)) && fail_state != 0 {
+                    fail_state = self.fail[fail_state];
                 }
+                self.fail[next_state] = self.goto.get(&(fail_state, c)).copied().unwrap_or(0);
+                
+                let fail_outputs = self.output[self.fail[next_state]].clone();
+                self.output[next_state].extend_from_slice(&fail_outputs);
+                
+                queue.push_back(next_state);
             }
         }
     }
 
-    fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (usize, usize)> + 'a {
+    /// Yields `(byte_start, pattern_index)` for every match, ordered by end position.
+    pub(crate) fn find_iter<'a>(&'a self, text: &'a str) -> impl Iterator<Item = (usize, usize)> + 'a {
         let mut current_state = 0;
         let mut results = Vec::new();
         
-        for (i, c) in text.chars().enumerate() {
+        for (i, c) in text.char_indices() {
             while !self.goto.contains_key(&(current_state, c)) && current_state != 0 {
                 current_state = self.fail[current_state];
             }
             current_state = self.goto.get(&(current_state, c)).copied().unwrap_or(0);
             
             for &pattern_index in &self.output[current_state] {
-                let start = i + 1 - self.pattern_lengths[pattern_index];
+                let start = i + c.len_utf8() - self.pattern_lengths[pattern_index];
                 results.push((start, pattern_index));
             }
         }
@@ -474,6 +694,611 @@ pub fn rabin_karp_search(text: &str, pattern: &str) -> Option<usize> {
     None
 }
 
+/// A labelled regular expression used by [`RegexClassifier`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegexRule {
+    /// The label reported when the rule matches.
+    pub label: String,
+    /// The regex pattern.
+    pub pattern: String,
+    /// The rule priority; higher priorities are reported first.
+    pub priority: i32,
+}
+
+impl RegexRule {
+    /// Creates a rule from a label, a regex pattern and a priority.
+    pub fn new(label: &str, pattern: &str, priority: i32) -> Self {
+        RegexRule {
+            label: label.to_string(),
+            pattern: pattern.to_string(),
+            priority,
+        }
+    }
+}
+
+/// The capture groups of a rule that matched a text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleCaptures<'a> {
+    /// The rule that matched.
+    pub rule: &'a RegexRule,
+    /// The byte range `(start, end)` of the whole match.
+    pub span: (usize, usize),
+    /// The numbered capture groups, starting with group 1; `None` for groups that did not participate.
+    pub groups: Vec<Option<String>>,
+    /// The named capture groups that participated in the match.
+    pub named: Vec<(String, String)>,
+}
+
+/// Classifies texts against many regexes in a single pass.
+///
+/// All patterns are compiled once into a [`regex::RegexSet`]; individual
+/// regexes are only compiled and run when capture groups are requested, and
+/// only for the rules that matched.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::search::{RegexClassifier, RegexRule};
+///
+/// let classifier = RegexClassifier::new(vec![
+///     RegexRule::new("timeout", r"timed? ?out", 10),
+///     RegexRule::new("http-5xx", r"HTTP/\d\.\d\s5\d\d", 20),
+/// ]).unwrap();
+/// let labels: Vec<&str> = classifier
+///     .matching_rules("GET / HTTP/1.1 504 gateway timeout")
+///     .iter()
+///     .map(|r| r.label.as_str())
+///     .collect();
+/// assert_eq!(labels, vec!["http-5xx", "timeout"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RegexClassifier {
+    rules: Vec<RegexRule>,
+    set: RegexSet,
+    // Compiled on first use by `captures`; `None` if the pattern fails on its own.
+    regexes: Vec<OnceLock<Option<Regex>>>,
+}
+
+impl RegexClassifier {
+    /// Compiles the rules, failing on the first invalid pattern.
+    pub fn new(rules: Vec<RegexRule>) -> Result<Self, String> {
+        let set = RegexSet::new(rules.iter().map(|r| &r.pattern)).map_err(|set_error| {
+            // The set error does not say which pattern failed, so find it.
+            rules
+                .iter()
+                .find_map(|rule| {
+                    Regex::new(&rule.pattern)
+                        .err()
+                        .map(|e| format!("Invalid regex in rule '{}': {}", rule.label, e))
+                })
+                .unwrap_or_else(|| set_error.to_string())
+        })?;
+        let regexes = rules.iter().map(|_| OnceLock::new()).collect();
+        Ok(RegexClassifier { rules, set, regexes })
+    }
+
+    /// Returns the rules, in the order they were given.
+    pub fn rules(&self) -> &[RegexRule] {
+        &self.rules
+    }
+
+    /// Returns `true` if any rule matches the text.
+    pub fn is_match(&self, text: &str) -> bool {
+        self.set.is_match(text)
+    }
+
+    fn matching_indices(&self, text: &str) -> Vec<usize> {
+        let mut indices: Vec<usize> = self.set.matches(text).into_iter().collect();
+        // Stable sort keeps declaration order among equal priorities.
+        indices.sort_by_key(|&i| std::cmp::Reverse(self.rules[i].priority));
+        indices
+    }
+
+    /// Returns the rules matching the text, highest priority first.
+    ///
+    /// Rules with the same priority keep their declaration order.
+    pub fn matching_rules(&self, text: &str) -> Vec<&RegexRule> {
+        self.matching_indices(text).into_iter().map(|i| &self.rules[i]).collect()
+    }
+
+    /// Returns the highest-priority rule matching the text.
+    pub fn classify(&self, text: &str) -> Option<&RegexRule> {
+        self.matching_indices(text).first().map(|&i| &self.rules[i])
+    }
+
+    /// Returns the first match and its capture groups for every matching rule, highest priority first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::search::{RegexClassifier, RegexRule};
+    ///
+    /// let classifier = RegexClassifier::new(vec![
+    ///     RegexRule::new("latency", r"took (?P<ms>\d+)ms", 0),
+    ///     RegexRule::new("oom", r"out of memory", 0),
+    /// ]).unwrap();
+    /// let captures = classifier.captures("request took 1500ms");
+    /// assert_eq!(captures.len(), 1);
+    /// assert_eq!(captures[0].named, vec![("ms".to_string(), "1500".to_string())]);
+    /// ```
+    pub fn captures(&self, text: &str) -> Vec<RuleCaptures<'_>> {
+        self.matching_indices(text)
+            .into_iter()
+            .filter_map(|i| {
+                let re = self.regexes[i].get_or_init(|| Regex::new(&self.rules[i].pattern).ok()).as_ref()?;
+                let caps = re.captures(text)?;
+                let whole = caps.get(0)?;
+                let groups = caps
+                    .iter()
+                    .skip(1)
+                    .map(|m| m.map(|m| m.as_str().to_string()))
+                    .collect();
+                let named = re
+                    .capture_names()
+                    .flatten()
+                    .filter_map(|name| caps.name(name).map(|m| (name.to_string(), m.as_str().to_string())))
+                    .collect();
+                Some(RuleCaptures {
+                    rule: &self.rules[i],
+                    span: (whole.start(), whole.end()),
+                    groups,
+                    named,
+                })
+            })
+            .collect()
+    }
+}
+
+/// A parsed boolean text query, see [`parse_query`].
+#[derive(Debug, Clone)]
+pub enum Query {
+    /// A single word, matched case-insensitively on word boundaries.
+    Term(String),
+    /// A quoted phrase, matched case-insensitively on word boundaries.
+    Phrase(String),
+    /// A `/.../` regular expression literal.
+    Regex(Regex),
+    /// A word containing `*` (any run of word characters) or `?` (one word character).
+    Wildcard(String, Regex),
+    /// Both clauses must match.
+    And(Box<Query>, Box<Query>),
+    /// At least one clause must match.
+    Or(Box<Query>, Box<Query>),
+    /// The clause must not match.
+    Not(Box<Query>),
+    /// Both clauses must match with at most the given number of words between them.
+    Near(Box<Query>, Box<Query>, usize),
+}
+
+impl std::fmt::Display for Query {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Query::Term(t) => write!(f, "{}", t),
+            Query::Phrase(p) => write!(f, "\"{}\"", p.replace('"', "\\\"")),
+            Query::Regex(re) => write!(f, "/{}/", re.as_str().replace('/', "\\/")),
+            Query::Wildcard(w, _) => write!(f, "{}", w),
+            Query::And(a, b) => write!(f, "({} AND {})", a, b),
+            Query::Or(a, b) => write!(f, "({} OR {})", a, b),
+            Query::Not(q) => write!(f, "NOT {}", q),
+            Query::Near(a, b, n) => write!(f, "({} NEAR/{} {})", a, n, b),
+        }
+    }
+}
+
+/// The outcome of one clause of a query evaluated against a text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClauseMatch {
+    /// The clause, written in query syntax.
+    pub clause: String,
+    /// Whether the clause is satisfied.
+    pub matched: bool,
+    /// The byte ranges `(start, end)` of the text that satisfied the clause, in order.
+    pub spans: Vec<(usize, usize)>,
+}
+
+/// The outcome of evaluating a query against a text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryMatch {
+    /// Whether the whole query is satisfied.
+    pub matched: bool,
+    /// Every clause of the query in pre-order, starting with the query itself.
+    pub clauses: Vec<ClauseMatch>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum QueryToken {
+    Word(String),
+    Phrase(String),
+    Regex(String),
+    And,
+    Or,
+    Not,
+    Near(usize),
+    Open,
+    Close,
+}
+
+fn lex_query(input: &str) -> Result<Vec<(usize, QueryToken)>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some(&(pos, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' => {
+                chars.next();
+                tokens.push((pos, QueryToken::Open));
+            }
+            ')' => {
+                chars.next();
+                tokens.push((pos, QueryToken::Close));
+            }
+            '"' | '/' => {
+                chars.next();
+                let mut body = String::new();
+                let mut closed = false;
+                while let Some((_, ch)) = chars.next() {
+                    if ch == c {
+                        closed = true;
+                        break;
+                    }
+                    if ch == '\\' && chars.peek().map(|&(_, n)| n) == Some(c) {
+                        chars.next();
+                        body.push(c);
+                    } else {
+                        body.push(ch);
+                    }
+                }
+                if !closed {
+                    let kind = if c == '"' { "phrase" } else { "regex" };
+                    return Err(format!("Unterminated {} starting at position {}", kind, pos));
+                }
+                tokens.push((pos, if c == '"' { QueryToken::Phrase(body) } else { QueryToken::Regex(body) }));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&(_, ch)) = chars.peek() {
+                    if ch.is_whitespace() || ch == '(' || ch == ')' || ch == '"' {
+                        break;
+                    }
+                    word.push(ch);
+                    chars.next();
+                }
+                let token = match word.as_str() {
+                    "AND" => QueryToken::And,
+                    "OR" => QueryToken::Or,
+                    "NOT" => QueryToken::Not,
+                    w if w.starts_with("NEAR/") => {
+                        let distance = w[5..]
+                            .parse()
+                            .map_err(|_| format!("Invalid NEAR distance at position {}", pos))?;
+                        QueryToken::Near(distance)
+                    }
+                    _ => QueryToken::Word(word),
+                };
+                tokens.push((pos, token));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct QueryParser {
+    tokens: Vec<(usize, QueryToken)>,
+    pos: usize,
+    len: usize,
+}
+
+impl QueryParser {
+    fn peek(&self) -> Option<&QueryToken> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn position(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.len, |&(p, _)| p)
+    }
+
+    fn or_expr(&mut self) -> Result<Query, String> {
+        let mut left = self.and_expr()?;
+        while self.peek() == Some(&QueryToken::Or) {
+            self.pos += 1;
+            let right = self.and_expr()?;
+            left = Query::Or(Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn and_expr(&mut self) -> Result<Query, String> {
+        let mut left = self.near_expr()?;
+        loop {
+            match self.peek() {
+                Some(QueryToken::And) => self.pos += 1,
+                // Adjacent operands are implicitly joined with AND.
+                Some(QueryToken::Word(_) | QueryToken::Phrase(_) | QueryToken::Regex(_) | QueryToken::Not | QueryToken::Open) => {}
+                _ => return Ok(left),
+            }
+            let right = self.near_expr()?;
+            left = Query::And(Box::new(left), Box::new(right));
+        }
+    }
+
+    fn near_expr(&mut self) -> Result<Query, String> {
+        let mut left = self.unary()?;
+        while let Some(&QueryToken::Near(distance)) = self.peek() {
+            self.pos += 1;
+            let right = self.unary()?;
+            left = Query::Near(Box::new(left), Box::new(right), distance);
+        }
+        Ok(left)
+    }
+
+    fn unary(&mut self) -> Result<Query, String> {
+        let position = self.position();
+        let Some((_, token)) = self.tokens.get(self.pos).cloned() else {
+            return Err(format!("Unexpected end of query at position {}", position));
+        };
+        self.pos += 1;
+        match token {
+            QueryToken::Not => Ok(Query::Not(Box::new(self.unary()?))),
+            QueryToken::Open => {
+                let inner = self.or_expr()?;
+                if self.peek() != Some(&QueryToken::Close) {
+                    return Err(format!("Expected ')' at position {}", self.position()));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            QueryToken::Phrase(p) if !p.is_empty() => Ok(Query::Phrase(p)),
+            QueryToken::Regex(r) => Regex::new(&r)
+                .map(Query::Regex)
+                .map_err(|e| format!("Invalid regex at position {}: {}", position, e)),
+            QueryToken::Word(w) if w.contains(['*', '?']) => {
+                // Matched against the lowercased text, like terms and phrases.
+                let mut pattern = String::from(r"\b");
+                for c in w.chars() {
+                    match c {
+                        '*' => pattern.push_str(r"\w*"),
+                        '?' => pattern.push_str(r"\w"),
+                        _ => pattern.push_str(&regex::escape(&c.to_lowercase().to_string())),
+                    }
+                }
+                pattern.push_str(r"\b");
+                let re = Regex::new(&pattern).map_err(|e| format!("Invalid wildcard at position {}: {}", position, e))?;
+                Ok(Query::Wildcard(w, re))
+            }
+            QueryToken::Word(w) => Ok(Query::Term(w)),
+            _ => Err(format!("Unexpected token at position {}", position)),
+        }
+    }
+}
+
+/// Parses a boolean text query.
+///
+/// The syntax supports bare terms, `"quoted phrases"`, `/regex/` literals,
+/// wildcards (`time*`, `h?llo`), `AND`, `OR`, `NOT`, parentheses and
+/// proximity (`a NEAR/5 b`). Adjacent clauses are joined with `AND`.
+/// Precedence from tightest to loosest is `NOT`, `NEAR`, `AND`, `OR`.
+///
+/// # Arguments
+///
+/// * `input` - A string slice that holds the query.
+///
+/// # Returns
+///
+/// * A `Result<Query, String>` containing the parsed query or an error with its position.
+///
+/// # Examples
+///
+/// ```
+/// let query = loki_text::search::parse_query("error AND (timeout OR \"connection reset\") AND NOT debug").unwrap();
+/// assert_eq!(query.to_string(), "((error AND (timeout OR \"connection reset\")) AND NOT debug)");
+/// ```
+pub fn parse_query(input: &str) -> Result<Query, String> {
+    let mut parser = QueryParser {
+        tokens: lex_query(input)?,
+        pos: 0,
+        len: input.len(),
+    };
+    let query = parser.or_expr()?;
+    if parser.pos < parser.tokens.len() {
+        return Err(format!("Unexpected token at position {}", parser.position()));
+    }
+    Ok(query)
+}
+
+fn word_regex() -> &'static Regex {
+    static WORD: OnceLock<Regex> = OnceLock::new();
+    WORD.get_or_init(|| Regex::new(r"\w+").expect("valid word regex"))
+}
+
+/// Whether a character counts as `\w` for the regex crate, so terms and wildcards agree on word boundaries.
+fn is_word_char(c: char) -> bool {
+    let mut buf = [0; 4];
+    word_regex().is_match(c.encode_utf8(&mut buf))
+}
+
+/// Lowercased text for case-insensitive matching, mapping each byte back to the original text.
+struct Folded {
+    text: String,
+    origin: Vec<usize>,
+}
+
+impl Folded {
+    fn new(text: &str) -> Self {
+        let mut folded = String::with_capacity(text.len());
+        let mut origin = Vec::with_capacity(text.len() + 1);
+        for (i, c) in text.char_indices() {
+            for lower in c.to_lowercase() {
+                folded.push(lower);
+                origin.resize(folded.len(), i);
+            }
+        }
+        origin.push(text.len());
+        Folded { text: folded, origin }
+    }
+
+    /// Maps a span of the folded text to the original text.
+    fn original(&self, (start, end): (usize, usize)) -> (usize, usize) {
+        (self.origin[start], self.origin[end])
+    }
+}
+
+/// Finds every whole-word, case-insensitive occurrence of a literal using [`kmp_search`].
+fn find_literal_words(folded: &Folded, needle: &str) -> Vec<(usize, usize)> {
+    let needle = needle.to_lowercase();
+    let text = folded.text.as_str();
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    while offset < text.len() {
+        let Some(found) = kmp_search(&text[offset..], &needle) else {
+            break;
+        };
+        let start = offset + found;
+        let end = start + needle.len();
+        let is_word = |c: Option<char>| c.is_some_and(is_word_char);
+        let starts_word = !is_word(text[..start].chars().next_back()) || !is_word(text[start..].chars().next());
+        let ends_word = !is_word(text[end..].chars().next()) || !is_word(text[..end].chars().next_back());
+        if starts_word && ends_word {
+            spans.push(folded.original((start, end)));
+        }
+        offset = start + text[start..].chars().next().map_or(1, char::len_utf8);
+    }
+    spans
+}
+
+/// Maps a byte span to the indices of the first and last words it touches.
+fn word_range(words: &[(usize, usize)], (start, end): (usize, usize)) -> (usize, usize) {
+    let first = words.partition_point(|&(_, e)| e <= start);
+    let last = words.partition_point(|&(s, _)| s < end).max(first + 1) - 1;
+    (first, last)
+}
+
+impl Query {
+    fn evaluate(&self, text: &str, folded: &Folded, words: &[(usize, usize)], out: &mut Vec<ClauseMatch>) -> (bool, Vec<(usize, usize)>) {
+        let slot = out.len();
+        out.push(ClauseMatch {
+            clause: self.to_string(),
+            matched: false,
+            spans: Vec::new(),
+        });
+
+        let (matched, spans) = match self {
+            Query::Term(t) | Query::Phrase(t) => {
+                let spans = find_literal_words(folded, t);
+                (!spans.is_empty(), spans)
+            }
+            Query::Regex(re) => {
+                let spans: Vec<(usize, usize)> = re.find_iter(text).map(|m| (m.start(), m.end())).collect();
+                (!spans.is_empty(), spans)
+            }
+            Query::Wildcard(_, re) => {
+                let spans: Vec<(usize, usize)> = re
+                    .find_iter(&folded.text)
+                    .map(|m| folded.original((m.start(), m.end())))
+                    .collect();
+                (!spans.is_empty(), spans)
+            }
+            Query::And(a, b) | Query::Or(a, b) => {
+                let (ma, mut sa) = a.evaluate(text, folded, words, out);
+                let (mb, sb) = b.evaluate(text, folded, words, out);
+                let matched = if matches!(self, Query::And(..)) { ma && mb } else { ma || mb };
+                if matched {
+                    sa.extend(sb);
+                    sa.sort_unstable();
+                    sa.dedup();
+                } else {
+                    sa.clear();
+                }
+                (matched, sa)
+            }
+            Query::Not(q) => {
+                let (m, _) = q.evaluate(text, folded, words, out);
+                (!m, Vec::new())
+            }
+            Query::Near(a, b, distance) => {
+                let (_, sa) = a.evaluate(text, folded, words, out);
+                let (_, sb) = b.evaluate(text, folded, words, out);
+                let mut spans = Vec::new();
+                for &x in &sa {
+                    for &y in &sb {
+                        let (x_first, x_last) = word_range(words, x);
+                        let (y_first, y_last) = word_range(words, y);
+                        let between = if x_last < y_first {
+                            y_first - x_last - 1
+                        } else if y_last < x_first {
+                            x_first - y_last - 1
+                        } else {
+                            0
+                        };
+                        if between <= *distance {
+                            spans.push(x);
+                            spans.push(y);
+                        }
+                    }
+                }
+                spans.sort_unstable();
+                spans.dedup();
+                (!spans.is_empty(), spans)
+            }
+        };
+
+        out[slot].matched = matched;
+        out[slot].spans = spans.clone();
+        (matched, spans)
+    }
+}
+
+/// Evaluates a parsed query against a text.
+///
+/// # Arguments
+///
+/// * `query` - The query, for example from [`parse_query`].
+/// * `text` - A string slice that holds the text to match.
+///
+/// # Returns
+///
+/// * A `QueryMatch` with the overall result and, for every clause, whether and where it matched.
+///
+/// # Examples
+///
+/// ```
+/// let query = loki_text::search::parse_query("error AND NOT debug").unwrap();
+/// let result = loki_text::search::evaluate_query(&query, "ERROR: disk full");
+/// assert!(result.matched);
+/// assert_eq!(result.clauses[1].clause, "error");
+/// assert_eq!(result.clauses[1].spans, vec![(0, 5)]);
+/// ```
+pub fn evaluate_query(query: &Query, text: &str) -> QueryMatch {
+    let folded = Folded::new(text);
+    let words: Vec<(usize, usize)> = word_regex().find_iter(text).map(|m| (m.start(), m.end())).collect();
+    let mut clauses = Vec::new();
+    let (matched, _) = query.evaluate(text, &folded, &words, &mut clauses);
+    QueryMatch { matched, clauses }
+}
+
+/// Parses a query and checks whether a text satisfies it.
+///
+/// # Arguments
+///
+/// * `text` - A string slice that holds the text to match.
+/// * `query` - A string slice that holds the query, see [`parse_query`].
+///
+/// # Returns
+///
+/// * A `Result<bool, String>` containing whether the text matches, or the query parse error.
+///
+/// # Examples
+///
+/// ```
+/// let result = loki_text::search::matches_query("upstream timeout after 30s", "timeout NEAR/1 upstream");
+/// assert_eq!(result, Ok(true));
+/// ```
+pub fn matches_query(text: &str, query: &str) -> Result<bool, String> {
+    Ok(evaluate_query(&parse_query(query)?, text).matched)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -508,6 +1333,15 @@ mod tests {
         assert_eq!(result, Some(4));
     }
 
+    #[test]
+    fn test_kmp_search_all() {
+        assert_eq!(kmp_search_all("abababa", "aba"), vec![0, 2, 4]);
+        assert_eq!(kmp_search_all("aaaa", "aa"), vec![0, 1, 2]);
+        assert_eq!(kmp_search_all("héllo héllo", "llo"), vec![3, 10]);
+        assert!(kmp_search_all("abc", "").is_empty());
+        assert!(kmp_search_all("abc", "abcd").is_empty());
+    }
+
     #[test]
     fn test_boyer_moore_search() {
         let text = "The quick brown fox jumps over the lazy dog";
@@ -524,6 +1358,48 @@ mod tests {
         assert_eq!(result, Some(4));
     }
 
+    #[test]
+    fn test_kmp_rsearch() {
+        assert_eq!(kmp_rsearch("path/to/some/file.txt", "/"), Some(12));
+        assert_eq!(kmp_rsearch("aaaa", "aa"), Some(2));
+        assert_eq!(kmp_rsearch("abcabd", "abc"), Some(0));
+        assert_eq!(kmp_rsearch("héllo héllo", "é"), Some(8));
+        assert_eq!(kmp_rsearch("abc", "x"), None);
+        assert_eq!(kmp_rsearch("abc", ""), None);
+    }
+
+    #[test]
+    fn test_boyer_moore_horspool_rsearch() {
+        let text = "The quick brown fox jumps over the quick dog";
+        assert_eq!(boyer_moore_horspool_rsearch(text, "quick"), Some(35));
+        assert_eq!(boyer_moore_horspool_rsearch(text, "The"), Some(0));
+        assert_eq!(boyer_moore_horspool_rsearch("aaaa", "aa"), Some(2));
+        assert_eq!(boyer_moore_horspool_rsearch("abcabcab", "cab"), Some(5));
+        assert_eq!(boyer_moore_horspool_rsearch(text, "cat"), None);
+        assert_eq!(boyer_moore_horspool_rsearch("ab", "abc"), None);
+    }
+
+    #[test]
+    fn test_find_in() {
+        let text = "key=value; key=other";
+        assert_eq!(find_in(text, .., "key"), Ok(Some(0)));
+        assert_eq!(find_in(text, 5.., "key"), Ok(Some(11)));
+        assert_eq!(find_in(text, 5..13, "key"), Ok(None));
+        assert_eq!(find_in(text, 11..=13, "key"), Ok(Some(11)));
+        assert!(find_in(text, 5..100, "key").unwrap_err().contains("out of bounds"));
+        assert!(find_in(text, (Bound::Included(6), Bound::Excluded(5)), "key").is_err());
+        assert!(find_in("héllo", 2.., "l").unwrap_err().contains("char boundary"));
+    }
+
+    #[test]
+    fn test_rfind_in() {
+        let text = "a.b.c.d";
+        assert_eq!(rfind_in(text, .., "."), Ok(Some(5)));
+        assert_eq!(rfind_in(text, ..5, "."), Ok(Some(3)));
+        assert_eq!(rfind_in(text, 2..3, "."), Ok(None));
+        assert!(rfind_in("héllo", ..2, "h").is_err());
+    }
+
     #[test]
     fn test_z_algorithm_search() {
         let text = "The quick brown fox jumps over the lazy dog";
@@ -538,6 +1414,10 @@ mod tests {
         let patterns = vec!["quick", "fox", "dog"];
         let result = aho_corasick_search(text, patterns);
         assert_eq!(result, vec![(4, "quick"), (16, "fox"), (40, "dog")]);
+
+        // Offsets are byte offsets, also after multi-byte characters.
+        let result = aho_corasick_search("café crème", vec!["crème"]);
+        assert_eq!(result, vec![(6, "crème")]);
     }
 
     #[test]
@@ -548,5 +1428,80 @@ mod tests {
         assert_eq!(result, Some(4));
     }
 
-}
+    #[test]
+    fn test_parse_query() {
+        let query = parse_query("error AND (timeout OR \"connection reset\") AND NOT debug").unwrap();
+        assert_eq!(query.to_string(), "((error AND (timeout OR \"connection reset\")) AND NOT debug)");
+        let query = parse_query("a b OR c NEAR/3 /d+/").unwrap();
+        assert_eq!(query.to_string(), "((a AND b) OR (c NEAR/3 /d+/))");
+        assert_eq!(parse_query("(a OR b").unwrap_err(), "Expected ')' at position 7");
+        assert_eq!(parse_query("\"open").unwrap_err(), "Unterminated phrase starting at position 0");
+        assert!(parse_query("a AND").is_err());
+        assert!(parse_query("/(/").is_err());
+        assert!(parse_query("a NEAR/x b").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_query() {
+        let query = parse_query("error AND (timeout OR \"connection reset\") AND NOT debug").unwrap();
+        let result = evaluate_query(&query, "ERROR: Connection reset by peer");
+        assert!(result.matched);
+        let phrase = result.clauses.iter().find(|c| c.clause == "\"connection reset\"").unwrap();
+        assert_eq!(phrase.spans, vec![(7, 23)]);
+        let timeout = result.clauses.iter().find(|c| c.clause == "timeout").unwrap();
+        assert!(!timeout.matched);
+
+        assert!(!evaluate_query(&query, "debug: error timeout").matched);
+        assert!(!evaluate_query(&query, "errors timeout").matched);
+    }
 
+    #[test]
+    fn test_matches_query() {
+        assert_eq!(matches_query("upstream timeout after 30s", "timeout NEAR/1 upstream"), Ok(true));
+        assert_eq!(matches_query("upstream was slow, then a timeout", "timeout NEAR/1 upstream"), Ok(false));
+        assert_eq!(matches_query("request took 1500ms", "/\\d+ms/ AND req*"), Ok(true));
+        assert_eq!(matches_query("hello", "h?llo"), Ok(true));
+        assert_eq!(matches_query("hallway", "h?llo"), Ok(false));
+        assert_eq!(matches_query("naïve approach", "na"), Ok(false));
+        assert_eq!(matches_query("naïve approach", "naïve"), Ok(true));
+        assert_eq!(matches_query("ÉTÉ", "été"), Ok(true));
+        assert_eq!(matches_query("ÉTÉ", "ét*"), Ok(true));
+        assert_eq!(matches_query("Straße", "STRASSE"), Ok(false));
+        assert_eq!(matches_query("c++ rocks", "c++"), Ok(true));
+        let query = parse_query("\"grüße aus\" AND kö*").unwrap();
+        let result = evaluate_query(&query, "Viele GRÜSSE, Grüße aus KÖLN");
+        assert_eq!(result.clauses[1].spans, vec![(15, 26)]);
+        assert_eq!(result.clauses[2].spans, vec![(27, 32)]);
+    }
+
+    #[test]
+    fn test_regex_classifier() {
+        let classifier = RegexClassifier::new(vec![
+            RegexRule::new("timeout", r"(?i)timed? ?out", 10),
+            RegexRule::new("http-5xx", r"HTTP/\d\.\d\s(5\d\d)", 20),
+            RegexRule::new("gateway", r"gateway", 10),
+            RegexRule::new("oom", r"out of memory", 30),
+        ])
+        .unwrap();
+        let line = "GET / HTTP/1.1 504 gateway timeout";
+        assert!(classifier.is_match(line));
+        let labels: Vec<&str> = classifier.matching_rules(line).iter().map(|r| r.label.as_str()).collect();
+        assert_eq!(labels, vec!["http-5xx", "timeout", "gateway"]);
+        assert_eq!(classifier.classify(line).map(|r| r.label.as_str()), Some("http-5xx"));
+        assert_eq!(classifier.classify("all good"), None);
+        assert!(classifier.regexes.iter().all(|re| re.get().is_none()));
+
+        let captures = classifier.captures(line);
+        assert_eq!(captures.len(), 3);
+        // Only the matching rules were compiled on their own.
+        assert_eq!(classifier.regexes.iter().filter(|re| re.get().is_some()).count(), 3);
+        assert_eq!(captures[0].groups, vec![Some("504".to_string())]);
+        assert_eq!(captures[0].span, (6, 18));
+    }
+
+    #[test]
+    fn test_regex_classifier_invalid() {
+        let result = RegexClassifier::new(vec![RegexRule::new("broken", r"(", 0)]);
+        assert!(result.unwrap_err().starts_with("Invalid regex in rule 'broken'"));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::phonetic::{double_metaphone, phonetic_key, PhoneticAlgorithm};
 use regex::Regex;
 
 /// Reverses a string.
@@ -43,6 +44,41 @@ pub fn is_palindrome(text: &str) -> bool {
     cleaned.eq_ignore_ascii_case(&cleaned.chars().rev().collect::<String>())
 }
 
+/// Checks if two strings sound alike according to a phonetic algorithm.
+///
+/// With [`PhoneticAlgorithm::DoubleMetaphone`], the strings match when any of
+/// their primary or alternate keys agree. Strings without a phonetic key never match.
+///
+/// # Arguments
+///
+/// * `a` - A string slice that holds the first word.
+/// * `b` - A string slice that holds the second word.
+/// * `algorithm` - The phonetic algorithm used to compare the words.
+///
+/// # Returns
+///
+/// * A `bool` indicating whether the words share a phonetic key.
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::phonetic::PhoneticAlgorithm;
+///
+/// let result = loki_text::transform::sounds_like("Robert", "Rupert", PhoneticAlgorithm::Soundex);
+/// assert_eq!(result, true);
+/// ```
+pub fn sounds_like(a: &str, b: &str, algorithm: PhoneticAlgorithm) -> bool {
+    if algorithm == PhoneticAlgorithm::DoubleMetaphone {
+        let (a1, a2) = double_metaphone(a);
+        let (b1, b2) = double_metaphone(b);
+        return [&a1, &a2]
+            .iter()
+            .any(|k| !k.is_empty() && (**k == b1 || **k == b2));
+    }
+    let key = phonetic_key(a, algorithm);
+    !key.is_empty() && key == phonetic_key(b, algorithm)
+}
+
 /// Removes punctuation from a string.
 ///
 /// # Arguments
@@ -174,10 +210,11 @@ pub fn to_snake_case(text: &str) -> String {
     let mut result = String::new();
     
     for (i, c) in text.chars().enumerate() {
-        if i > 0 && (c.is_uppercase() || c.is_whitespace() || c == '-') {
-            if !result.ends_with('_') {
-                result.push('_');
-            }
+        if i > 0
+            && (c.is_uppercase() || c.is_whitespace() || c == '-')
+            && !result.ends_with('_')
+        {
+            result.push('_');
         }
         
         if !c.is_whitespace() && c != '-' {
@@ -209,10 +246,11 @@ pub fn to_kebab_case(text: &str) -> String {
     let mut result = String::new();
     
     for (i, c) in text.chars().enumerate() {
-        if i > 0 && (c.is_uppercase() || c.is_whitespace() || c == '_') {
-            if !result.ends_with('-') {
-                result.push('-');
-            }
+        if i > 0
+            && (c.is_uppercase() || c.is_whitespace() || c == '_')
+            && !result.ends_with('-')
+        {
+            result.push('-');
         }
         
         if !c.is_whitespace() && c != '_' {
@@ -610,6 +648,15 @@ mod tests {
         assert_eq!(is_palindrome(text), false);
     }
 
+    #[test]
+    fn test_sounds_like() {
+        assert!(sounds_like("Robert", "Rupert", PhoneticAlgorithm::Soundex));
+        assert!(!sounds_like("Robert", "Rubin", PhoneticAlgorithm::Soundex));
+        assert!(sounds_like("Meier", "Mayr", PhoneticAlgorithm::Cologne));
+        assert!(sounds_like("Schmidt", "Smith", PhoneticAlgorithm::DoubleMetaphone));
+        assert!(!sounds_like("", "", PhoneticAlgorithm::Nysiis));
+    }
+
     #[test]
     fn test_remove_punctuation() {
         let text = "Hello, world!";
@@ -0,0 +1,367 @@
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone)]
+struct Node<V> {
+    children: BTreeMap<char, Node<V>>,
+    entry: Option<(V, u64)>,
+}
+
+impl<V> Default for Node<V> {
+    fn default() -> Self {
+        Node {
+            children: BTreeMap::new(),
+            entry: None,
+        }
+    }
+}
+
+impl<V> Node<V> {
+    /// Collects every key below this node in sorted order, prefixed with `path`.
+    fn collect<'a>(&'a self, path: &mut String, out: &mut Vec<(String, &'a V, u64)>) {
+        if let Some((value, weight)) = &self.entry {
+            out.push((path.clone(), value, *weight));
+        }
+        for (&c, child) in &self.children {
+            path.push(c);
+            child.collect(path, out);
+            path.pop();
+        }
+    }
+
+    /// Removes the key spelled by `chars`, pruning nodes left without keys.
+    fn remove(&mut self, chars: &[char]) -> Option<V> {
+        match chars.split_first() {
+            None => self.entry.take().map(|(value, _)| value),
+            Some((c, rest)) => {
+                let child = self.children.get_mut(c)?;
+                let removed = child.remove(rest);
+                if child.entry.is_none() && child.children.is_empty() {
+                    self.children.remove(c);
+                }
+                removed
+            }
+        }
+    }
+}
+
+/// A prefix tree mapping string keys to values.
+///
+/// Keys are stored character by character, so iteration is in lexicographic
+/// order of `char`s. Each key can carry a weight used to rank completions.
+/// For finding many fixed patterns inside a text, see
+/// [`aho_corasick_search`](crate::search::aho_corasick_search).
+///
+/// # Examples
+///
+/// ```
+/// use loki_text::trie::Trie;
+///
+/// let mut trie = Trie::new();
+/// trie.insert("status", 1);
+/// trie.insert("stash", 2);
+/// trie.insert("start", 3);
+/// assert_eq!(trie.keys_with_prefix("sta"), vec!["start", "stash", "status"]);
+/// assert_eq!(trie.get("stash"), Some(&2));
+/// ```
+#[derive(Debug, Clone)]
+pub struct Trie<V> {
+    root: Node<V>,
+    len: usize,
+}
+
+impl<V> Default for Trie<V> {
+    fn default() -> Self {
+        Trie {
+            root: Node::default(),
+            len: 0,
+        }
+    }
+}
+
+impl<V> Trie<V> {
+    /// Creates an empty trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the number of keys in the trie.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if the trie holds no keys.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    fn node(&self, prefix: &str) -> Option<&Node<V>> {
+        prefix.chars().try_fold(&self.root, |node, c| node.children.get(&c))
+    }
+
+    /// Inserts a key with a weight of zero, returning the previous value if the key existed.
+    pub fn insert(&mut self, key: &str, value: V) -> Option<V> {
+        self.insert_weighted(key, value, 0)
+    }
+
+    /// Inserts a key with a ranking weight, returning the previous value if the key existed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert_weighted("commit", (), 10);
+    /// trie.insert_weighted("config", (), 50);
+    /// let ranked: Vec<String> = trie.ranked_with_prefix("co", 10).into_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(ranked, vec!["config", "commit"]);
+    /// ```
+    pub fn insert_weighted(&mut self, key: &str, value: V, weight: u64) -> Option<V> {
+        let node = key
+            .chars()
+            .fold(&mut self.root, |node, c| node.children.entry(c).or_default());
+        let previous = node.entry.replace((value, weight)).map(|(v, _)| v);
+        if previous.is_none() {
+            self.len += 1;
+        }
+        previous
+    }
+
+    /// Returns a reference to the value stored under `key`.
+    pub fn get(&self, key: &str) -> Option<&V> {
+        self.node(key)?.entry.as_ref().map(|(v, _)| v)
+    }
+
+    /// Returns a mutable reference to the value stored under `key`.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut V> {
+        let node = key
+            .chars()
+            .try_fold(&mut self.root, |node, c| node.children.get_mut(&c))?;
+        node.entry.as_mut().map(|(v, _)| v)
+    }
+
+    /// Returns `true` if the trie contains `key`.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Removes a key, returning its value if it was present.
+    pub fn remove(&mut self, key: &str) -> Option<V> {
+        let chars: Vec<char> = key.chars().collect();
+        let removed = self.root.remove(&chars);
+        if removed.is_some() {
+            self.len -= 1;
+        }
+        removed
+    }
+
+    /// Returns every key and value in sorted key order.
+    pub fn entries(&self) -> Vec<(String, &V)> {
+        self.with_prefix("")
+    }
+
+    /// Returns the keys and values starting with `prefix`, in sorted key order.
+    pub fn with_prefix(&self, prefix: &str) -> Vec<(String, &V)> {
+        self.entries_with_prefix(prefix)
+            .into_iter()
+            .map(|(k, v, _)| (k, v))
+            .collect()
+    }
+
+    /// Returns the keys starting with `prefix`, in sorted order.
+    pub fn keys_with_prefix(&self, prefix: &str) -> Vec<String> {
+        self.entries_with_prefix(prefix)
+            .into_iter()
+            .map(|(k, _, _)| k)
+            .collect()
+    }
+
+    /// Returns at most `limit` keys starting with `prefix`, highest weight first.
+    ///
+    /// Keys of equal weight are returned in sorted order.
+    pub fn ranked_with_prefix(&self, prefix: &str, limit: usize) -> Vec<(String, &V)> {
+        let mut entries = self.entries_with_prefix(prefix);
+        entries.sort_by(|a, b| b.2.cmp(&a.2).then_with(|| a.0.cmp(&b.0)));
+        entries.into_iter().take(limit).map(|(k, v, _)| (k, v)).collect()
+    }
+
+    fn entries_with_prefix(&self, prefix: &str) -> Vec<(String, &V, u64)> {
+        let mut out = Vec::new();
+        if let Some(node) = self.node(prefix) {
+            node.collect(&mut prefix.to_string(), &mut out);
+        }
+        out
+    }
+
+    /// Finds the longest key that is a prefix of `text`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::trie::Trie;
+    ///
+    /// let mut routes = Trie::new();
+    /// routes.insert("/api", "api");
+    /// routes.insert("/api/users", "users");
+    /// assert_eq!(routes.longest_prefix_match("/api/users/42"), Some(("/api/users", &"users")));
+    /// assert_eq!(routes.longest_prefix_match("/static"), None);
+    /// ```
+    pub fn longest_prefix_match<'t>(&self, text: &'t str) -> Option<(&'t str, &V)> {
+        let mut node = &self.root;
+        let mut best = node.entry.as_ref().map(|(v, _)| (0, v));
+        for (i, c) in text.char_indices() {
+            match node.children.get(&c) {
+                Some(child) => node = child,
+                None => break,
+            }
+            if let Some((value, _)) = &node.entry {
+                best = Some((i + c.len_utf8(), value));
+            }
+        }
+        best.map(|(end, value)| (&text[..end], value))
+    }
+
+    /// Completes a possibly misspelled prefix.
+    ///
+    /// Returns every key that starts with some string within Levenshtein
+    /// distance `max_distance` of `prefix`, in sorted key order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use loki_text::trie::Trie;
+    ///
+    /// let mut trie = Trie::new();
+    /// trie.insert("checkout", ());
+    /// trie.insert("cherry-pick", ());
+    /// trie.insert("clone", ());
+    /// let keys: Vec<String> = trie.fuzzy_complete("chekc", 1).into_iter().map(|(k, _)| k).collect();
+    /// assert_eq!(keys, vec!["checkout"]);
+    /// ```
+    pub fn fuzzy_complete(&self, prefix: &str, max_distance: usize) -> Vec<(String, &V)> {
+        let target: Vec<char> = prefix.chars().collect();
+        let first_row: Vec<usize> = (0..=target.len()).collect();
+        let mut out = Vec::new();
+        if first_row[target.len()] <= max_distance {
+            self.root.collect(&mut String::new(), &mut out);
+        } else {
+            for (&c, child) in &self.root.children {
+                let mut path = c.to_string();
+                fuzzy_walk(child, c, &target, &first_row, max_distance, &mut path, &mut out);
+            }
+        }
+        out.into_iter().map(|(k, v, _)| (k, v)).collect()
+    }
+}
+
+/// Extends the edit-distance row by one trie edge and descends while within `max`.
+fn fuzzy_walk<'a, V>(
+    node: &'a Node<V>,
+    c: char,
+    target: &[char],
+    prev: &[usize],
+    max: usize,
+    path: &mut String,
+    out: &mut Vec<(String, &'a V, u64)>,
+) {
+    let mut row = vec![prev[0] + 1; target.len() + 1];
+    for j in 1..=target.len() {
+        let cost = usize::from(target[j - 1] != c);
+        row[j] = (prev[j] + 1).min(row[j - 1] + 1).min(prev[j - 1] + cost);
+    }
+
+    if row[target.len()] <= max {
+        // The path already matches the prefix, so every key below it completes it.
+        node.collect(path, out);
+        return;
+    }
+    if row.iter().min().is_some_and(|&m| m > max) {
+        return;
+    }
+    for (&next, child) in &node.children {
+        path.push(next);
+        fuzzy_walk(child, next, target, &row, max, path, out);
+        path.pop();
+    }
+}
+
+impl<K: AsRef<str>, V> FromIterator<(K, V)> for Trie<V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut trie = Trie::new();
+        for (key, value) in iter {
+            trie.insert(key.as_ref(), value);
+        }
+        trie
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_get() {
+        let mut trie = Trie::new();
+        assert_eq!(trie.insert("hello", 1), None);
+        assert_eq!(trie.insert("help", 2), None);
+        assert_eq!(trie.insert("hello", 3), Some(1));
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.get("hello"), Some(&3));
+        assert_eq!(trie.get("hel"), None);
+        *trie.get_mut("help").unwrap() += 10;
+        assert_eq!(trie.get("help"), Some(&12));
+        assert!(trie.contains_key("help"));
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut trie: Trie<i32> = [("a", 1), ("ab", 2), ("abc", 3)].into_iter().collect();
+        assert_eq!(trie.remove("ab"), Some(2));
+        assert_eq!(trie.remove("ab"), None);
+        assert_eq!(trie.len(), 2);
+        assert_eq!(trie.get("abc"), Some(&3));
+        assert_eq!(trie.remove("abc"), Some(3));
+        assert_eq!(trie.keys_with_prefix(""), vec!["a"]);
+        assert_eq!(trie.remove("a"), Some(1));
+        assert!(trie.is_empty());
+        assert!(trie.root.children.is_empty());
+    }
+
+    #[test]
+    fn test_keys_with_prefix() {
+        let trie: Trie<()> = ["status", "stash", "start", "commit"].iter().map(|k| (k, ())).collect();
+        assert_eq!(trie.keys_with_prefix("sta"), vec!["start", "stash", "status"]);
+        assert!(trie.keys_with_prefix("x").is_empty());
+        assert_eq!(trie.entries().len(), 4);
+    }
+
+    #[test]
+    fn test_ranked_with_prefix() {
+        let mut trie = Trie::new();
+        trie.insert_weighted("commit", 'a', 10);
+        trie.insert_weighted("config", 'b', 50);
+        trie.insert_weighted("clone", 'c', 10);
+        let ranked = trie.ranked_with_prefix("c", 2);
+        assert_eq!(ranked, vec![("config".to_string(), &'b'), ("clone".to_string(), &'c')]);
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let mut routes = Trie::new();
+        routes.insert("/api", 1);
+        routes.insert("/api/users", 2);
+        assert_eq!(routes.longest_prefix_match("/api/users/42"), Some(("/api/users", &2)));
+        assert_eq!(routes.longest_prefix_match("/api/items"), Some(("/api", &1)));
+        assert_eq!(routes.longest_prefix_match("/ap"), None);
+    }
+
+    #[test]
+    fn test_fuzzy_complete() {
+        let trie: Trie<()> = ["checkout", "cherry-pick", "clone", "commit"].iter().map(|k| (k, ())).collect();
+        let keys = |v: Vec<(String, &())>| v.into_iter().map(|(k, _)| k).collect::<Vec<_>>();
+        assert_eq!(keys(trie.fuzzy_complete("chekc", 1)), vec!["checkout"]);
+        assert_eq!(keys(trie.fuzzy_complete("cl", 0)), vec!["clone"]);
+        assert_eq!(keys(trie.fuzzy_complete("ch", 1)), vec!["checkout", "cherry-pick", "clone", "commit"]);
+        assert_eq!(keys(trie.fuzzy_complete("x", 1)).len(), 4);
+    }
+}
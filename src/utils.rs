@@ -1,3 +1,5 @@
+use crate::encoding::charset::{decode_with_bom, Charset, ErrorMode};
+
 /// Checks if a string is empty or contains only whitespace.
 ///
 /// # Arguments
@@ -63,6 +65,10 @@ pub fn to_byte_vector(text: &str) -> Vec<u8> {
 
 /// Converts a vector of bytes to a string.
 ///
+/// Bytes starting with a UTF-8, UTF-16 or UTF-32 byte order mark are decoded in
+/// that encoding with the mark removed; anything else is read as UTF-8. Invalid
+/// sequences become U+FFFD. Use [`crate::encoding::charset`] for other charsets.
+///
 /// # Arguments
 ///
 /// * `bytes` - A slice of bytes to convert.
@@ -77,9 +83,13 @@ pub fn to_byte_vector(text: &str) -> Vec<u8> {
 /// let bytes = vec![104, 101, 108, 108, 111];
 /// let result = loki_text::utils::to_string(&bytes);
 /// assert_eq!(result, "hello");
+/// assert_eq!(loki_text::utils::to_string(b"\xFF\xFEh\x00i\x00"), "hi");
 /// ```
 pub fn to_string(bytes: &[u8]) -> String {
-    String::from_utf8_lossy(bytes).to_string()
+    // Replace mode never fails.
+    decode_with_bom(bytes, Charset::Utf8, ErrorMode::Replace)
+        .map(|(text, _)| text)
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
@@ -110,6 +120,8 @@ mod tests {
     fn test_to_string() {
         let bytes = vec![104, 101, 108, 108, 111];
         assert_eq!(to_string(&bytes), "hello");
+        assert_eq!(to_string(b"\xEF\xBB\xBFcaf\xC3\xA9 \xFF"), "café \u{FFFD}");
+        assert_eq!(to_string(b"\xFE\xFF\x00h\x00i"), "hi");
     }
 }
 